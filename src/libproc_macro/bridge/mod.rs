@@ -17,7 +17,7 @@ use std::panic;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Once;
 use std::thread;
-use crate::{Delimiter, Level, LineColumn, Spacing};
+use crate::{Applicability, Delimiter, Level, LineColumn, LiteralKind, Spacing};
 
 /// Higher-order macro describing the server RPC API, allowing automatic
 /// generation of type-safe Rust APIs, both client-side and server-side.
@@ -52,6 +52,11 @@ use crate::{Delimiter, Level, LineColumn, Spacing};
 macro_rules! with_api {
     ($S:ident, $self:ident, $m:ident) => {
         $m! {
+            FreeFunctions {
+                fn drop($self: $S::FreeFunctions);
+                fn track_env_var(var: &str, value: Option<&str>);
+                fn track_path(path: &str);
+            },
             TokenStream {
                 fn drop($self: $S::TokenStream);
                 fn clone($self: &$S::TokenStream) -> $S::TokenStream;
@@ -59,6 +64,7 @@ macro_rules! with_api {
                 fn is_empty($self: &$S::TokenStream) -> bool;
                 fn from_str(src: &str) -> $S::TokenStream;
                 fn to_string($self: &$S::TokenStream) -> String;
+                fn expand_expr($self: &$S::TokenStream) -> Result<$S::TokenStream, ()>;
                 fn from_token_tree(
                     tree: TokenTree<$S::Group, $S::Punct, $S::Ident, $S::Literal>,
                 ) -> $S::TokenStream;
@@ -113,6 +119,15 @@ macro_rules! with_api {
                 fn string(string: &str) -> $S::Literal;
                 fn character(ch: char) -> $S::Literal;
                 fn byte_string(bytes: &[u8]) -> $S::Literal;
+                fn kind($self: &$S::Literal) -> LiteralKind;
+                fn suffix($self: &$S::Literal) -> Option<String>;
+                fn str_value($self: &$S::Literal) -> Option<String>;
+                fn byte_str_value($self: &$S::Literal) -> Option<Vec<u8>>;
+                fn char_value($self: &$S::Literal) -> Option<char>;
+                fn byte_value($self: &$S::Literal) -> Option<u8>;
+                fn int_value($self: &$S::Literal) -> Option<u128>;
+                // Floats are passed as text, to be parsed by the client.
+                fn float_value($self: &$S::Literal) -> Option<String>;
                 fn span($self: &$S::Literal) -> $S::Span;
                 fn set_span($self: &mut $S::Literal, span: $S::Span);
                 fn subspan(
@@ -142,6 +157,13 @@ macro_rules! with_api {
                     msg: &str,
                     span: $S::MultiSpan,
                 );
+                fn set_code($self: &mut $S::Diagnostic, code: &str);
+                fn suggestion(
+                    $self: &mut $S::Diagnostic,
+                    msg: &str,
+                    applicability: Applicability,
+                );
+                fn suggestion_part($self: &mut $S::Diagnostic, span: $S::Span, replacement: &str);
                 fn emit($self: $S::Diagnostic);
             },
             Span {
@@ -327,14 +349,19 @@ macro_rules! mark_noop {
 }
 mark_noop! {
     (),
+    Applicability,
     bool,
     char,
+    u8,
+    u128,
     &'a [u8],
     &'a str,
     String,
+    Vec<u8>,
     Delimiter,
     Level,
     LineColumn,
+    LiteralKind,
     Spacing,
     Bound<usize>,
 }
@@ -353,9 +380,31 @@ rpc_encode_decode!(
         Warning,
         Note,
         Help,
+        Lint,
+    }
+);
+rpc_encode_decode!(
+    enum Applicability {
+        MachineApplicable,
+        MaybeIncorrect,
+        HasPlaceholders,
+        Unspecified,
     }
 );
 rpc_encode_decode!(struct LineColumn { line, column });
+rpc_encode_decode!(
+    enum LiteralKind {
+        Byte,
+        Char,
+        Integer,
+        Float,
+        Str,
+        StrRaw(n),
+        ByteStr,
+        ByteStrRaw(n),
+        Err,
+    }
+);
 rpc_encode_decode!(
     enum Spacing {
         Alone,
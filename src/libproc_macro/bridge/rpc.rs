@@ -126,7 +126,9 @@ impl<S> DecodeMut<'_, '_, S> for u8 {
     }
 }
 
+rpc_encode_decode!(le u16);
 rpc_encode_decode!(le u32);
+rpc_encode_decode!(le u128);
 rpc_encode_decode!(le usize);
 
 impl<S> Encode<S> for bool {
@@ -246,6 +248,18 @@ impl<S> DecodeMut<'_, '_, S> for String {
     }
 }
 
+impl<S> Encode<S> for Vec<u8> {
+    fn encode(self, w: &mut Writer, s: &mut S) {
+        self[..].encode(w, s);
+    }
+}
+
+impl<S> DecodeMut<'_, '_, S> for Vec<u8> {
+    fn decode(r: &mut Reader<'_>, s: &mut S) -> Self {
+        <&[u8]>::decode(r, s).to_vec()
+    }
+}
+
 /// Simplied version of panic payloads, ignoring
 /// types other than `&'static str` and `String`.
 pub enum PanicMessage {
@@ -13,6 +13,29 @@ pub enum Level {
     Note,
     /// A help message.
     Help,
+    /// A warning reported through the `proc_macro_warnings` lint, which lint
+    /// attributes in scope of the macro invocation can silence or turn into an
+    /// error. Child diagnostics with this level are plain warnings.
+    Lint,
+}
+
+/// Indicates the confidence in the correctness of a suggestion, and whether
+/// tools may apply it without asking.
+#[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, tools like
+    /// `rustfix` apply it automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain. It
+    /// should result in valid code if it is applied.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `(...)`, which the user needs
+    /// to fill in.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
 }
 
 /// Trait implemented by types that can be converted into a set of `Span`s.
@@ -51,7 +74,17 @@ pub struct Diagnostic {
     level: Level,
     message: String,
     spans: Vec<Span>,
-    children: Vec<Diagnostic>
+    children: Vec<Diagnostic>,
+    code: Option<String>,
+    suggestions: Vec<Suggestion>,
+}
+
+/// A suggested change to the source code, made of one replacement per span.
+#[derive(Clone, Debug)]
+struct Suggestion {
+    message: String,
+    parts: Vec<(Span, String)>,
+    applicability: Applicability,
 }
 
 macro_rules! diagnostic_child_methods {
@@ -100,7 +133,9 @@ impl Diagnostic {
             level: level,
             message: message.into(),
             spans: vec![],
-            children: vec![]
+            children: vec![],
+            code: None,
+            suggestions: vec![],
         }
     }
 
@@ -114,7 +149,9 @@ impl Diagnostic {
             level: level,
             message: message.into(),
             spans: spans.into_spans(),
-            children: vec![]
+            children: vec![],
+            code: None,
+            suggestions: vec![],
         }
     }
 
@@ -123,6 +160,43 @@ impl Diagnostic {
     diagnostic_child_methods!(span_note, note, Level::Note);
     diagnostic_child_methods!(span_help, help, Level::Help);
 
+    /// Adds a suggestion to replace the source code of `span` with `replacement`.
+    ///
+    /// Suggestions are shown along with the diagnostic, and tools like `rustfix`
+    /// can apply them depending on their `applicability`.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn span_suggestion<T, U>(
+        self,
+        span: Span,
+        message: T,
+        replacement: U,
+        applicability: Applicability,
+    ) -> Diagnostic
+        where T: Into<String>, U: Into<String>
+    {
+        self.multipart_suggestion(message, vec![(span, replacement.into())], applicability)
+    }
+
+    /// Adds a suggestion made of several replacements, which should be applied
+    /// together. A suggestion without any replacement is ignored.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn multipart_suggestion<T: Into<String>>(
+        mut self,
+        message: T,
+        parts: Vec<(Span, String)>,
+        applicability: Applicability,
+    ) -> Diagnostic {
+        if parts.is_empty() {
+            return self;
+        }
+        self.suggestions.push(Suggestion {
+            message: message.into(),
+            parts,
+            applicability,
+        });
+        self
+    }
+
     /// Returns the diagnostic `level` for `self`.
     #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
     pub fn level(&self) -> Level {
@@ -147,6 +221,20 @@ impl Diagnostic {
         self.message = message.into();
     }
 
+    /// Returns the error code of `self`, if it has one.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_ref().map(|code| &code[..])
+    }
+
+    /// Sets the error code of `self`, like `E0001`, shown next to its level
+    /// and included in the JSON output. Diagnostics with `Level::Lint` are
+    /// identified by the name of the lint instead, their code is ignored.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn set_code<T: Into<String>>(&mut self, code: T) {
+        self.code = Some(code.into());
+    }
+
     /// Returns the `Span`s in `self`.
     #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
     pub fn spans(&self) -> &[Span] {
@@ -184,6 +272,18 @@ impl Diagnostic {
         for c in self.children {
             diag.sub(c.level, &c.message[..], to_internal(c.spans));
         }
+        match (self.level, self.code) {
+            (Level::Lint, _) | (_, None) => {}
+            (_, Some(code)) => diag.set_code(&code[..]),
+        }
+        // Empty suggestions are rejected when they are added, the compiler
+        // can't display them.
+        for suggestion in self.suggestions {
+            diag.suggestion(&suggestion.message[..], suggestion.applicability);
+            for (span, replacement) in suggestion.parts {
+                diag.suggestion_part(span.0, &replacement[..]);
+            }
+        }
         diag.emit();
     }
 }
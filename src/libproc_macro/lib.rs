@@ -37,7 +37,7 @@ pub mod bridge;
 mod diagnostic;
 
 #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
-pub use diagnostic::{Diagnostic, Level, MultiSpan};
+pub use diagnostic::{Applicability, Diagnostic, Level, MultiSpan};
 
 use std::{fmt, iter, mem};
 use std::ops::{Bound, RangeBounds};
@@ -72,6 +72,18 @@ impl !Send for LexError {}
 #[stable(feature = "proc_macro_lib", since = "1.15.0")]
 impl !Sync for LexError {}
 
+/// Error returned from `TokenStream::expand_expr`.
+#[unstable(feature = "proc_macro_expand", issue = "0")]
+#[derive(Debug)]
+pub struct ExpandError {
+    _inner: (),
+}
+
+#[unstable(feature = "proc_macro_expand", issue = "0")]
+impl !Send for ExpandError {}
+#[unstable(feature = "proc_macro_expand", issue = "0")]
+impl !Sync for ExpandError {}
+
 impl TokenStream {
     /// Returns an empty `TokenStream` containing no token trees.
     #[stable(feature = "proc_macro_lib2", since = "1.29.0")]
@@ -84,6 +96,24 @@ impl TokenStream {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Parses this `TokenStream` as an expression and expands the macros in it,
+    /// returning the resulting literal.
+    ///
+    /// This lets a procedural macro see the value of macros like `concat!`,
+    /// `env!` or `include_str!` in its input, as long as they expand to a literal.
+    /// A negated literal like `-1` is returned as a `-` followed by the literal.
+    ///
+    /// Returns an error if the stream is not a single expression, or if it does
+    /// not expand to a literal. Errors found while parsing or expanding the
+    /// expression are reported by the compiler.
+    #[unstable(feature = "proc_macro_expand", issue = "0")]
+    pub fn expand_expr(&self) -> Result<TokenStream, ExpandError> {
+        match self.0.expand_expr() {
+            Ok(stream) => Ok(TokenStream(stream)),
+            Err(()) => Err(ExpandError { _inner: () }),
+        }
+    }
 }
 
 /// Attempts to break the string into tokens and parse those tokens into a token stream.
@@ -1089,6 +1119,115 @@ impl Literal {
             cloned_bound(range.end_bound()),
         ).map(Span)
     }
+
+    /// Returns the kind of this literal, as written in the source.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn kind(&self) -> LiteralKind {
+        self.0.kind()
+    }
+
+    /// Returns the suffix of this literal, like `u8` in `1u8`, if it has one.
+    ///
+    /// Any identifier can be used as a suffix in the input of a procedural
+    /// macro, the compiler only checks suffixes once the literal is parsed as
+    /// part of an expression.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn suffix(&self) -> Option<String> {
+        self.0.suffix()
+    }
+
+    /// Returns the value of a string literal, with escapes like `\n` decoded
+    /// and, for raw strings, line endings normalized the way the compiler does.
+    ///
+    /// Returns `None` if this is not a string literal, or if it contains an
+    /// invalid escape.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn str_value(&self) -> Option<String> {
+        self.0.str_value()
+    }
+
+    /// Returns the value of a byte string literal, with escapes decoded.
+    ///
+    /// Returns `None` if this is not a byte string literal, or if it contains
+    /// an invalid escape.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn byte_str_value(&self) -> Option<Vec<u8>> {
+        self.0.byte_str_value()
+    }
+
+    /// Returns the value of a character literal, or `None` if this is not a
+    /// valid character literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn char_value(&self) -> Option<char> {
+        self.0.char_value()
+    }
+
+    /// Returns the value of a byte literal, or `None` if this is not a valid
+    /// byte literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn byte_value(&self) -> Option<u8> {
+        self.0.byte_value()
+    }
+
+    /// Returns the value of an integer literal, whatever its base and suffix.
+    ///
+    /// Returns `None` if this is not an integer literal, if the value does not
+    /// fit in a `u128`, or if it has a float suffix like `1f32`, which makes it
+    /// a float literal. The suffix is otherwise ignored, so `300u8` returns
+    /// `Some(300)` and leaves the range check to the compiler.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn int_value(&self) -> Option<u128> {
+        self.0.int_value()
+    }
+
+    /// Returns the value of a floating point literal, including integer
+    /// literals with a float suffix like `1f32`.
+    ///
+    /// Returns `None` if this is not a floating point literal. The value is
+    /// always parsed as an `f64`, even if the literal has an `f32` suffix.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    pub fn float_value(&self) -> Option<f64> {
+        self.0.float_value().and_then(|value| value.parse().ok())
+    }
+}
+
+/// The kind of a `Literal`, as returned by `Literal::kind`.
+#[unstable(feature = "proc_macro_literal_value", issue = "0")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LiteralKind {
+    /// A byte literal, like `b'a'`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    Byte,
+    /// A character literal, like `'a'`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    Char,
+    /// An integer literal, like `1` or `0xff_u8`.
+    ///
+    /// Integer literals with a float suffix, like `1f32`, are still of this
+    /// kind, even though their value is returned by `Literal::float_value`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    Integer,
+    /// A floating point literal, like `1.0` or `1e3f64`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    Float,
+    /// A string literal, like `"a"`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    Str,
+    /// A raw string literal delimited by the given number of `#` symbols,
+    /// like `r#"a"#`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    StrRaw(u16),
+    /// A byte string literal, like `b"a"`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    ByteStr,
+    /// A raw byte string literal delimited by the given number of `#`
+    /// symbols, like `br#"a"#`.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    ByteStrRaw(u16),
+    /// A literal the compiler could not lex, for which it already reported an
+    /// error.
+    #[unstable(feature = "proc_macro_literal_value", issue = "0")]
+    Err,
 }
 
 // N.B., the bridge only provides `to_string`, implement `fmt::Display`
@@ -1116,3 +1255,50 @@ impl fmt::Debug for Literal {
         self.0.fmt(f)
     }
 }
+
+/// Tracked access to environment variables.
+#[unstable(feature = "proc_macro_tracked_env", issue = "0")]
+pub mod tracked_env {
+    use std::env::{self, VarError};
+    use std::ffi::OsStr;
+
+    /// Retrieves an environment variable and adds it to the build dependency info.
+    ///
+    /// The variable and its value are written to the dep-info file (`--emit dep-info`),
+    /// so that the build system running the compiler knows that the variable was
+    /// accessed during compilation and can rerun the build when its value changes.
+    /// Incremental compilation needs no extra tracking: macros are expanded again
+    /// in every compilation session, so a new value reaches the expanded code and
+    /// invalidates the results depending on it like an edit to the source would.
+    /// Besides the dependency tracking this function is equivalent to `env::var`
+    /// from the standard library, except that the name must be UTF-8.
+    #[unstable(feature = "proc_macro_tracked_env", issue = "0")]
+    pub fn var<K: AsRef<OsStr> + AsRef<str>>(key: K) -> Result<String, VarError> {
+        let key: &str = key.as_ref();
+        let value = env::var(key);
+        crate::bridge::client::FreeFunctions::track_env_var(
+            key,
+            value.as_ref().map(|value| &value[..]).ok(),
+        );
+        value
+    }
+}
+
+/// Tracked access to additional files.
+#[unstable(feature = "proc_macro_tracked_path", issue = "0")]
+pub mod tracked_path {
+    /// Adds a file to the build dependency info.
+    ///
+    /// The path is written to the dep-info file (`--emit dep-info`) like the
+    /// source files of the crate, so that the build system running the compiler
+    /// can rerun the build when the file changes. As with `tracked_env::var`, the
+    /// macro runs again in every incremental session, so the new contents are picked
+    /// up without further tracking. Relative paths are relative to
+    /// the working directory of the compiler, as with any other file access.
+    /// The file itself is not read, proc macros still have to do that.
+    #[unstable(feature = "proc_macro_tracked_path", issue = "0")]
+    pub fn path<P: AsRef<str>>(path: P) {
+        let path: &str = path.as_ref();
+        crate::bridge::client::FreeFunctions::track_path(path);
+    }
+}
@@ -5,3 +5,8 @@ impl_stable_hash_for!(enum ::rustc_target::spec::PanicStrategy {
     Abort,
     Unwind
 });
+
+impl_stable_hash_for!(enum crate::session::config::SymbolManglingVersion {
+    Legacy,
+    V0
+});
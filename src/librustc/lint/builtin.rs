@@ -365,6 +365,12 @@ pub mod parser {
         Warn,
         "ill-formed attribute inputs that were previously accepted and used in practice"
     }
+
+    declare_lint! {
+        pub PROC_MACRO_WARNINGS,
+        Warn,
+        "warnings reported by procedural macros"
+    }
 }
 
 declare_lint! {
@@ -453,6 +459,7 @@ declare_lint_pass! {
         MACRO_EXPANDED_MACRO_EXPORTS_ACCESSED_BY_ABSOLUTE_PATHS,
         parser::QUESTION_MARK_MACRO_SEP,
         parser::ILL_FORMED_ATTRIBUTE_INPUT,
+        parser::PROC_MACRO_WARNINGS,
         DEPRECATED_IN_FUTURE,
         AMBIGUOUS_ASSOCIATED_ITEMS,
         NESTED_IMPL_TRAIT,
@@ -475,6 +482,9 @@ pub enum BuiltinLintDiagnostics {
     UnusedImports(String, Vec<(Span, String)>),
     NestedImplTrait { outer_impl_trait_span: Span, inner_impl_trait_span: Span },
     RedundantImport(Vec<(Span, bool)>, ast::Ident),
+    /// The children and suggestions of a diagnostic reported as a lint, like the warnings
+    /// of procedural macros.
+    Diagnostic(errors::Diagnostic),
 }
 
 pub(crate) fn add_elided_lifetime_in_path_suggestion(
@@ -600,6 +610,10 @@ impl BuiltinLintDiagnostics {
                     );
                 }
             }
+            BuiltinLintDiagnostics::Diagnostic(diagnostic) => {
+                db.children.extend(diagnostic.children);
+                db.suggestions.extend(diagnostic.suggestions);
+            }
         }
     }
 }
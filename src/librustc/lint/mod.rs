@@ -28,6 +28,7 @@ use crate::hir::intravisit;
 use crate::hir;
 use crate::lint::builtin::BuiltinLintDiagnostics;
 use crate::lint::builtin::parser::{QUESTION_MARK_MACRO_SEP, ILL_FORMED_ATTRIBUTE_INPUT};
+use crate::lint::builtin::parser::PROC_MACRO_WARNINGS;
 use crate::session::{Session, DiagnosticMessageId};
 use crate::ty::TyCtxt;
 use crate::ty::query::Providers;
@@ -68,6 +69,10 @@ pub struct Lint {
     /// e.g., "imports that are never used"
     pub desc: &'static str,
 
+    /// Long-form explanation of the lint, with examples and the rationale
+    /// behind it, shown by `rustc --explain <lint name>`.
+    pub explanation: Option<&'static str>,
+
     /// Starting at the given edition, default to the given lint level. If this is `None`, then use
     /// `default_level`.
     pub edition_lint_opts: Option<(Edition, Level)>,
@@ -82,6 +87,7 @@ impl Lint {
         match lint_id {
             BufferedEarlyLintId::QuestionMarkMacroSep => QUESTION_MARK_MACRO_SEP,
             BufferedEarlyLintId::IllFormedAttributeInput => ILL_FORMED_ATTRIBUTE_INPUT,
+            BufferedEarlyLintId::ProcMacroWarnings => PROC_MACRO_WARNINGS,
         }
     }
 
@@ -99,6 +105,10 @@ impl Lint {
 }
 
 /// Declares a static item of type `&'static Lint`.
+///
+/// A long-form explanation, shown by `rustc --explain`, can be given last as
+/// `explanation: r##"..."##`. Like the explanations of error codes it starts
+/// and ends with a newline and uses Markdown.
 #[macro_export]
 macro_rules! declare_lint {
     ($vis: vis $NAME: ident, $Level: ident, $desc: expr) => (
@@ -107,11 +117,28 @@ macro_rules! declare_lint {
     ($vis: vis $NAME: ident, $Level: ident, $desc: expr, report_in_external_macro: $rep: expr) => (
         declare_lint!{$vis $NAME, $Level, $desc, $rep}
     );
+    ($vis: vis $NAME: ident, $Level: ident, $desc: expr, explanation: $explanation: expr) => (
+        declare_lint!{$vis $NAME, $Level, $desc, report_in_external_macro: false,
+                      explanation: $explanation}
+    );
+    ($vis: vis $NAME: ident, $Level: ident, $desc: expr, report_in_external_macro: $rep: expr,
+     explanation: $explanation: expr
+    ) => (
+        $vis static $NAME: &$crate::lint::Lint = &$crate::lint::Lint {
+            name: stringify!($NAME),
+            default_level: $crate::lint::$Level,
+            desc: $desc,
+            explanation: Some($explanation),
+            edition_lint_opts: None,
+            report_in_external_macro: $rep,
+        };
+    );
     ($vis: vis $NAME: ident, $Level: ident, $desc: expr, $external: expr) => (
         $vis static $NAME: &$crate::lint::Lint = &$crate::lint::Lint {
             name: stringify!($NAME),
             default_level: $crate::lint::$Level,
             desc: $desc,
+            explanation: None,
             edition_lint_opts: None,
             report_in_external_macro: $external,
         };
@@ -123,6 +150,7 @@ macro_rules! declare_lint {
             name: stringify!($NAME),
             default_level: $crate::lint::$Level,
             desc: $desc,
+            explanation: None,
             edition_lint_opts: Some(($lint_edition, $crate::lint::Level::$edition_level)),
             report_in_external_macro: false,
         };
@@ -151,6 +179,7 @@ macro_rules! declare_tool_lint {
             name: &concat!(stringify!($tool), "::", stringify!($NAME)),
             default_level: $crate::lint::$Level,
             desc: $desc,
+            explanation: None,
             edition_lint_opts: None,
             report_in_external_macro: $external,
         };
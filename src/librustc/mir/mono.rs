@@ -200,6 +200,8 @@ pub struct Stats {
     pub llvm_insns: FxHashMap<String, usize>,
     // (ident, llvm-instructions)
     pub fn_stats: Vec<(String, usize)>,
+    // (function, symbol of the instance, llvm-instructions), for `-Z print-mono-bloat`
+    pub mono_fn_sizes: Vec<(DefId, String, usize)>,
 }
 
 impl_stable_hash_for!(struct self::Stats {
@@ -211,7 +213,8 @@ impl_stable_hash_for!(struct self::Stats {
     n_closures,
     n_llvm_insns,
     llvm_insns,
-    fn_stats
+    fn_stats,
+    mono_fn_sizes
 });
 
 impl Stats {
@@ -228,6 +231,7 @@ impl Stats {
             *self.llvm_insns.entry(k).or_insert(0) += v;
         }
         self.fn_stats.extend(stats.fn_stats);
+        self.mono_fn_sizes.extend(stats.mono_fn_sizes);
     }
 }
 
@@ -1,6 +1,9 @@
 use rustc_target::abi::{Align, Size};
 use rustc_data_structures::fx::{FxHashSet};
+use rustc_serialize::json::{Json, ToJson};
 use std::cmp::{self, Ordering};
+use std::collections::BTreeMap;
+use std::u64;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
@@ -25,6 +28,25 @@ pub struct FieldInfo {
     pub align: u64,
 }
 
+/// A niche-encoded discriminant, stored in invalid values of a field of the
+/// dataful variant.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheDiscrInfo {
+    pub offset: u64,
+    pub size: u64,
+    pub dataful_variant: String,
+}
+
+/// The largest niche of a type, which enclosing enums can use to store
+/// their discriminant.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// The number of invalid values of the niche.
+    pub available: u128,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum DataTypeKind {
     Struct,
@@ -41,6 +63,8 @@ pub struct TypeSizeInfo {
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    pub opt_niche_discr: Option<NicheDiscrInfo>,
+    pub largest_niche: Option<NicheInfo>,
     pub variants: Vec<VariantInfo>,
 }
 
@@ -57,6 +81,8 @@ impl CodeStats {
                                          overall_size: Size,
                                          packed: bool,
                                          opt_discr_size: Option<Size>,
+                                         opt_niche_discr: Option<NicheDiscrInfo>,
+                                         largest_niche: Option<NicheInfo>,
                                          mut variants: Vec<VariantInfo>) {
         // Sort variants so the largest ones are shown first. A stable sort is
         // used here so that source code order is preserved for all variants
@@ -71,12 +97,14 @@ impl CodeStats {
             overall_size: overall_size.bytes(),
             packed: packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            opt_niche_discr,
+            largest_niche,
             variants,
         };
         self.type_sizes.insert(info);
     }
 
-    pub fn print_type_sizes(&self) {
+    fn sorted_type_sizes(&self) -> Vec<&TypeSizeInfo> {
         let mut sorted: Vec<_> = self.type_sizes.iter().collect();
 
         // Primary sort: large-to-small.
@@ -88,6 +116,19 @@ impl CodeStats {
                 other => other,
             }
         });
+        sorted
+    }
+
+    /// Prints the layout of each type as a JSON object on its own line, in
+    /// the same order as `print_type_sizes`.
+    pub fn print_type_sizes_json(&self) {
+        for info in self.sorted_type_sizes() {
+            println!("{}", info.to_json());
+        }
+    }
+
+    pub fn print_type_sizes(&self) {
+        let sorted = self.sorted_type_sizes();
 
         for info in &sorted {
             println!("print-type-size type: `{}`: {} bytes, alignment: {} bytes",
@@ -173,3 +214,91 @@ impl CodeStats {
         }
     }
 }
+
+impl ToJson for TypeSizeInfo {
+    fn to_json(&self) -> Json {
+        let discr_size = self.opt_discr_size.unwrap_or(0);
+        let mut max_variant_size = discr_size;
+        let variants = self.variants.iter().map(|variant| {
+            max_variant_size = cmp::max(max_variant_size, variant.size);
+
+            let mut fields = variant.fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|f| f.offset);
+            let mut padding = 0;
+            let mut min_offset = discr_size;
+            for field in &fields {
+                padding += field.offset.saturating_sub(min_offset);
+                min_offset = cmp::max(min_offset, field.offset + field.size);
+            }
+
+            let mut obj = BTreeMap::new();
+            obj.insert("name".to_string(), variant.name.to_json());
+            obj.insert("size_kind".to_string(), match variant.kind {
+                SizeKind::Exact => "exact",
+                SizeKind::Min => "min",
+            }.to_json());
+            // Like in the text output, the tag is not counted in the size
+            // of the variants.
+            obj.insert("size".to_string(), variant.size.saturating_sub(discr_size).to_json());
+            obj.insert("padding".to_string(), padding.to_json());
+            obj.insert("fields".to_string(), Json::Array(fields.iter().map(|field| {
+                let mut obj = BTreeMap::new();
+                obj.insert("name".to_string(), field.name.to_json());
+                obj.insert("offset".to_string(), field.offset.to_json());
+                obj.insert("size".to_string(), field.size.to_json());
+                obj.insert("align".to_string(), field.align.to_json());
+                Json::Object(obj)
+            }).collect()));
+            Json::Object(obj)
+        }).collect::<Vec<_>>();
+
+        let discriminant = match (self.opt_discr_size, &self.opt_niche_discr) {
+            (Some(size), _) => {
+                let mut obj = BTreeMap::new();
+                obj.insert("kind".to_string(), "tag".to_json());
+                obj.insert("size".to_string(), size.to_json());
+                Json::Object(obj)
+            }
+            (None, &Some(ref niche)) => {
+                let mut obj = BTreeMap::new();
+                obj.insert("kind".to_string(), "niche".to_json());
+                obj.insert("offset".to_string(), niche.offset.to_json());
+                obj.insert("size".to_string(), niche.size.to_json());
+                obj.insert("dataful_variant".to_string(), niche.dataful_variant.to_json());
+                Json::Object(obj)
+            }
+            (None, &None) => Json::Null,
+        };
+
+        let niche = match self.largest_niche {
+            Some(ref niche) => {
+                let mut obj = BTreeMap::new();
+                obj.insert("offset".to_string(), niche.offset.to_json());
+                obj.insert("size".to_string(), niche.size.to_json());
+                // Saturated, only 128-bit niches can have more values.
+                obj.insert("available".to_string(),
+                           (cmp::min(niche.available, u64::MAX as u128) as u64).to_json());
+                Json::Object(obj)
+            }
+            None => Json::Null,
+        };
+
+        let mut obj = BTreeMap::new();
+        obj.insert("type".to_string(), self.type_description.to_json());
+        obj.insert("kind".to_string(), match self.kind {
+            DataTypeKind::Struct => "struct",
+            DataTypeKind::Union => "union",
+            DataTypeKind::Enum => "enum",
+            DataTypeKind::Closure => "closure",
+        }.to_json());
+        obj.insert("size".to_string(), self.overall_size.to_json());
+        obj.insert("align".to_string(), self.align.to_json());
+        obj.insert("packed".to_string(), self.packed.to_json());
+        obj.insert("discriminant".to_string(), discriminant);
+        obj.insert("niche".to_string(), niche);
+        obj.insert("variants".to_string(), Json::Array(variants));
+        obj.insert("end_padding".to_string(),
+                   self.overall_size.saturating_sub(max_variant_size).to_json());
+        Json::Object(obj)
+    }
+}
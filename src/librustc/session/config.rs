@@ -131,6 +131,12 @@ impl PgoGenerate {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Hash, Debug, RustcEncodable, RustcDecodable)]
+pub enum SymbolManglingVersion {
+    Legacy,
+    V0,
+}
+
 #[derive(Clone, Copy, PartialEq, Hash)]
 pub enum DebugInfo {
     None,
@@ -228,6 +234,8 @@ pub enum ErrorOutputType {
         /// The way the `rendered` field is created
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF 2.1.0 log, written once compilation ends
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -427,6 +435,9 @@ top_level_options!(
         // is currently just a hack and will be removed eventually, so please
         // try to not rely on this too much.
         actually_rustdoc: bool [TRACKED],
+        // Whether rustdoc keeps the bodies of functions instead of replacing
+        // them with loops, because it looks at the code they contain.
+        rustdoc_keep_bodies: bool [TRACKED],
 
         // Specifications of codegen units / ThinLTO which are forced as a
         // result of parsing command line options. These are not necessarily
@@ -484,6 +495,7 @@ impl BorrowckMode {
     }
 }
 
+#[derive(Clone)]
 pub enum Input {
     /// Loads source from file
     File(PathBuf),
@@ -623,6 +635,7 @@ impl Default for Options {
             unstable_features: UnstableFeatures::Disallow,
             debug_assertions: true,
             actually_rustdoc: false,
+            rustdoc_keep_bodies: false,
             cli_forced_codegen_units: None,
             cli_forced_thinlto_off: false,
             remap_path_prefix: Vec::new(),
@@ -838,11 +851,14 @@ macro_rules! options {
             Some("an optional path to the profiling data output directory");
         pub const parse_merge_functions: Option<&str> =
             Some("one of: `disabled`, `trampolines`, or `aliases`");
+        pub const parse_symbol_mangling_version: Option<&str> =
+            Some("either `legacy` or `v0` (RFC 2603)");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
-        use super::{$struct_name, Passes, Sanitizer, LtoCli, LinkerPluginLto, PgoGenerate};
+        use super::{$struct_name, Passes, Sanitizer, LtoCli, LinkerPluginLto, PgoGenerate,
+                    SymbolManglingVersion};
         use rustc_target::spec::{LinkerFlavor, MergeFunctions, PanicStrategy, RelroLevel};
         use std::path::PathBuf;
         use std::str::FromStr;
@@ -1112,6 +1128,18 @@ macro_rules! options {
             }
             true
         }
+
+        fn parse_symbol_mangling_version(
+            slot: &mut SymbolManglingVersion,
+            v: Option<&str>,
+        ) -> bool {
+            *slot = match v {
+                Some("legacy") => SymbolManglingVersion::Legacy,
+                Some("v0") => SymbolManglingVersion::V0,
+                _ => return false,
+            };
+            true
+        }
     }
 ) }
 
@@ -1329,8 +1357,16 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "show spans for compiler debugging (expr|pat|ty)"),
     print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print layout information for each type encountered"),
+    print_type_sizes_json: bool = (false, parse_bool, [UNTRACKED],
+        "print the layout information of `-Z print-type-sizes` as JSON, one type per line"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass"),
+    print_mono_bloat: bool = (false, parse_bool, [UNTRACKED],
+        "print the number of instances of each function and each crate, and the number of LLVM \
+         instructions emitted for them before optimization, the largest first"),
+    print_symbol_sizes: bool = (false, parse_bool, [UNTRACKED],
+        "print the size of each symbol of the linked binary as JSON, summed for each crate, \
+         module and function (ELF only)"),
     mir_opt_level: usize = (1, parse_uint, [TRACKED],
         "set the MIR optimization level (0-3, default: 1)"),
     mutable_noalias: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1451,6 +1487,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "run the self profiler and output the raw event data"),
     self_profile_events: Option<Vec<String>> = (None, parse_opt_comma_list, [UNTRACKED],
         "specifies which kinds of events get recorded by the self profiler"),
+    self_profile_format: Option<Vec<String>> = (None, parse_opt_comma_list, [UNTRACKED],
+        "also write the self profile in the given formats: `chrome` for a Chrome trace, \
+         `folded` for folded stacks used to draw flamegraphs"),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emits a section containing stack size metadata"),
     plt: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1462,8 +1501,24 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
          the same values as the target option of the same name"),
     allow_features: Option<Vec<String>> = (None, parse_opt_comma_list, [TRACKED],
         "only allow the listed language features to be enabled in code (space separated)"),
+    symbol_mangling_version: SymbolManglingVersion = (SymbolManglingVersion::Legacy,
+        parse_symbol_mangling_version, [TRACKED],
+        "which mangling version to use for symbol names"),
     emit_artifact_notifications: bool = (false, parse_bool, [UNTRACKED],
         "emit notifications after each artifact has been output (only in the JSON format)"),
+    lint_baseline: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "don't report the lint diagnostics listed in this baseline file"),
+    write_lint_baseline: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write a baseline listing every lint diagnostic of this compilation to this file,
+         only if compilation succeeds (use `--cap-lints=warn` to also list denied lints)"),
+    apply_suggestions: bool = (false, parse_bool, [UNTRACKED],
+        "rewrite the source files with the machine-applicable suggestions of the diagnostics,
+         compiling again until none are left"),
+    sort_diagnostics: bool = (false, parse_bool, [UNTRACKED],
+        "emit the diagnostics at the end of compilation, sorted by file and line"),
+    diagnostic_summary: bool = (false, parse_bool, [UNTRACKED],
+        "print the number of diagnostics per error code and lint, and the files with the most
+         diagnostics, at the end of compilation"),
 }
 
 pub fn default_lib_output() -> CrateType {
@@ -1763,7 +1818,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "",
             "explain",
             "Provide a detailed explanation of an error \
-             message",
+             message or of a lint",
             "OPT",
         ),
         opt::flag_s("", "test", "Build a test harness"),
@@ -1986,12 +2041,13 @@ pub fn build_session_options_and_crate_config(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
                 &format!(
-                    "argument for --error-format must be `human`, `json` or \
-                     `short` (instead was `{}`)",
+                    "argument for --error-format must be `human`, `json`, `short` or \
+                     `sarif` (instead was `{}`)",
                     arg
                 ),
             ),
@@ -2019,6 +2075,12 @@ pub fn build_session_options_and_crate_config(
                 "--error-format=pretty-json is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::default(),
+                "--error-format=sarif is unstable",
+            );
+        }
     }
 
     if debugging_opts.pgo_gen.enabled() && !debugging_opts.pgo_use.is_empty() {
@@ -2426,6 +2488,7 @@ pub fn build_session_options_and_crate_config(
             unstable_features: UnstableFeatures::from_environment(),
             debug_assertions,
             actually_rustdoc: false,
+            rustdoc_keep_bodies: false,
             cli_forced_codegen_units: codegen_units,
             cli_forced_thinlto_off: disable_thinlto,
             remap_path_prefix,
@@ -2558,7 +2621,8 @@ mod dep_tracking {
     use std::path::PathBuf;
     use std::collections::hash_map::DefaultHasher;
     use super::{CrateType, DebugInfo, ErrorOutputType, OptLevel, OutputTypes,
-                Passes, Sanitizer, LtoCli, LinkerPluginLto, PgoGenerate};
+                Passes, Sanitizer, LtoCli, LinkerPluginLto, PgoGenerate,
+                SymbolManglingVersion};
     use syntax::feature_gate::UnstableFeatures;
     use rustc_target::spec::{MergeFunctions, PanicStrategy, RelroLevel, TargetTriple};
     use syntax::edition::Edition;
@@ -2627,6 +2691,7 @@ mod dep_tracking {
     impl_dep_tracking_hash_via_hash!(Edition);
     impl_dep_tracking_hash_via_hash!(LinkerPluginLto);
     impl_dep_tracking_hash_via_hash!(PgoGenerate);
+    impl_dep_tracking_hash_via_hash!(SymbolManglingVersion);
 
     impl_dep_tracking_hash_for_sortable_vec_of!(String);
     impl_dep_tracking_hash_for_sortable_vec_of!(PathBuf);
@@ -2695,6 +2760,7 @@ mod tests {
         to_crate_config
     };
     use crate::session::config::{LtoCli, LinkerPluginLto, PgoGenerate, ExternEntry};
+    use crate::session::config::SymbolManglingVersion;
     use crate::session::build_session;
     use crate::session::search_paths::SearchPath;
     use std::collections::{BTreeMap, BTreeSet};
@@ -3379,6 +3445,10 @@ mod tests {
         opts = reference.clone();
         opts.debugging_opts.allow_features = Some(vec![String::from("lang_items")]);
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.symbol_mangling_version = SymbolManglingVersion::V0;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 
     #[test]
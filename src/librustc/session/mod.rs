@@ -1,4 +1,5 @@
 pub use self::code_stats::{DataTypeKind, SizeKind, FieldInfo, VariantInfo};
+pub use self::code_stats::{NicheDiscrInfo, NicheInfo};
 use self::code_stats::CodeStats;
 
 use crate::dep_graph::cgu_reuse_tracker::CguReuseTracker;
@@ -22,11 +23,15 @@ use rustc_data_structures::sync::{
 };
 
 use errors::{DiagnosticBuilder, DiagnosticId, Applicability};
+use errors::baseline::Baseline;
+use errors::fixes::{FixedFile, Fixes};
+use errors::summary::Summary;
 use errors::emitter::{Emitter, EmitterWriter};
 use syntax::ast::{self, NodeId};
 use syntax::edition::Edition;
 use syntax::feature_gate::{self, AttributeType};
 use syntax::json::JsonEmitter;
+use syntax::sarif::SarifEmitter;
 use syntax::source_map;
 use syntax::parse::{self, ParseSess};
 use syntax::symbol::Symbol;
@@ -320,6 +325,49 @@ impl Session {
     pub fn compile_status(&self) -> Result<(), ErrorReported> {
         compile_result_from_err_count(self.err_count())
     }
+    /// Applies the suggestions collected with `-Z apply-suggestions` to the
+    /// source files and reports what changed. Returns the changed files, with
+    /// their previous contents.
+    pub fn apply_suggestions(&self) -> Vec<FixedFile> {
+        let applied = self.diagnostic().apply_fixes();
+        for file in &applied.files {
+            self.diagnostic().note_without_error(&format!(
+                "applied {} suggestion{} to `{}`",
+                file.applied,
+                if file.applied == 1 { "" } else { "s" },
+                file.path.display(),
+            ));
+        }
+        if applied.overlapping > 0 {
+            self.diagnostic().note_without_error(&format!(
+                "skipped {} suggestion{} overlapping with others",
+                applied.overlapping,
+                if applied.overlapping == 1 { "" } else { "s" },
+            ));
+        }
+        for (path, e) in &applied.errors {
+            self.warn(&format!("failed to apply suggestions to `{}`: {}", path.display(), e));
+        }
+        applied.files
+    }
+
+    /// Writes the baseline requested with `-Z write-lint-baseline`. Nothing is
+    /// written when compilation failed, as lints that did not get to run would
+    /// be missing from it.
+    pub fn write_lint_baseline(&self) {
+        let path = match self.opts.debugging_opts.write_lint_baseline {
+            Some(ref path) => path,
+            None => return,
+        };
+        if self.has_errors() {
+            self.warn(&format!("not writing lint baseline `{}` as compilation failed",
+                               path.display()));
+            return;
+        }
+        if let Err(e) = self.diagnostic().write_baseline(path) {
+            self.err(&format!("failed to write lint baseline `{}`: {}", path.display(), e));
+        }
+    }
     pub fn track_errors<F, T>(&self, f: F) -> Result<T, ErrorReported>
     where
         F: FnOnce() -> T,
@@ -1071,6 +1119,12 @@ fn default_emitter(
                 json_rendered,
             ).ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => Box::new(
+            SarifEmitter::stderr(Some(registry), source_map.clone()),
+        ),
+        (config::ErrorOutputType::Sarif, Some(dst)) => Box::new(
+            SarifEmitter::new(dst, Some(registry), source_map.clone()),
+        ),
     }
 }
 
@@ -1106,6 +1160,8 @@ pub fn build_session_with_source_map(
     let report_delayed_bugs = sopts.debugging_opts.report_delayed_bugs;
 
     let external_macro_backtrace = sopts.debugging_opts.external_macro_backtrace;
+    // Holding diagnostics back would defeat the backtrace of the first error.
+    let sort_diagnostics = sopts.debugging_opts.sort_diagnostics && treat_err_as_bug.is_none();
 
     let emitter = match diagnostics_output {
         DiagnosticOutput::Default => default_emitter(&sopts, registry, &source_map, None),
@@ -1122,10 +1178,31 @@ pub fn build_session_with_source_map(
             report_delayed_bugs,
             dont_buffer_diagnostics,
             external_macro_backtrace,
+            sort_diagnostics,
             ..Default::default()
         },
     );
 
+    if let Some(ref path) = sopts.debugging_opts.lint_baseline {
+        match Baseline::load(source_map.clone(), path) {
+            Ok(baseline) => diagnostic_handler.set_baseline(baseline),
+            Err(e) => early_error(
+                sopts.error_format,
+                &format!("failed to read lint baseline `{}`: {}", path.display(), e),
+            ),
+        }
+    } else if sopts.debugging_opts.write_lint_baseline.is_some() {
+        diagnostic_handler.set_baseline(Baseline::new(source_map.clone()));
+    }
+
+    if sopts.debugging_opts.apply_suggestions {
+        diagnostic_handler.set_fixes(Fixes::new(source_map.clone()));
+    }
+
+    if sopts.debugging_opts.diagnostic_summary {
+        diagnostic_handler.set_summary(Summary::new(source_map.clone()));
+    }
+
     build_session_(sopts, local_crate_source_file, diagnostic_handler, source_map, lint_caps)
 }
 
@@ -1138,7 +1215,9 @@ fn build_session_(
 ) -> Session {
     let self_profiler =
         if sopts.debugging_opts.self_profile {
-            let profiler = SelfProfiler::new(&sopts.debugging_opts.self_profile_events);
+            let profiler = SelfProfiler::new(&sopts.debugging_opts.self_profile_events,
+                                             &sopts.debugging_opts.self_profile_format,
+                                             sopts.error_format);
             match profiler {
                 Ok(profiler) => {
                     crate::ty::query::QueryName::register_with_profiler(&profiler);
@@ -1332,6 +1411,7 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
         }
         config::ErrorOutputType::Json { pretty, json_rendered } =>
             Box::new(JsonEmitter::basic(pretty, json_rendered)),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, None, emitter);
     handler.emit(&MultiSpan::new(), msg, errors::Level::Fatal);
@@ -1346,6 +1426,7 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
         }
         config::ErrorOutputType::Json { pretty, json_rendered } =>
             Box::new(JsonEmitter::basic(pretty, json_rendered)),
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = errors::Handler::with_emitter(true, None, emitter);
     handler.emit(&MultiSpan::new(), msg, errors::Level::Warning);
@@ -1262,14 +1262,29 @@ impl<'a, 'tcx> LayoutCx<'tcx, TyCtxt<'a, 'tcx, 'tcx>> {
         }
 
         // (delay format until we actually need it)
-        let record = |kind, packed, opt_discr_size, variants| {
+        let record = |kind, packed, opt_discr_size, opt_niche_discr, variants| {
             let type_desc = format!("{:?}", layout.ty);
+            // `find_niche` doesn't look into the variants of enums, their
+            // niche is what is left in the discriminant.
+            let niche = match layout.variants {
+                Variants::Multiple { ref discr, discr_index, .. } => {
+                    self.scalar_niche(discr, layout.fields.offset(discr_index))
+                }
+                Variants::Single { .. } => self.find_niche(layout).unwrap_or(None),
+            };
+            let largest_niche = niche.map(|niche| session::NicheInfo {
+                offset: niche.offset.bytes(),
+                size: niche.scalar.value.size(self).bytes(),
+                available: niche.available,
+            });
             self.tcx.sess.code_stats.borrow_mut().record_type_size(kind,
                                                                    type_desc,
                                                                    layout.align.abi,
                                                                    layout.size,
                                                                    packed,
                                                                    opt_discr_size,
+                                                                   opt_niche_discr,
+                                                                   largest_niche,
                                                                    variants);
         };
 
@@ -1281,7 +1296,7 @@ impl<'a, 'tcx> LayoutCx<'tcx, TyCtxt<'a, 'tcx, 'tcx>> {
 
             ty::Closure(..) => {
                 debug!("print-type-size t: `{:?}` record closure", layout.ty);
-                record(DataTypeKind::Closure, false, None, vec![]);
+                record(DataTypeKind::Closure, false, None, None, vec![]);
                 return;
             }
 
@@ -1347,17 +1362,18 @@ impl<'a, 'tcx> LayoutCx<'tcx, TyCtxt<'a, 'tcx, 'tcx>> {
                     record(adt_kind.into(),
                            adt_packed,
                            None,
+                           None,
                            vec![build_variant_info(Some(variant_def.ident),
                                                    &fields,
                                                    layout)]);
                 } else {
                     // (This case arises for *empty* enums; so give it
                     // zero variants.)
-                    record(adt_kind.into(), adt_packed, None, vec![]);
+                    record(adt_kind.into(), adt_packed, None, None, vec![]);
                 }
             }
 
-            Variants::Multiple { ref discr, ref discr_kind, .. } => {
+            Variants::Multiple { ref discr, ref discr_kind, discr_index, .. } => {
                 debug!("print-type-size `{:#?}` adt general variants def {}",
                        layout.ty, adt_def.variants.len());
                 let variant_infos: Vec<_> =
@@ -1369,10 +1385,20 @@ impl<'a, 'tcx> LayoutCx<'tcx, TyCtxt<'a, 'tcx, 'tcx>> {
                                            layout.for_variant(self, i))
                     })
                     .collect();
+                let opt_niche_discr = match *discr_kind {
+                    DiscriminantKind::Niche { dataful_variant, .. } => {
+                        Some(session::NicheDiscrInfo {
+                            offset: layout.fields.offset(discr_index).bytes(),
+                            size: discr.value.size(self).bytes(),
+                            dataful_variant: adt_def.variants[dataful_variant].ident.to_string(),
+                        })
+                    }
+                    DiscriminantKind::Tag => None,
+                };
                 record(adt_kind.into(), adt_packed, match discr_kind {
                     DiscriminantKind::Tag => Some(discr.value.size(self)),
                     _ => None
-                }, variant_infos);
+                }, opt_niche_discr, variant_infos);
             }
         }
     }
@@ -2000,31 +2026,35 @@ impl Niche {
 }
 
 impl<'a, 'tcx> LayoutCx<'tcx, TyCtxt<'a, 'tcx, 'tcx>> {
-    /// Find the offset of a niche leaf field, starting from
-    /// the given type and recursing through aggregates.
-    // FIXME(eddyb) traverse already optimized enums.
-    fn find_niche(&self, layout: TyLayout<'tcx>) -> Result<Option<Niche>, LayoutError<'tcx>> {
-        let scalar_niche = |scalar: &Scalar, offset| {
-            let Scalar { value, valid_range: ref v } = *scalar;
+    /// The niche of a scalar at the given offset, if it has values outside
+    /// of its valid range.
+    fn scalar_niche(&self, scalar: &Scalar, offset: Size) -> Option<Niche> {
+        let Scalar { value, valid_range: ref v } = *scalar;
 
-            let bits = value.size(self).bits();
-            assert!(bits <= 128);
-            let max_value = !0u128 >> (128 - bits);
+        let bits = value.size(self).bits();
+        assert!(bits <= 128);
+        let max_value = !0u128 >> (128 - bits);
 
-            // Find out how many values are outside the valid range.
-            let available = if v.start() <= v.end() {
-                v.start() + (max_value - v.end())
-            } else {
-                v.start() - v.end() - 1
-            };
+        // Find out how many values are outside the valid range.
+        let available = if v.start() <= v.end() {
+            v.start() + (max_value - v.end())
+        } else {
+            v.start() - v.end() - 1
+        };
 
-            // Give up if there is no niche value available.
-            if available == 0 {
-                return None;
-            }
+        // Give up if there is no niche value available.
+        if available == 0 {
+            return None;
+        }
 
-            Some(Niche { offset, scalar: scalar.clone(), available })
-        };
+        Some(Niche { offset, scalar: scalar.clone(), available })
+    }
+
+    /// Find the offset of a niche leaf field, starting from
+    /// the given type and recursing through aggregates.
+    // FIXME(eddyb) traverse already optimized enums.
+    fn find_niche(&self, layout: TyLayout<'tcx>) -> Result<Option<Niche>, LayoutError<'tcx>> {
+        let scalar_niche = |scalar: &Scalar, offset| self.scalar_niche(scalar, offset);
 
         // Locals variables which live across yields are stored
         // in the generator type as fields. These may be uninitialized
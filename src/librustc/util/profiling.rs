@@ -1,11 +1,20 @@
 use std::borrow::Cow;
 use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
 use std::mem::{self, Discriminant};
 use std::process;
+use std::sync::Mutex;
 use std::thread::ThreadId;
+use std::time::Instant;
 use std::u32;
 
+use crate::session::config::ErrorOutputType;
+use crate::session::early_warn;
 use crate::ty::query::QueryName;
+use crate::util::nodemap::FxHashMap;
+
+use rustc_serialize::json;
 
 use measureme::{StringId, TimestampKind};
 
@@ -34,6 +43,7 @@ bitflags! {
         const QUERY_CACHE_HITS   = 1 << 2;
         const QUERY_BLOCKED      = 1 << 3;
         const INCR_CACHE_LOADS   = 1 << 4;
+        const QUERY_KEYS         = 1 << 5;
 
         const DEFAULT = Self::GENERIC_ACTIVITIES.bits |
                         Self::QUERY_PROVIDERS.bits |
@@ -42,7 +52,8 @@ bitflags! {
 
         // empty() and none() aren't const-fns unfortunately
         const NONE = 0;
-        const ALL  = !Self::NONE.bits;
+        // Query keys are costly to format, they have to be asked for explicitly.
+        const ALL  = !Self::NONE.bits & !Self::QUERY_KEYS.bits;
     }
 }
 
@@ -54,8 +65,53 @@ const EVENT_FILTERS_BY_NAME: &[(&str, EventFilter)] = &[
     ("query-cache-hit", EventFilter::QUERY_CACHE_HITS),
     ("query-blocked" , EventFilter::QUERY_BLOCKED),
     ("incr-cache-load", EventFilter::INCR_CACHE_LOADS),
+    ("query-keys", EventFilter::QUERY_KEYS),
+];
+
+/// The formats the profile can be exported in, in addition to the raw event file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    /// The Chrome Trace Event format, which can be opened in `chrome://tracing` and other trace
+    /// viewers.
+    Chrome,
+    /// Folded stacks, as taken by `flamegraph.pl` and `inferno-flamegraph`.
+    Folded,
+}
+
+const EXPORT_FORMATS_BY_NAME: &[(&str, ExportFormat)] = &[
+    ("chrome", ExportFormat::Chrome),
+    ("folded", ExportFormat::Folded),
 ];
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EventPhase {
+    Start,
+    End,
+    Instant,
+}
+
+/// An event kept in memory to be exported when the profiler is dropped.
+struct ExportedEvent {
+    /// The name of the activity or query, empty for the end of an event since it always closes
+    /// the last event started on the same thread.
+    label: Cow<'static, str>,
+    kind: &'static str,
+    thread_id: u64,
+    /// Nanoseconds since the profiler was created.
+    timestamp: u64,
+    phase: EventPhase,
+}
+
+/// An event with its start and end paired.
+struct Interval<'a> {
+    event: &'a ExportedEvent,
+    /// The labels of the events it is nested in, outermost first, and its own label.
+    stack: Vec<&'a str>,
+    duration: u64,
+    /// The duration minus the time spent in nested events.
+    self_time: u64,
+}
+
 fn thread_id_to_u64(tid: ThreadId) -> u64 {
     unsafe { mem::transmute::<ThreadId, u64>(tid) }
 }
@@ -68,10 +124,38 @@ pub struct SelfProfiler {
     incremental_load_result_event_kind: StringId,
     query_blocked_event_kind: StringId,
     query_cache_hit_event_kind: StringId,
+    error_format: ErrorOutputType,
+    export_formats: Vec<ExportFormat>,
+    /// The events recorded for the exports, if any format was requested.
+    exported_events: Option<Mutex<Vec<ExportedEvent>>>,
+    start_time: Instant,
 }
 
 impl SelfProfiler {
-    pub fn new(event_filters: &Option<Vec<String>>) -> Result<SelfProfiler, Box<dyn Error>> {
+    pub fn new(
+        event_filters: &Option<Vec<String>>,
+        export_formats: &Option<Vec<String>>,
+        error_format: ErrorOutputType,
+    ) -> Result<SelfProfiler, Box<dyn Error>> {
+        let mut formats = Vec::new();
+        for name in export_formats.iter().flatten() {
+            match EXPORT_FORMATS_BY_NAME.iter().find(|&&(known, _)| known == name) {
+                Some(&(_, format)) => {
+                    if !formats.contains(&format) {
+                        formats.push(format);
+                    }
+                }
+                None => {
+                    return Err(format!("unknown self-profile format `{}`, expected one of: {}",
+                                       name,
+                                       EXPORT_FORMATS_BY_NAME.iter()
+                                                             .map(|&(name, _)| name)
+                                                             .collect::<Vec<_>>()
+                                                             .join(", ")).into());
+                }
+            }
+        }
+
         let filename = format!("pid-{}.rustc_profile", process::id());
         let path = std::path::Path::new(&filename);
         let profiler = Profiler::new(path)?;
@@ -119,6 +203,10 @@ impl SelfProfiler {
             incremental_load_result_event_kind,
             query_blocked_event_kind,
             query_cache_hit_event_kind,
+            error_format,
+            exported_events: if formats.is_empty() { None } else { Some(Mutex::new(Vec::new())) },
+            export_formats: formats,
+            start_time: Instant::now(),
         })
     }
 
@@ -162,10 +250,27 @@ impl SelfProfiler {
         }
     }
 
+    /// Records the start of a query. The key is only used with the `query-keys` event filter,
+    /// in the exported formats, since formatting it is costly.
     #[inline]
-    pub fn start_query(&self, query_name: QueryName) {
+    pub fn start_query<K: fmt::Debug>(&self, query_name: QueryName, key: &K) {
         if self.event_filter_mask.contains(EventFilter::QUERY_PROVIDERS) {
             self.record_query(query_name, self.query_event_kind, TimestampKind::Start);
+            if self.exported_events.is_none() {
+                return;
+            }
+            if self.event_filter_mask.contains(EventFilter::QUERY_KEYS) {
+                // The query is still running, and printing the paths of impls in the key would
+                // run queries like `type_of`, which can depend on it.
+                let key = crate::ty::print::with_forced_impl_filename_line(|| {
+                    format!("{:?}", key)
+                });
+                self.export_event(format!("{}({})", query_name.as_str(), key).into(),
+                                  "Query",
+                                  EventPhase::Start);
+            } else {
+                self.export_event(query_name.as_str().into(), "Query", EventPhase::Start);
+            }
         }
     }
 
@@ -173,6 +278,7 @@ impl SelfProfiler {
     pub fn end_query(&self, query_name: QueryName) {
         if self.event_filter_mask.contains(EventFilter::QUERY_PROVIDERS) {
             self.record_query(query_name, self.query_event_kind, TimestampKind::End);
+            self.export_event("".into(), "Query", EventPhase::End);
         }
     }
 
@@ -216,8 +322,12 @@ impl SelfProfiler {
     fn record(&self, event_id: &str, event_kind: StringId, timestamp_kind: TimestampKind) {
         let thread_id = thread_id_to_u64(std::thread::current().id());
 
-        let event_id = self.profiler.alloc_string(event_id);
-        self.profiler.record_event(event_kind, event_id, thread_id, timestamp_kind);
+        let event_id_string = self.profiler.alloc_string(event_id);
+        self.profiler.record_event(event_kind, event_id_string, thread_id, timestamp_kind);
+        if self.exported_events.is_some() {
+            let kind = self.event_kind_name(event_kind);
+            self.export_event(event_id.to_string().into(), kind, timestamp_kind.into());
+        }
     }
 
     #[inline]
@@ -232,5 +342,180 @@ impl SelfProfiler {
         let thread_id = thread_id_to_u64(std::thread::current().id());
 
         self.profiler.record_event(event_kind, dep_node_name, thread_id, timestamp_kind);
+        // Query providers are exported by `start_query` and `end_query`, along with their keys.
+        if self.exported_events.is_some() && event_kind != self.query_event_kind {
+            let kind = self.event_kind_name(event_kind);
+            self.export_event(query_name.as_str().into(), kind, timestamp_kind.into());
+        }
+    }
+
+    fn event_kind_name(&self, event_kind: StringId) -> &'static str {
+        if event_kind == self.query_event_kind {
+            "Query"
+        } else if event_kind == self.generic_activity_event_kind {
+            "GenericActivity"
+        } else if event_kind == self.incremental_load_result_event_kind {
+            "IncrementalLoadResult"
+        } else if event_kind == self.query_blocked_event_kind {
+            "QueryBlocked"
+        } else {
+            "QueryCacheHit"
+        }
+    }
+
+    #[inline]
+    fn export_event(&self, label: Cow<'static, str>, kind: &'static str, phase: EventPhase) {
+        if let Some(ref events) = self.exported_events {
+            let elapsed = self.start_time.elapsed();
+            events.lock().unwrap().push(ExportedEvent {
+                label: if phase == EventPhase::End { "".into() } else { label },
+                kind,
+                thread_id: thread_id_to_u64(std::thread::current().id()),
+                timestamp: elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64,
+                phase,
+            });
+        }
+    }
+
+    /// Writes the profile in the requested export formats, next to the raw event file.
+    fn write_exports(&self) {
+        let events = match self.exported_events {
+            Some(ref events) => events.lock().unwrap_or_else(|e| e.into_inner()),
+            None => return,
+        };
+        let (intervals, instants) = pair_events(&events);
+        for &format in &self.export_formats {
+            let (path, contents) = match format {
+                ExportFormat::Chrome => {
+                    (format!("pid-{}.trace.json", process::id()),
+                     chrome_trace(&intervals, &instants))
+                }
+                ExportFormat::Folded => {
+                    (format!("pid-{}.folded", process::id()), folded_stacks(&intervals))
+                }
+            };
+            if let Err(e) = fs::write(&path, contents) {
+                early_warn(self.error_format,
+                           &format!("failed to write the self profile to `{}`: {}", path, e));
+            }
+        }
+    }
+}
+
+impl Drop for SelfProfiler {
+    fn drop(&mut self) {
+        self.write_exports();
+    }
+}
+
+impl From<TimestampKind> for EventPhase {
+    fn from(kind: TimestampKind) -> EventPhase {
+        match kind {
+            TimestampKind::Start => EventPhase::Start,
+            TimestampKind::End => EventPhase::End,
+            TimestampKind::Instant => EventPhase::Instant,
+        }
+    }
+}
+
+/// Pairs the start and end of the events of each thread. Events still running when the profile
+/// is written, for instance after a fatal error, end with the last recorded event.
+fn pair_events(events: &[ExportedEvent]) -> (Vec<Interval<'_>>, Vec<&ExportedEvent>) {
+    fn close<'a>(stack: &mut Vec<(&'a ExportedEvent, u64)>, end: u64,
+                 intervals: &mut Vec<Interval<'a>>) {
+        if let Some((event, nested_time)) = stack.pop() {
+            let duration = end.saturating_sub(event.timestamp);
+            if let Some(parent) = stack.last_mut() {
+                parent.1 += duration;
+            }
+            let mut labels = stack.iter().map(|&(e, _)| &e.label[..]).collect::<Vec<_>>();
+            labels.push(&event.label);
+            intervals.push(Interval {
+                event,
+                stack: labels,
+                duration,
+                self_time: duration.saturating_sub(nested_time),
+            });
+        }
+    }
+
+    let end_of_profile = events.last().map_or(0, |event| event.timestamp);
+    let mut intervals = Vec::new();
+    let mut instants = Vec::new();
+    // The open events of each thread, with the time spent in the events nested in them.
+    let mut open = FxHashMap::<u64, Vec<(&ExportedEvent, u64)>>::default();
+
+    for event in events {
+        let stack = open.entry(event.thread_id).or_default();
+        match event.phase {
+            EventPhase::Start => stack.push((event, 0)),
+            EventPhase::End => close(stack, event.timestamp, &mut intervals),
+            EventPhase::Instant => instants.push(event),
+        }
+    }
+    for stack in open.values_mut() {
+        while !stack.is_empty() {
+            close(stack, end_of_profile, &mut intervals);
+        }
+    }
+
+    intervals.sort_by_key(|interval| (interval.event.timestamp, interval.event.thread_id));
+    (intervals, instants)
+}
+
+/// Renders the events in the Chrome Trace Event format, with timestamps in microseconds.
+fn chrome_trace(intervals: &[Interval<'_>], instants: &[&ExportedEvent]) -> String {
+    let pid = process::id();
+    let mut out = String::from("{\"traceEvents\":[\n");
+    let mut first = true;
+    let mut separator = |out: &mut String| {
+        if !first {
+            out.push_str(",\n");
+        }
+        first = false;
+    };
+    for interval in intervals {
+        separator(&mut out);
+        let _ = write!(out, "{{\"name\":{},\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\
+                             \"dur\":{:.3},\"pid\":{},\"tid\":{}}}",
+                       json::as_json(&*interval.event.label),
+                       interval.event.kind,
+                       interval.event.timestamp as f64 / 1000.0,
+                       interval.duration as f64 / 1000.0,
+                       pid,
+                       interval.event.thread_id);
+    }
+    for event in instants {
+        separator(&mut out);
+        let _ = write!(out, "{{\"name\":{},\"cat\":\"{}\",\"ph\":\"i\",\"s\":\"t\",\
+                             \"ts\":{:.3},\"pid\":{},\"tid\":{}}}",
+                       json::as_json(&*event.label),
+                       event.kind,
+                       event.timestamp as f64 / 1000.0,
+                       pid,
+                       event.thread_id);
+    }
+    out.push_str("\n],\"displayTimeUnit\":\"ms\"}\n");
+    out
+}
+
+/// Renders the self time of each stack of events in nanoseconds, one stack per line with the
+/// events separated by `;`.
+fn folded_stacks(intervals: &[Interval<'_>]) -> String {
+    let mut stacks = FxHashMap::<String, u64>::default();
+    for interval in intervals {
+        // `;` separates the frames and would split query keys such as `[u8; 4]`.
+        let stack = interval.stack.iter()
+                                  .map(|label| label.replace(';', ","))
+                                  .collect::<Vec<_>>()
+                                  .join(";");
+        *stacks.entry(stack).or_insert(0) += interval.self_time;
+    }
+    let mut stacks = stacks.into_iter().collect::<Vec<_>>();
+    stacks.sort();
+    let mut out = String::new();
+    for (stack, self_time) in stacks {
+        let _ = writeln!(out, "{} {}", stack, self_time);
     }
+    out
 }
@@ -24,7 +24,7 @@ use crate::common;
 use crate::context::CodegenCx;
 use crate::monomorphize::partitioning::CodegenUnitExt;
 use rustc::dep_graph;
-use rustc::mir::mono::{Linkage, Visibility, Stats};
+use rustc::mir::mono::{Linkage, Visibility, Stats, MonoItem};
 use rustc::middle::cstore::{EncodedMetadata};
 use rustc::ty::TyCtxt;
 use rustc::middle::exported_symbols;
@@ -146,6 +146,23 @@ pub fn compile_codegen_unit<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                 mono_item.define::<Builder<'_, '_, '_>>(&cx);
             }
 
+            // The functions are measured before LLVM optimizes them, as emitted by rustc.
+            if cx.sess().opts.debugging_opts.print_mono_bloat {
+                for &(mono_item, _) in &mono_items {
+                    let instance = match mono_item {
+                        MonoItem::Fn(instance) => instance,
+                        MonoItem::Static(..) | MonoItem::GlobalAsm(..) => continue,
+                    };
+                    if let Some(&llfn) = cx.instances.borrow().get(&instance) {
+                        cx.stats.borrow_mut().mono_fn_sizes.push((
+                            instance.def_id(),
+                            tcx.symbol_name(instance).as_str().to_string(),
+                            llvm::count_instructions(llfn),
+                        ));
+                    }
+                }
+            }
+
             // If this codegen unit contains the main function, also create the
             // wrapper here
             maybe_create_entry_wrapper::<Builder<'_, '_, '_>>(&cx);
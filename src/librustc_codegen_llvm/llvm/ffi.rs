@@ -812,9 +812,12 @@ extern "C" {
                                          Name: *const c_char)
                                          -> &'a BasicBlock;
     pub fn LLVMDeleteBasicBlock(BB: &BasicBlock);
+    pub fn LLVMGetNextBasicBlock(BB: &BasicBlock) -> Option<&BasicBlock>;
 
     // Operations on instructions
     pub fn LLVMGetFirstBasicBlock(Fn: &Value) -> &BasicBlock;
+    pub fn LLVMGetFirstInstruction(BB: &BasicBlock) -> Option<&Value>;
+    pub fn LLVMGetNextInstruction(Inst: &Value) -> Option<&Value>;
 
     // Operations on call sites
     pub fn LLVMSetInstructionCallConv(Instr: &Value, CC: c_uint);
@@ -220,6 +220,26 @@ pub fn get_param(llfn: &'a Value, index: c_uint) -> &'a Value {
     }
 }
 
+/// Counts the instructions in the body of `llfn`, which is zero if it is only declared.
+pub fn count_instructions(llfn: &'a Value) -> usize {
+    unsafe {
+        if LLVMIsDeclaration(llfn) == True {
+            return 0;
+        }
+        let mut count = 0;
+        let mut llbb = Some(LLVMGetFirstBasicBlock(llfn));
+        while let Some(bb) = llbb {
+            let mut llinst = LLVMGetFirstInstruction(bb);
+            while let Some(inst) = llinst {
+                count += 1;
+                llinst = LLVMGetNextInstruction(inst);
+            }
+            llbb = LLVMGetNextBasicBlock(bb);
+        }
+        count
+    }
+}
+
 pub fn build_string(f: impl FnOnce(&RustString)) -> Result<String, FromUtf8Error> {
     let sr = RustString {
         bytes: RefCell::new(Vec::new()),
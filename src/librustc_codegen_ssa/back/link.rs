@@ -19,6 +19,7 @@ use super::archive::ArchiveBuilder;
 use super::command::Command;
 use super::linker::Linker;
 use super::rpath::{self, RPathConfig};
+use super::symbol_sizes::print_symbol_sizes;
 
 use cc::windows_registry;
 use tempfile::{Builder as TempFileBuilder, TempDir};
@@ -93,6 +94,12 @@ pub fn link_binary<'a, B: ArchiveBuilder<'a>>(sess: &'a Session,
                         tmpdir.path(),
                         target_cpu,
                     );
+                    if sess.opts.debugging_opts.print_symbol_sizes {
+                        print_symbol_sizes(sess,
+                                           crate_type,
+                                           &out_filename,
+                                           &codegen_results.linker_info);
+                    }
                 }
             }
             if sess.opts.debugging_opts.emit_artifact_notifications {
@@ -33,6 +33,11 @@ impl LinkerInfo {
         }
     }
 
+    /// The symbols exported from the given crate type.
+    pub fn exports(&self, crate_type: CrateType) -> &[String] {
+        self.exports.get(&crate_type).map_or(&[][..], |exports| &exports[..])
+    }
+
     pub fn to_linker<'a>(
         &'a self,
         cmd: Command,
@@ -6,4 +6,5 @@ pub mod command;
 pub mod symbol_export;
 pub mod archive;
 pub mod rpath;
+pub mod symbol_sizes;
 pub mod wasm;
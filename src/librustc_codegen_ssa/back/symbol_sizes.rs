@@ -0,0 +1,399 @@
+//! The `-Z print-symbol-sizes` report: the size of each symbol of a linked
+//! binary, attributed to the crates, modules and functions the symbols come
+//! from.
+//!
+//! The symbols are read from the symbol table of the linked binary, so the
+//! report reflects what the linker kept. Only ELF binaries are supported.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use rustc::session::Session;
+use rustc::session::config::CrateType;
+use rustc::util::nodemap::{FxHashMap, FxHashSet};
+use rustc_codegen_utils::symbol_names::demangle;
+use serialize::json::{Json, ToJson};
+
+use crate::back::linker::LinkerInfo;
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+const SHN_UNDEF: u16 = 0;
+
+/// The crate of the symbols that aren't Rust symbols, such as the functions
+/// of C libraries.
+const NATIVE_CRATE: &str = "<native>";
+
+#[derive(Copy, Clone, PartialEq)]
+enum SymbolKind {
+    Function,
+    Object,
+}
+
+struct ElfSymbol<'a> {
+    name: &'a str,
+    kind: SymbolKind,
+    size: u64,
+}
+
+/// Reads integers of the class and byte order of an ELF file.
+struct ElfReader<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    is_big_endian: bool,
+}
+
+impl<'a> ElfReader<'a> {
+    fn bytes(&self, offset: u64, len: u64) -> Option<&'a [u8]> {
+        let start = offset as usize;
+        let end = start.checked_add(len as usize)?;
+        self.data.get(start..end)
+    }
+
+    fn uint(&self, offset: u64, len: u64) -> Option<u64> {
+        let bytes = self.bytes(offset, len)?;
+        let mut value = 0;
+        if self.is_big_endian {
+            for &b in bytes {
+                value = value << 8 | b as u64;
+            }
+        } else {
+            for &b in bytes.iter().rev() {
+                value = value << 8 | b as u64;
+            }
+        }
+        Some(value)
+    }
+
+    fn u16(&self, offset: u64) -> Option<u16> {
+        self.uint(offset, 2).map(|v| v as u16)
+    }
+
+    fn u32(&self, offset: u64) -> Option<u32> {
+        self.uint(offset, 4).map(|v| v as u32)
+    }
+
+    /// Reads an address or offset, whose size depends on the class.
+    fn word(&self, offset: u64) -> Option<u64> {
+        self.uint(offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    fn str(&self, offset: u64) -> Option<&'a str> {
+        let bytes = self.data.get(offset as usize..)?;
+        let len = bytes.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&bytes[..len]).ok()
+    }
+}
+
+/// Returns the defined functions and objects of the symbol table of an ELF
+/// binary, or of its dynamic symbol table if it was stripped. Returns `None`
+/// if the file is not an ELF file or is malformed.
+fn elf_symbols(data: &[u8]) -> Option<Vec<ElfSymbol<'_>>> {
+    if !data.starts_with(b"\x7fELF") {
+        return None;
+    }
+    let r = ElfReader {
+        data,
+        is_64: *data.get(4)? == 2,
+        is_big_endian: *data.get(5)? == 2,
+    };
+
+    let (shoff, shentsize, shnum) = if r.is_64 {
+        (r.word(0x28)?, r.u16(0x3a)?, r.u16(0x3c)?)
+    } else {
+        (r.word(0x20)?, r.u16(0x2e)?, r.u16(0x30)?)
+    };
+    // The offsets of the fields of a section header, after `sh_name`.
+    let (sh_type, sh_offset, sh_size, sh_link) = if r.is_64 {
+        (4, 24, 32, 40)
+    } else {
+        (4, 16, 20, 24)
+    };
+    let section = |i: u64| shoff + i * shentsize as u64;
+
+    let table = (0..shnum as u64).find(|&i| r.u32(section(i) + sh_type) == Some(SHT_SYMTAB))
+        .or_else(|| {
+            (0..shnum as u64).find(|&i| r.u32(section(i) + sh_type) == Some(SHT_DYNSYM))
+        })?;
+    let offset = r.word(section(table) + sh_offset)?;
+    let size = r.word(section(table) + sh_size)?;
+    let strtab = r.u32(section(table) + sh_link)? as u64;
+    let strtab_offset = r.word(section(strtab) + sh_offset)?;
+
+    // The size of a symbol and the offsets of `st_value`, `st_size`,
+    // `st_info` and `st_shndx`.
+    let (entsize, st_value, st_size, st_info, st_shndx) = if r.is_64 {
+        (24, 8, 16, 4, 6)
+    } else {
+        (16, 4, 8, 12, 14)
+    };
+    let mut seen = FxHashSet::default();
+    let mut symbols = Vec::new();
+    for i in 0..size / entsize {
+        let sym = offset + i * entsize;
+        let kind = match r.bytes(sym + st_info, 1)?[0] & 0xf {
+            STT_FUNC => SymbolKind::Function,
+            STT_OBJECT => SymbolKind::Object,
+            _ => continue,
+        };
+        let shndx = r.u16(sym + st_shndx)?;
+        let size = r.word(sym + st_size)?;
+        if shndx == SHN_UNDEF || size == 0 {
+            continue;
+        }
+        // Aliases of a symbol are counted once.
+        if !seen.insert((shndx, r.word(sym + st_value)?)) {
+            continue;
+        }
+        let name = match r.str(strtab_offset + r.u32(sym)? as u64) {
+            Some(name) => name,
+            None => continue,
+        };
+        symbols.push(ElfSymbol { name, kind, size });
+    }
+    Some(symbols)
+}
+
+/// Splits a demangled path at the `::` that are not inside generic
+/// arguments or qualified paths.
+fn split_path(path: &str) -> Vec<&str> {
+    let bytes = path.as_bytes();
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' => depth += 1,
+            // Not the `>` of a `->` in a function pointer type.
+            b'>' if i == 0 || bytes[i - 1] != b'-' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                segments.push(&path[start..i]);
+                i += 2;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+/// Removes the generic arguments ending a demangled path, such as the
+/// `::<u8>` of `foo::bar::<u8>`, which tell the instances of a generic
+/// function apart.
+fn strip_generic_args(path: &str) -> &str {
+    if !path.ends_with('>') {
+        return path;
+    }
+    let bytes = path.as_bytes();
+    let mut depth = 0usize;
+    for i in (0..bytes.len()).rev() {
+        match bytes[i] {
+            // Not the `>` of a `->` in a function pointer type.
+            b'>' if i == 0 || bytes[i - 1] != b'-' => depth += 1,
+            b'<' => {
+                depth -= 1;
+                if depth == 0 {
+                    return if path[..i].ends_with("::") { &path[..i - 2] } else { path };
+                }
+            }
+            _ => {}
+        }
+    }
+    path
+}
+
+/// The crate of a demangled path. Symbols of trait implementations, such as
+/// `<alloc::vec::Vec<T> as core::ops::Drop>::drop`, are attributed to the
+/// crate of their `Self` type, or to the crate of the trait when `Self` is not
+/// a path starting with a crate, as in `<i32 as core::fmt::Display>::fmt` or
+/// `<&T as core::fmt::Display>::fmt`.
+fn crate_of(path: &str) -> &str {
+    if let Some((self_ty, trait_)) = split_trait_impl(path) {
+        let (self_name, self_rest) = leading_name(self_ty);
+        if self_rest.starts_with("::") {
+            return self_name;
+        }
+        return leading_name(trait_).0;
+    }
+    leading_name(path).0
+}
+
+/// The first name of a path or type, after any references, pointers and
+/// brackets, along with the rest of it.
+fn leading_name(path: &str) -> (&str, &str) {
+    let mut path = path.trim_start_matches(|c| c == '<' || c == '&' || c == '*' || c == '[');
+    for prefix in &["mut ", "const ", "dyn "] {
+        if path.starts_with(prefix) {
+            path = &path[prefix.len()..];
+        }
+    }
+    let end = path.find(|c| c == ':' || c == '<' || c == '>' || c == ' ' || c == ';' ||
+                            c == ']' || c == ',' || c == '(' || c == ')')
+                  .unwrap_or(path.len());
+    (&path[..end], &path[end..])
+}
+
+/// Splits a path starting with `<Self as Trait>` into `Self` and the path
+/// starting with `Trait`.
+fn split_trait_impl(path: &str) -> Option<(&str, &str)> {
+    if !path.starts_with('<') {
+        return None;
+    }
+    let bytes = path.as_bytes();
+    let mut depth = 0usize;
+    for i in 0..bytes.len() {
+        match bytes[i] {
+            b'<' | b'(' | b'[' => depth += 1,
+            // Not the `>` of a `->` in a function pointer type.
+            b'>' if i == 0 || bytes[i - 1] != b'-' => depth -= 1,
+            b')' | b']' => depth -= 1,
+            b' ' if depth == 1 && path[i..].starts_with(" as ") => {
+                return Some((&path[1..i], &path[i + 4..]));
+            }
+            _ => {}
+        }
+        if depth == 0 {
+            return None;
+        }
+    }
+    None
+}
+
+#[derive(Default)]
+struct Group {
+    size: u64,
+    symbols: usize,
+}
+
+fn add_to_group(groups: &mut FxHashMap<String, Group>, key: &str, size: u64) {
+    let group = groups.entry(key.to_string()).or_default();
+    group.size += size;
+    group.symbols += 1;
+}
+
+fn groups_to_json(groups: FxHashMap<String, Group>, key: &str, count: &str) -> Json {
+    let mut groups = groups.into_iter().collect::<Vec<_>>();
+    groups.sort_by(|a, b| b.1.size.cmp(&a.1.size).then_with(|| a.0.cmp(&b.0)));
+    Json::Array(groups.into_iter().map(|(name, group)| {
+        let mut obj = BTreeMap::new();
+        obj.insert(key.to_string(), name.to_json());
+        obj.insert("size".to_string(), group.size.to_json());
+        obj.insert(count.to_string(), group.symbols.to_json());
+        Json::Object(obj)
+    }).collect())
+}
+
+/// Prints the report of the sizes of the symbols of the linked binary
+/// `out_filename` as JSON.
+pub fn print_symbol_sizes(sess: &Session,
+                          crate_type: CrateType,
+                          out_filename: &Path,
+                          linker_info: &LinkerInfo) {
+    let data = match fs::read(out_filename) {
+        Ok(data) => data,
+        Err(e) => {
+            sess.err(&format!("failed to read `{}` for the symbol sizes: {}",
+                              out_filename.display(), e));
+            return;
+        }
+    };
+    let symbols = elf_symbols(&data).unwrap_or_default();
+    if symbols.is_empty() {
+        sess.warn(&format!("no symbol sizes for `{}`: only ELF binaries with a symbol \
+                            table are supported",
+                           out_filename.display()));
+        return;
+    }
+    let exports = linker_info.exports(crate_type)
+                             .iter()
+                             .map(|name| &name[..])
+                             .collect::<FxHashSet<_>>();
+
+    let mut total = 0;
+    let mut crates = FxHashMap::<String, Group>::default();
+    let mut modules = FxHashMap::<String, Group>::default();
+    let mut functions = FxHashMap::<String, Group>::default();
+    let mut symbol_list = Vec::new();
+    for symbol in symbols {
+        total += symbol.size;
+        // The alternate format leaves out the hash, which is all that tells
+        // the instances of a generic function apart in legacy symbols. The
+        // generic arguments of v0 symbols are left out separately.
+        let demangled = match demangle::demangle(symbol.name) {
+            Some(d) => Some(strip_generic_args(&format!("{:#}", d)).to_string()),
+            None => rustc_demangle::try_demangle(symbol.name)
+                                   .ok()
+                                   .map(|d| format!("{:#}", d)),
+        };
+        let (krate, module) = match demangled {
+            Some(ref path) => {
+                let segments = split_path(path);
+                let module = if segments.len() > 1 {
+                    segments[..segments.len() - 1].join("::")
+                } else {
+                    crate_of(path).to_string()
+                };
+                (crate_of(path).to_string(), module)
+            }
+            None => (NATIVE_CRATE.to_string(), NATIVE_CRATE.to_string()),
+        };
+        add_to_group(&mut crates, &krate, symbol.size);
+        add_to_group(&mut modules, &module, symbol.size);
+        if let (Some(path), SymbolKind::Function) = (&demangled, symbol.kind) {
+            add_to_group(&mut functions, path, symbol.size);
+        }
+
+        let mut obj = BTreeMap::new();
+        obj.insert("name".to_string(),
+                   demangled.clone().unwrap_or_else(|| symbol.name.to_string()).to_json());
+        obj.insert("symbol".to_string(), symbol.name.to_json());
+        obj.insert("kind".to_string(), match symbol.kind {
+            SymbolKind::Function => "function",
+            SymbolKind::Object => "object",
+        }.to_json());
+        obj.insert("size".to_string(), symbol.size.to_json());
+        obj.insert("crate".to_string(), krate.to_json());
+        obj.insert("exported".to_string(), exports.contains(symbol.name).to_json());
+        symbol_list.push((symbol.size, symbol.name, Json::Object(obj)));
+    }
+    symbol_list.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    let mut report = BTreeMap::new();
+    report.insert("file".to_string(), out_filename.display().to_string().to_json());
+    report.insert("total_size".to_string(), total.to_json());
+    report.insert("crates".to_string(), groups_to_json(crates, "name", "symbols"));
+    report.insert("modules".to_string(), groups_to_json(modules, "path", "symbols"));
+    report.insert("functions".to_string(), groups_to_json(functions, "path", "instances"));
+    report.insert("symbols".to_string(),
+                  Json::Array(symbol_list.into_iter().map(|(_, _, obj)| obj).collect()));
+    println!("{}", Json::Object(report).pretty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crate_of;
+
+    #[test]
+    fn crate_of_paths() {
+        assert_eq!(crate_of("core::fmt::write"), "core");
+        assert_eq!(crate_of("main"), "main");
+        assert_eq!(crate_of("<alloc::vec::Vec<T> as core::ops::drop::Drop>::drop"), "alloc");
+        assert_eq!(crate_of("<&mut std::io::Stdout as std::io::Write>::flush"), "std");
+    }
+
+    #[test]
+    fn crate_of_impls_for_non_paths() {
+        assert_eq!(crate_of("<i32 as core::fmt::Display>::fmt"), "core");
+        assert_eq!(crate_of("<&T as core::fmt::Display>::fmt"), "core");
+        assert_eq!(crate_of("<[T] as core::fmt::Debug>::fmt"), "core");
+        assert_eq!(crate_of("<(A, B) as foo::Trait>::method"), "foo");
+        assert_eq!(crate_of("<fn() -> u8 as foo::Trait>::method"), "foo");
+    }
+}
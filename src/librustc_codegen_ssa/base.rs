@@ -16,7 +16,7 @@
 use crate::{ModuleCodegen, ModuleKind, CachedModuleCodegen};
 
 use rustc::dep_graph::cgu_reuse_tracker::CguReuse;
-use rustc::hir::def_id::{DefId, LOCAL_CRATE};
+use rustc::hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use rustc::middle::cstore::EncodedMetadata;
 use rustc::middle::lang_items::StartFnLangItem;
 use rustc::middle::weak_lang_items;
@@ -31,7 +31,7 @@ use rustc::session::Session;
 use rustc_mir::monomorphize::item::DefPathBasedNames;
 use rustc_mir::monomorphize::Instance;
 use rustc_mir::monomorphize::partitioning::{CodegenUnit, CodegenUnitExt};
-use rustc::util::nodemap::FxHashMap;
+use rustc::util::nodemap::{FxHashMap, FxHashSet};
 use rustc_data_structures::indexed_vec::Idx;
 use rustc_data_structures::sync::Lrc;
 use rustc_codegen_utils::{symbol_names_test, check_for_rustc_errors_attr};
@@ -655,6 +655,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
 
     let mut total_codegen_time = Duration::new(0, 0);
     let mut all_stats = Stats::default();
+    let mut reused_cgus = 0;
 
     for cgu in codegen_units.into_iter() {
         ongoing_codegen.wait_for_signal_to_codegen_item();
@@ -674,6 +675,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
                 false
             }
             CguReuse::PreLto => {
+                reused_cgus += 1;
                 submit_pre_lto_module_to_llvm(&backend, tcx, CachedModuleCodegen {
                     name: cgu.name().to_string(),
                     source: cgu.work_product(tcx),
@@ -681,6 +683,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
                 true
             }
             CguReuse::PostLto => {
+                reused_cgus += 1;
                 submit_post_lto_module_to_llvm(&backend, tcx, CachedModuleCodegen {
                     name: cgu.name().to_string(),
                     source: cgu.work_product(tcx),
@@ -724,12 +727,87 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
         }
     }
 
+    if tcx.sess.opts.debugging_opts.print_mono_bloat {
+        print_mono_bloat(tcx, &all_stats.mono_fn_sizes, reused_cgus);
+    }
+
     ongoing_codegen.check_for_errors(tcx.sess);
 
     assert_and_save_dep_graph(tcx);
     ongoing_codegen.into_inner()
 }
 
+#[derive(Default)]
+struct MonoCost {
+    /// The number of distinct instances.
+    instances: usize,
+    /// The number of instances summed over the codegen units they are
+    /// emitted in, which counts the copies of inlined functions.
+    copies: usize,
+    /// The number of LLVM instructions of the copies, before optimization.
+    insns: usize,
+}
+
+impl MonoCost {
+    fn add(&mut self, other: &MonoCost) {
+        self.instances += other.instances;
+        self.copies += other.copies;
+        self.insns += other.insns;
+    }
+}
+
+/// Prints the size of the LLVM IR emitted for the functions of the codegen
+/// units, summed for all the instances of each function and for each crate
+/// the functions come from, the largest first. `sizes` has an entry for each
+/// copy of an instance, identified by its symbol.
+fn print_mono_bloat(tcx: TyCtxt<'_, '_, '_>,
+                    sizes: &[(DefId, String, usize)],
+                    reused_cgus: usize) {
+    let mut seen = FxHashSet::default();
+    let mut per_fn = FxHashMap::<DefId, MonoCost>::default();
+    for &(def_id, ref symbol, insns) in sizes {
+        let cost = per_fn.entry(def_id).or_default();
+        if seen.insert(symbol) {
+            cost.instances += 1;
+        }
+        cost.copies += 1;
+        cost.insns += insns;
+    }
+
+    let mut per_crate = FxHashMap::<CrateNum, MonoCost>::default();
+    for (def_id, cost) in &per_fn {
+        per_crate.entry(def_id.krate).or_default().add(cost);
+    }
+
+    let mut per_fn = per_fn.into_iter()
+        .map(|(def_id, cost)| (tcx.def_path_str(def_id), cost))
+        .collect::<Vec<_>>();
+    let mut per_crate = per_crate.into_iter()
+        .map(|(cnum, cost)| (tcx.crate_name(cnum).to_string(), cost))
+        .collect::<Vec<_>>();
+    let by_cost = |a: &(String, MonoCost), b: &(String, MonoCost)| {
+        b.1.insns.cmp(&a.1.insns).then_with(|| a.0.cmp(&b.0))
+    };
+    per_fn.sort_by(by_cost);
+    per_crate.sort_by(by_cost);
+
+    // Codegen units reused by incremental compilation are not codegened again.
+    if reused_cgus > 0 {
+        println!("note: the functions of {} codegen units reused from the incremental cache \
+                  are not counted", reused_cgus);
+        println!();
+    }
+    println!("{:>10} {:>8} {:>10}  function", "llvm insns", "copies", "instances");
+    for (name, cost) in &per_fn {
+        println!("{:>10} {:>8} {:>10}  {}", cost.insns, cost.copies, cost.instances, name);
+    }
+    println!();
+    println!("{:>10} {:>8} {:>10}  crate", "llvm insns", "copies", "instances");
+    for (name, cost) in &per_crate {
+        println!("{:>10} {:>8} {:>10}  {}", cost.insns, cost.copies, cost.instances, name);
+    }
+}
+
 /// A curious wrapper structure whose only purpose is to call `codegen_aborted`
 /// when it's dropped abnormally.
 ///
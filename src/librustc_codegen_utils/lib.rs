@@ -9,6 +9,7 @@
 #![feature(box_syntax)]
 #![feature(custom_attribute)]
 #![feature(nll)]
+#![feature(never_type)]
 #![allow(unused_attributes)]
 #![feature(rustc_diagnostic_macros)]
 #![feature(in_band_lifetimes)]
@@ -0,0 +1,699 @@
+//! A demangler for the v0 symbol names (see the `v0` module), turning them
+//! back into the paths they were produced from.
+//!
+//! The symbol is printed while it is parsed, following backreferences by
+//! parsing again from the position they point to. Printing with `{:#}`
+//! leaves out the crate disambiguators.
+
+use super::punycode;
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::mem;
+
+/// The maximum nesting of paths and types, which bounds the recursion of the
+/// printer, as backreferences could otherwise make it loop forever.
+const MAX_DEPTH: u32 = 500;
+
+/// The maximum length of a demangled symbol. Backreferences to paths which
+/// contain backreferences themselves let a short symbol expand to an output
+/// exponentially longer, so symbols that would exceed it are rejected.
+const MAX_SIZE: usize = 1_000_000;
+
+/// A v0 symbol name, checked to be well-formed.
+pub struct Demangle<'a> {
+    /// The symbol, without the `_R` prefix and the suffix.
+    inner: &'a str,
+    /// What follows the symbol, e.g. `.llvm.1234` added by LLVM.
+    suffix: &'a str,
+}
+
+/// Parses a v0 symbol name, returning `None` if `symbol` isn't one.
+pub fn demangle(symbol: &str) -> Option<Demangle<'_>> {
+    // Depending on the platform, the symbol may have an underscore added in
+    // front of it, or its own underscore removed.
+    let inner = if symbol.starts_with("_R") {
+        &symbol[2..]
+    } else if symbol.starts_with("__R") {
+        &symbol[3..]
+    } else if symbol.starts_with('R') {
+        &symbol[1..]
+    } else {
+        return None;
+    };
+
+    // Only the first version of the mangling, which has no version number,
+    // is supported.
+    if inner.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let (inner, suffix) = match inner.find('.') {
+        Some(i) => inner.split_at(i),
+        None => (inner, ""),
+    };
+    if !inner.bytes().all(|b| b == b'_' || b.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    let demangle = Demangle { inner, suffix };
+    demangle.render(false)?;
+    Some(demangle)
+}
+
+impl Demangle<'_> {
+    fn render(&self, alternate: bool) -> Option<String> {
+        let mut printer = Printer {
+            sym: self.inner,
+            next: 0,
+            out: String::new(),
+            alternate,
+            bound_lifetime_depth: 0,
+            depth: 0,
+        };
+        printer.print_path(true)?;
+        // The instantiating crate only tells apart the copies of an instance,
+        // it isn't printed.
+        if printer.next < printer.sym.len() {
+            printer.skip_path()?;
+        }
+        if printer.next != printer.sym.len() {
+            return None;
+        }
+        printer.out.push_str(self.suffix);
+        Some(printer.out)
+    }
+}
+
+impl fmt::Display for Demangle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The symbol was checked by `demangle`, so it can always be printed.
+        match self.render(f.alternate()) {
+            Some(s) => f.write_str(&s),
+            None => f.write_str("{invalid syntax}"),
+        }
+    }
+}
+
+fn basic_type(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        b'b' => "bool",
+        b'c' => "char",
+        b'e' => "str",
+        b'u' => "()",
+        b'a' => "i8",
+        b's' => "i16",
+        b'l' => "i32",
+        b'x' => "i64",
+        b'n' => "i128",
+        b'i' => "isize",
+        b'h' => "u8",
+        b't' => "u16",
+        b'm' => "u32",
+        b'y' => "u64",
+        b'o' => "u128",
+        b'j' => "usize",
+        b'f' => "f32",
+        b'd' => "f64",
+        b'z' => "!",
+        b'p' => "_",
+        b'v' => "...",
+        _ => return None,
+    })
+}
+
+struct Printer<'a> {
+    sym: &'a str,
+    /// The position of the next byte of `sym` to parse.
+    next: usize,
+    out: String,
+    alternate: bool,
+    /// The number of lifetimes bound by the binders being printed.
+    bound_lifetime_depth: u32,
+    depth: u32,
+}
+
+impl<'a> Printer<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.sym.as_bytes().get(self.next).cloned()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.next += 1;
+        Some(b)
+    }
+
+    fn enter(&mut self) -> Option<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH || self.out.len() > MAX_SIZE {
+            return None;
+        }
+        Some(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Parses a `<base-62-number>`: `_` is 0, and `x_` is `x + 1`.
+    fn integer_62(&mut self) -> Option<u64> {
+        if self.eat(b'_') {
+            return Some(0);
+        }
+        let mut x = 0u64;
+        while !self.eat(b'_') {
+            let d = match self.next()? {
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'z' => 10 + c - b'a',
+                c @ b'A'..=b'Z' => 36 + c - b'A',
+                _ => return None,
+            };
+            x = x.checked_mul(62)?.checked_add(d as u64)?;
+        }
+        x.checked_add(1)
+    }
+
+    /// Parses a `tag`-prefixed `<base-62-number>`, which is 0 if absent and
+    /// otherwise the number plus one.
+    fn opt_integer_62(&mut self, tag: u8) -> Option<u64> {
+        if !self.eat(tag) {
+            return Some(0);
+        }
+        self.integer_62()?.checked_add(1)
+    }
+
+    fn disambiguator(&mut self) -> Option<u64> {
+        self.opt_integer_62(b's')
+    }
+
+    fn hex_nibbles(&mut self) -> Option<&'a str> {
+        let start = self.next;
+        loop {
+            match self.next()? {
+                b'0'..=b'9' | b'a'..=b'f' => {}
+                b'_' => break,
+                _ => return None,
+            }
+        }
+        Some(&self.sym[start..self.next - 1])
+    }
+
+    fn ident(&mut self) -> Option<Cow<'a, str>> {
+        let is_punycode = self.eat(b'u');
+        let mut len = match self.next()? {
+            c @ b'0'..=b'9' => (c - b'0') as usize,
+            _ => return None,
+        };
+        if len != 0 {
+            while let Some(c @ b'0'..=b'9') = self.peek() {
+                len = len.checked_mul(10)?.checked_add((c - b'0') as usize)?;
+                self.next += 1;
+            }
+        }
+
+        // Skip the `_` separating the length from an identifier starting
+        // with a digit or an `_`.
+        self.eat(b'_');
+
+        let start = self.next;
+        self.next = self.next.checked_add(len)?;
+        let ident = self.sym.get(start..self.next)?;
+
+        if !is_punycode {
+            return Some(Cow::Borrowed(ident));
+        }
+        // The `-` separating the ASCII characters from the encoded ones was
+        // replaced with an `_`.
+        let (basic, encoded) = match ident.rfind('_') {
+            Some(i) => (&ident[..i], &ident[i + 1..]),
+            None => ("", ident),
+        };
+        if encoded.is_empty() {
+            return None;
+        }
+        punycode::decode(basic, encoded).map(Cow::Owned)
+    }
+
+    /// Parses a backreference, whose `B` was just parsed, and calls `f` at
+    /// the position it refers to.
+    fn print_backref(&mut self, f: impl FnOnce(&mut Self) -> Option<()>) -> Option<()> {
+        let tag_pos = self.next - 1;
+        let target = self.integer_62()?;
+        if target >= tag_pos as u64 {
+            return None;
+        }
+        let orig_next = mem::replace(&mut self.next, target as usize);
+        f(self)?;
+        self.next = orig_next;
+        Some(())
+    }
+
+    fn print_lifetime(&mut self, lt: u64) -> Option<()> {
+        self.out.push('\'');
+        if lt == 0 {
+            self.out.push('_');
+            return Some(());
+        }
+        let depth = (self.bound_lifetime_depth as u64).checked_sub(lt)?;
+        if depth < 26 {
+            self.out.push((b'a' + depth as u8) as char);
+        } else {
+            let _ = write!(self.out, "_{}", depth);
+        }
+        Some(())
+    }
+
+    /// Prints the lifetimes of an optional binder, as `for<'a, 'b> `, then
+    /// calls `f` with the lifetimes in scope.
+    fn in_binder(&mut self, f: impl FnOnce(&mut Self) -> Option<()>) -> Option<()> {
+        let bound_lifetimes = self.opt_integer_62(b'G')?;
+        if bound_lifetimes > MAX_DEPTH as u64 {
+            return None;
+        }
+        let bound_lifetimes = bound_lifetimes as u32;
+
+        if bound_lifetimes > 0 {
+            self.out.push_str("for<");
+            for i in 0..bound_lifetimes {
+                if i > 0 {
+                    self.out.push_str(", ");
+                }
+                self.bound_lifetime_depth += 1;
+                self.print_lifetime(1)?;
+            }
+            self.out.push_str("> ");
+        }
+        f(self)?;
+        self.bound_lifetime_depth -= bound_lifetimes;
+        Some(())
+    }
+
+    /// Prints a path. In a value, its generic arguments are printed after
+    /// `::`, as in `foo::<u8>`.
+    fn print_path(&mut self, in_value: bool) -> Option<()> {
+        self.enter()?;
+        let tag = self.next()?;
+        match tag {
+            b'C' => {
+                let dis = self.disambiguator()?;
+                let name = self.ident()?;
+                self.out.push_str(&name);
+                if !self.alternate {
+                    let _ = write!(self.out, "[{:x}]", dis);
+                }
+            }
+            b'N' => {
+                let ns = self.next()?;
+                if !ns.is_ascii_alphabetic() {
+                    return None;
+                }
+                self.print_path(in_value)?;
+                let dis = self.disambiguator()?;
+                let name = self.ident()?;
+                if ns.is_ascii_uppercase() {
+                    // Special namespaces, like closures and shims.
+                    self.out.push_str("::{");
+                    match ns {
+                        b'C' => self.out.push_str("closure"),
+                        b'S' => self.out.push_str("shim"),
+                        _ => self.out.push(ns as char),
+                    }
+                    if !name.is_empty() {
+                        self.out.push(':');
+                        self.out.push_str(&name);
+                    }
+                    let _ = write!(self.out, "#{}}}", dis);
+                } else if !name.is_empty() {
+                    self.out.push_str("::");
+                    self.out.push_str(&name);
+                } else {
+                    let _ = write!(self.out, "::{{{}#{}}}", ns as char, dis);
+                }
+            }
+            b'M' | b'X' | b'Y' => {
+                if tag != b'Y' {
+                    // The path of the impl only tells it apart from the other
+                    // impls of the same type.
+                    self.disambiguator()?;
+                    self.skip_path()?;
+                }
+                self.out.push('<');
+                self.print_type()?;
+                if tag != b'M' {
+                    self.out.push_str(" as ");
+                    self.print_path(false)?;
+                }
+                self.out.push('>');
+            }
+            b'I' => {
+                self.print_path(in_value)?;
+                if in_value {
+                    self.out.push_str("::");
+                }
+                self.out.push('<');
+                self.print_generic_args()?;
+                self.out.push('>');
+            }
+            b'B' => self.print_backref(|p| p.print_path(in_value))?,
+            _ => return None,
+        }
+        self.leave();
+        Some(())
+    }
+
+    /// Parses a path without printing it.
+    fn skip_path(&mut self) -> Option<()> {
+        let len = self.out.len();
+        self.print_path(false)?;
+        self.out.truncate(len);
+        Some(())
+    }
+
+    /// Prints the generic arguments up to their `E`, without the delimiters.
+    fn print_generic_args(&mut self) -> Option<()> {
+        let mut first = true;
+        while !self.eat(b'E') {
+            if !first {
+                self.out.push_str(", ");
+            }
+            first = false;
+            if self.eat(b'L') {
+                let lt = self.integer_62()?;
+                self.print_lifetime(lt)?;
+            } else if self.eat(b'K') {
+                self.print_const()?;
+            } else {
+                self.print_type()?;
+            }
+        }
+        Some(())
+    }
+
+    fn print_type(&mut self) -> Option<()> {
+        self.enter()?;
+        let tag = self.next()?;
+        if let Some(name) = basic_type(tag) {
+            self.out.push_str(name);
+            self.leave();
+            return Some(());
+        }
+
+        match tag {
+            b'R' | b'Q' => {
+                self.out.push('&');
+                if self.eat(b'L') {
+                    let lt = self.integer_62()?;
+                    if lt != 0 {
+                        self.print_lifetime(lt)?;
+                        self.out.push(' ');
+                    }
+                }
+                if tag == b'Q' {
+                    self.out.push_str("mut ");
+                }
+                self.print_type()?;
+            }
+            b'P' => {
+                self.out.push_str("*const ");
+                self.print_type()?;
+            }
+            b'O' => {
+                self.out.push_str("*mut ");
+                self.print_type()?;
+            }
+            b'A' | b'S' => {
+                self.out.push('[');
+                self.print_type()?;
+                if tag == b'A' {
+                    self.out.push_str("; ");
+                    self.print_const()?;
+                }
+                self.out.push(']');
+            }
+            b'T' => {
+                self.out.push('(');
+                let mut count = 0;
+                while !self.eat(b'E') {
+                    if count > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.print_type()?;
+                    count += 1;
+                }
+                if count == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            b'F' => self.in_binder(|p| {
+                let is_unsafe = p.eat(b'U');
+                let abi = if p.eat(b'K') {
+                    if p.eat(b'C') {
+                        Some(Cow::Borrowed("C"))
+                    } else {
+                        let abi = p.ident()?;
+                        if abi.is_empty() {
+                            return None;
+                        }
+                        Some(abi)
+                    }
+                } else {
+                    None
+                };
+
+                if is_unsafe {
+                    p.out.push_str("unsafe ");
+                }
+                if let Some(abi) = abi {
+                    // The `-` of ABI names were replaced with `_`.
+                    let _ = write!(p.out, "extern \"{}\" ", abi.replace('_', "-"));
+                }
+                p.out.push_str("fn(");
+                let mut first = true;
+                while !p.eat(b'E') {
+                    if !first {
+                        p.out.push_str(", ");
+                    }
+                    first = false;
+                    p.print_type()?;
+                }
+                p.out.push(')');
+                if !p.eat(b'u') {
+                    p.out.push_str(" -> ");
+                    p.print_type()?;
+                }
+                Some(())
+            })?,
+            b'D' => {
+                self.out.push_str("dyn ");
+                self.in_binder(|p| {
+                    let mut first = true;
+                    while !p.eat(b'E') {
+                        if !first {
+                            p.out.push_str(" + ");
+                        }
+                        first = false;
+                        p.print_dyn_trait()?;
+                    }
+                    Some(())
+                })?;
+                if !self.eat(b'L') {
+                    return None;
+                }
+                let lt = self.integer_62()?;
+                if lt != 0 {
+                    self.out.push_str(" + ");
+                    self.print_lifetime(lt)?;
+                }
+            }
+            b'B' => self.print_backref(|p| p.print_type())?,
+            _ => {
+                // Nominal types are paths.
+                self.next -= 1;
+                self.print_path(false)?;
+            }
+        }
+        self.leave();
+        Some(())
+    }
+
+    /// Prints a trait of a `dyn` type, with its associated type bindings
+    /// inside its generic arguments, as in `Iterator<Item = u8>`.
+    fn print_dyn_trait(&mut self) -> Option<()> {
+        let mut open = self.print_path_maybe_open_generics()?;
+        while self.eat(b'p') {
+            if open {
+                self.out.push_str(", ");
+            } else {
+                self.out.push('<');
+                open = true;
+            }
+            let name = self.ident()?;
+            self.out.push_str(&name);
+            self.out.push_str(" = ");
+            self.print_type()?;
+        }
+        if open {
+            self.out.push('>');
+        }
+        Some(())
+    }
+
+    /// Prints a path, leaving its generic arguments open if it has any.
+    /// Returns whether they were left open.
+    fn print_path_maybe_open_generics(&mut self) -> Option<bool> {
+        if self.eat(b'B') {
+            let mut open = false;
+            self.print_backref(|p| {
+                open = p.print_path_maybe_open_generics()?;
+                Some(())
+            })?;
+            Some(open)
+        } else if self.eat(b'I') {
+            self.print_path(false)?;
+            self.out.push('<');
+            self.print_generic_args()?;
+            Some(true)
+        } else {
+            self.print_path(false)?;
+            Some(false)
+        }
+    }
+
+    fn print_const(&mut self) -> Option<()> {
+        if self.eat(b'B') {
+            return self.print_backref(|p| p.print_const());
+        }
+        match self.next()? {
+            // A placeholder, for a constant that isn't known.
+            b'p' => {
+                self.out.push('_');
+                return Some(());
+            }
+            // Only unsigned integers are supported.
+            b'h' | b't' | b'm' | b'y' | b'o' | b'j' => {}
+            _ => return None,
+        }
+        if self.eat(b'p') {
+            self.out.push('_');
+            return Some(());
+        }
+        let hex = self.hex_nibbles()?;
+        if hex.is_empty() || hex.len() > 32 {
+            return None;
+        }
+        let value = u128::from_str_radix(hex, 16).ok()?;
+        let _ = write!(self.out, "{}", value);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::demangle;
+
+    fn check(symbol: &str, expected: &str, expected_alt: &str) {
+        let demangled = demangle(symbol).unwrap_or_else(|| panic!("{:?} was rejected", symbol));
+        assert_eq!(demangled.to_string(), expected);
+        assert_eq!(format!("{:#}", demangled), expected_alt);
+    }
+
+    #[test]
+    fn paths() {
+        check("_RNvC6_123foo3bar", "123foo[0]::bar", "123foo::bar");
+        check("_RNvCs4fqI2P2rA04_7mycrate4main",
+              "mycrate[317d481089b8c8fe]::main",
+              "mycrate::main");
+        check("_RNvMNtCs4fqI2P2rA04_7mycrate3fooNtB2_3Foo3bar",
+              "<mycrate[317d481089b8c8fe]::foo::Foo>::bar",
+              "<mycrate::foo::Foo>::bar");
+        check("_RINvC7mycrate3fooTuhEE", "mycrate[0]::foo::<((), u8)>", "mycrate::foo::<((), u8)>");
+        check("_RNvC7mycrateu9bcher_kva", "mycrate[0]::bücher", "mycrate::bücher");
+    }
+
+    #[test]
+    fn prefixes_and_suffixes() {
+        check("__RNvC1a1b", "a[0]::b", "a::b");
+        check("RNvC1a1b", "a[0]::b", "a::b");
+        check("_RNvC7mycrate3foo.llvm.1234", "mycrate[0]::foo.llvm.1234", "mycrate::foo.llvm.1234");
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = [
+            "",
+            "_R",
+            "_RNv",
+            "foo",
+            "_ZN3foo3barE",
+            // Unsupported version.
+            "_R1NvC1a1b",
+            // Trailing characters.
+            "_RNvC7mycrate3foo_",
+            // Backreferences must point before themselves.
+            "_RB_",
+            "_RNvB0_1a",
+            // Identifiers can't be longer than the symbol.
+            "_RNvC1a9b",
+            "_RNvC1a99999999999999999999999b",
+            // Invalid punycode.
+            "_RNvC1au3bc_",
+            "_RNvC1au1A",
+            // Base-62 numbers must not overflow.
+            "_RNvCsZZZZZZZZZZZZZZZ_1a1b",
+        ];
+        for symbol in invalid.iter() {
+            assert!(demangle(symbol).is_none(), "{:?} was accepted", symbol);
+        }
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let symbol = format!("_R{}C1a{}", "I".repeat(10_000), "E".repeat(10_000));
+        assert!(demangle(&symbol).is_none());
+    }
+
+    #[test]
+    fn exponential_backrefs() {
+        // Each tuple type holds the previous one twice, through backreferences,
+        // so the output doubles with each of them.
+        let mut inner = String::from("IC1aTuuE");
+        let mut prev = 4;
+        for _ in 0..64 {
+            let pos = inner.len();
+            let backref = base_62(prev);
+            inner.push_str(&format!("TB{}B{}E", backref, backref));
+            prev = pos;
+        }
+        inner.push('E');
+        assert!(demangle(&format!("_R{}", inner)).is_none());
+    }
+
+    fn base_62(x: usize) -> String {
+        if x == 0 {
+            return "_".to_string();
+        }
+        let digits = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        let mut x = x - 1;
+        let mut s = vec![b'_'];
+        loop {
+            s.push(digits[x % 62]);
+            x /= 62;
+            if x == 0 {
+                break;
+            }
+        }
+        s.reverse();
+        String::from_utf8(s).unwrap()
+    }
+}
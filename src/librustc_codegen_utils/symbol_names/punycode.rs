@@ -0,0 +1,207 @@
+//! Punycode (RFC 3492), which the v0 mangling uses to encode identifiers
+//! that aren't ASCII with only the characters allowed in symbol names.
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+    delta /= if first_time { DAMP } else { 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+fn encode_digit(d: u32) -> char {
+    if d < 26 {
+        (b'a' + d as u8) as char
+    } else {
+        (b'0' + (d - 26) as u8) as char
+    }
+}
+
+fn decode_digit(c: u8) -> Option<u32> {
+    match c {
+        b'a'..=b'z' => Some((c - b'a') as u32),
+        b'0'..=b'9' => Some((c - b'0') as u32 + 26),
+        _ => None,
+    }
+}
+
+/// Encodes `input`: its ASCII characters, followed by a `-` if there are
+/// any, followed by the encoding of the other characters.
+/// Returns `None` on overflow.
+pub fn encode(input: &str) -> Option<String> {
+    let input = input.chars().map(|c| c as u32).collect::<Vec<_>>();
+    let mut output = input.iter()
+                          .filter(|&&c| c < INITIAL_N)
+                          .map(|&c| c as u8 as char)
+                          .collect::<String>();
+    let basic = output.len() as u32;
+    if basic > 0 {
+        output.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta = 0u32;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic;
+    while (handled as usize) < input.len() {
+        let m = input.iter().cloned().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &input {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n = n.checked_add(1)?;
+    }
+    Some(output)
+}
+
+/// Decodes the `encoded` characters and inserts them into the `basic` ones,
+/// which were separated by the last `-` of the encoding.
+/// Returns `None` if `encoded` is invalid.
+pub fn decode(basic: &str, encoded: &str) -> Option<String> {
+    let mut output = basic.chars().collect::<Vec<_>>();
+    let mut encoded = encoded.bytes().peekable();
+
+    let mut n = INITIAL_N;
+    let mut i = 0u32;
+    let mut bias = INITIAL_BIAS;
+    while encoded.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let d = decode_digit(encoded.next()?)?;
+            i = i.checked_add(d.checked_mul(w)?)?;
+            let t = threshold(k, bias);
+            if d < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, std::char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, encode_digit, threshold, BASE, INITIAL_BIAS, INITIAL_N};
+
+    fn decode_encoded(encoded: &str) -> Option<String> {
+        match encoded.rfind('-') {
+            Some(i) => decode(&encoded[..i], &encoded[i + 1..]),
+            None => decode("", encoded),
+        }
+    }
+
+    #[test]
+    fn encode_known() {
+        assert_eq!(encode("bücher").as_ref().map(|s| &s[..]), Some("bcher-kva"));
+        assert_eq!(encode("ü").as_ref().map(|s| &s[..]), Some("tda"));
+        assert_eq!(encode("münchen").as_ref().map(|s| &s[..]), Some("mnchen-3ya"));
+        assert_eq!(encode("他们为什么不说中文").as_ref().map(|s| &s[..]),
+                   Some("ihqwcrb4cv8a8dqg056pqjye"));
+        assert_eq!(encode("abc").as_ref().map(|s| &s[..]), Some("abc-"));
+        assert_eq!(encode("").as_ref().map(|s| &s[..]), Some(""));
+    }
+
+    #[test]
+    fn round_trip() {
+        let inputs = [
+            "bücher",
+            "ü",
+            "münchen",
+            "他们为什么不说中文",
+            "Pročprostěnemluvíčesky",
+            "なぜみんな日本語を話してくれないのか",
+            "𝔘𝔫𝔦𝔠𝔬𝔡𝔢",
+            "a_b_ç_d",
+            "abc",
+        ];
+        for input in inputs.iter() {
+            let encoded = encode(input).unwrap();
+            assert_eq!(decode_encoded(&encoded).as_ref().map(|s| &s[..]), Some(*input),
+                       "{:?} encoded as {:?}", input, encoded);
+        }
+    }
+
+    #[test]
+    fn decode_invalid() {
+        // Digits are lowercase letters and decimal digits.
+        assert_eq!(decode("", "A"), None);
+        assert_eq!(decode("", "kv!"), None);
+        // A number can't end with a digit above the threshold.
+        assert_eq!(decode("", "z"), None);
+        // Numbers, and the code points they add up to, must not overflow.
+        assert_eq!(decode("", "99999999999999"), None);
+        // Surrogates aren't characters.
+        assert_eq!(decode("", &encode_code_point(0xd800)), None);
+        assert_eq!(decode("", &encode_code_point(0x110000)), None);
+    }
+
+    /// Encodes the insertion of `code_point` into an empty string, even if it
+    /// isn't a valid character.
+    fn encode_code_point(code_point: u32) -> String {
+        let mut output = String::new();
+        let mut q = code_point - INITIAL_N;
+        let mut k = BASE;
+        loop {
+            let t = threshold(k, INITIAL_BIAS);
+            if q < t {
+                break;
+            }
+            output.push(encode_digit(t + (q - t) % (BASE - t)));
+            q = (q - t) / (BASE - t);
+            k += BASE;
+        }
+        output.push(encode_digit(q));
+        output
+    }
+}
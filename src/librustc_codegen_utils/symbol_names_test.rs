@@ -2,11 +2,14 @@
 //! either a `rustc_symbol_name` or `rustc_def_path` attribute and
 //! generates an error giving, respectively, the symbol name or
 //! def-path. This is used for unit testing the code that generates
-//! paths etc in all kinds of annoying scenarios.
+//! paths etc in all kinds of annoying scenarios. The symbol names of the
+//! v0 mangling are also reported demangled, with and without the crate
+//! disambiguators.
 
 use rustc::hir;
 use rustc::ty::TyCtxt;
 use rustc_mir::monomorphize::Instance;
+use crate::symbol_names::demangle;
 use syntax::symbol::{Symbol, sym};
 
 const SYMBOL_NAME: Symbol = sym::rustc_symbol_name;
@@ -41,6 +44,11 @@ impl<'a, 'tcx> SymbolNamesTest<'a, 'tcx> {
                 let instance = Instance::mono(tcx, def_id);
                 let name = self.tcx.symbol_name(instance);
                 tcx.sess.span_err(attr.span, &format!("symbol-name({})", name));
+                // The demangled forms are only reported for the v0 mangling.
+                if let Some(demangling) = demangle::demangle(&name.as_str()) {
+                    tcx.sess.span_err(attr.span, &format!("demangling({})", demangling));
+                    tcx.sess.span_err(attr.span, &format!("demangling-alt({:#})", demangling));
+                }
             } else if attr.check_name(DEF_PATH) {
                 let path = tcx.def_path_str(def_id);
                 tcx.sess.span_err(attr.span, &format!("def-path({})", path));
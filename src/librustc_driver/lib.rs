@@ -43,8 +43,10 @@ use rustc_metadata::cstore::CStore;
 use rustc_codegen_utils::codegen_backend::CodegenBackend;
 use rustc_interface::interface;
 use rustc_interface::util::get_codegen_sysroot;
+use rustc_data_structures::OnDrop;
 use rustc_data_structures::sync::SeqCst;
 
+use errors::fixes::FixedFile;
 use serialize::json::ToJson;
 
 use std::borrow::Cow;
@@ -52,11 +54,13 @@ use std::cmp::max;
 use std::default::Default;
 use std::env;
 use std::ffi::OsString;
+use std::fs;
 use std::io::{self, Read, Write};
 use std::panic::{self, catch_unwind};
 use std::path::PathBuf;
 use std::process::{self, Command, Stdio};
 use std::str;
+use std::sync::Mutex;
 use std::mem;
 
 use syntax::ast;
@@ -83,6 +87,9 @@ const ICE_REPORT_COMPILER_FLAGS_EXCLUDE: &[&str] = &["metadata", "extra-filename
 
 const ICE_REPORT_COMPILER_FLAGS_STRIP_VALUE: &[&str] = &["incremental"];
 
+/// The maximum number of compilations done with `-Z apply-suggestions`.
+const MAX_SUGGESTION_PASSES: usize = 4;
+
 pub fn source_name(input: &Input) -> FileName {
     match *input {
         Input::File(ref ifile) => ifile.clone().into(),
@@ -211,24 +218,88 @@ pub fn run_compiler(
         return Err(ErrorReported);
     }
 
-    let mut config = interface::Config {
-        opts: sopts,
-        crate_cfg: cfg,
-        input,
-        input_path: input_file_path,
-        output_file: ofile,
-        output_dir: odir,
-        file_loader,
-        diagnostic_output,
-        stderr: None,
-        crate_name: None,
-        lint_caps: Default::default(),
+    // With `-Z apply-suggestions`, compilation is repeated as long as applying
+    // the suggestions changed the sources, as a fix can make new suggestions
+    // apply. This is only done when the sources are read from the file system
+    // and diagnostics go to the default output, which later passes can reuse.
+    let mut applying_suggestions = sopts.debugging_opts.apply_suggestions;
+    let can_repeat = file_loader.is_none() && match diagnostic_output {
+        DiagnosticOutput::Default => true,
+        DiagnosticOutput::Raw(_) => false,
     };
+    let mut file_loader = file_loader;
+    let mut diagnostic_output = Some(diagnostic_output);
+    // The files changed by the previous pass, with their contents before it.
+    let mut fixed_files: Vec<FixedFile> = Vec::new();
+    let mut pass = 1;
+
+    loop {
+        let mut config = interface::Config {
+            opts: sopts.clone(),
+            crate_cfg: cfg.clone(),
+            input: input.clone(),
+            input_path: input_file_path.clone(),
+            output_file: ofile.clone(),
+            output_dir: odir.clone(),
+            file_loader: file_loader.take(),
+            diagnostic_output: diagnostic_output.take().unwrap_or(DiagnosticOutput::Default),
+            stderr: None,
+            crate_name: None,
+            lint_caps: Default::default(),
+        };
+
+        callbacks.config(&mut config);
+
+        // Whether the pass failed, and the files it changed.
+        let outcome = Mutex::new((true, Vec::new()));
+        let result = run_compiler_pass(config, callbacks, &matches, |sess| {
+            let fixed = if applying_suggestions && !sess.has_errors() {
+                sess.apply_suggestions()
+            } else {
+                Vec::new()
+            };
+            *outcome.lock().unwrap() = (sess.has_errors(), fixed);
+        });
+        let (failed, newly_fixed) = outcome.into_inner().unwrap();
+
+        if failed && !fixed_files.is_empty() {
+            // The suggestions applied by the previous pass broke the build, so
+            // they are reverted and the sources are compiled once more.
+            for file in &fixed_files {
+                if let Err(e) = fs::write(&file.path, &file.original) {
+                    early_error(sopts.error_format, &format!(
+                        "failed to revert the suggestions applied to `{}`: {}",
+                        file.path.display(), e,
+                    ));
+                }
+            }
+            early_warn(sopts.error_format, "applying suggestions caused errors, \
+                                            the suggestions have been reverted");
+            fixed_files.clear();
+            applying_suggestions = false;
+            continue;
+        }
 
-    callbacks.config(&mut config);
+        fixed_files = newly_fixed;
+        if fixed_files.is_empty() || !can_repeat || pass == MAX_SUGGESTION_PASSES {
+            return result;
+        }
+        pass += 1;
+    }
+}
 
+/// Runs one compilation with the given configuration, calling `finish` with
+/// the session once it is done, whether compilation succeeded or not.
+fn run_compiler_pass(
+    config: interface::Config,
+    callbacks: &mut (dyn Callbacks + Send),
+    matches: &getopts::Matches,
+    finish: impl Fn(&Session) + Send,
+) -> interface::Result<()> {
     interface::run_compiler(config, |compiler| {
         let sess = compiler.session();
+        let _finish = OnDrop(|| finish(sess));
+
         let should_stop = RustcDefaultCalls::print_crate_info(
             &***compiler.codegen_backend(),
             sess,
@@ -238,7 +309,7 @@ pub fn run_compiler(
         ).and_then(|| RustcDefaultCalls::list_metadata(
             sess,
             compiler.cstore(),
-            &matches,
+            matches,
             compiler.input()
         ));
 
@@ -246,7 +317,7 @@ pub fn run_compiler(
             return sess.compile_status();
         }
 
-        let pretty_info = parse_pretty(sess, &matches);
+        let pretty_info = parse_pretty(sess, matches);
 
         compiler.parse()?;
 
@@ -355,7 +426,11 @@ pub fn run_compiler(
         mem::drop(compiler.global_ctxt()?.take());
 
         if sess.opts.debugging_opts.print_type_sizes {
-            sess.code_stats.borrow().print_type_sizes();
+            if sess.opts.debugging_opts.print_type_sizes_json {
+                sess.code_stats.borrow().print_type_sizes_json();
+            } else {
+                sess.code_stats.borrow().print_type_sizes();
+            }
         }
 
         compiler.link()?;
@@ -486,10 +561,14 @@ fn stdout_isatty() -> bool {
 fn handle_explain(code: &str,
                   output: ErrorOutputType) {
     let descriptions = rustc_interface::util::diagnostics_registry();
-    let normalised = if code.starts_with("E") {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let normalised = if code.starts_with("E") && is_number(&code[1..]) {
         code.to_string()
-    } else {
+    } else if is_number(code) {
         format!("E{0:0>4}", code)
+    } else {
+        // Lints are looked up by the name used in attributes.
+        code.to_lowercase().replace("-", "_")
     };
     match descriptions.find_description(&normalised) {
         Some(ref description) => {
@@ -0,0 +1,140 @@
+//! Lint baselines: files listing known lint diagnostics that should not be
+//! reported again.
+//!
+//! Each entry identifies one lint diagnostic by the name of the lint, the file
+//! of its primary span and a hash of the source text it points at, normalized
+//! so that whitespace changes do not matter. Line numbers are deliberately left
+//! out so that editing unrelated code does not invalidate the baseline. An
+//! entry suppresses one occurrence, the same entry can be listed several times.
+//!
+//! The file has one tab separated `lint file hash` entry per line, lines
+//! starting with `#` are comments.
+
+use crate::{Diagnostic, DiagnosticId, SourceMapperDyn};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::sync::Lrc;
+use syntax_pos::Span;
+
+use std::fs;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct BaselineEntry {
+    lint: String,
+    file: String,
+    snippet_hash: u64,
+}
+
+pub struct Baseline {
+    sm: Lrc<SourceMapperDyn>,
+    /// Number of occurrences of each entry that are still to be suppressed.
+    known: FxHashMap<BaselineEntry, usize>,
+    /// Every lint diagnostic seen so far, suppressed or not, used to write an
+    /// updated baseline.
+    seen: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Creates a baseline that does not suppress anything, only recording the
+    /// lint diagnostics it sees.
+    pub fn new(sm: Lrc<SourceMapperDyn>) -> Baseline {
+        Baseline {
+            sm,
+            known: Default::default(),
+            seen: Vec::new(),
+        }
+    }
+
+    pub fn load(sm: Lrc<SourceMapperDyn>, path: &Path) -> io::Result<Baseline> {
+        let mut baseline = Baseline::new(sm);
+        let contents = fs::read_to_string(path)?;
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = match &line.split('\t').collect::<Vec<_>>()[..] {
+                &[lint, file, hash] => u64::from_str_radix(hash, 16).ok().map(|snippet_hash| {
+                    BaselineEntry {
+                        lint: lint.to_string(),
+                        file: file.to_string(),
+                        snippet_hash,
+                    }
+                }),
+                _ => None,
+            };
+            match entry {
+                Some(entry) => *baseline.known.entry(entry).or_insert(0) += 1,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected `lint<TAB>file<TAB>hash`", i + 1),
+                    ));
+                }
+            }
+        }
+        Ok(baseline)
+    }
+
+    /// Records `diagnostic` if it comes from a lint, returning whether it is
+    /// known and should not be emitted.
+    pub fn suppresses(&mut self, diagnostic: &Diagnostic) -> bool {
+        let lint = match diagnostic.code {
+            Some(DiagnosticId::Lint(ref lint)) => lint.clone(),
+            _ => return false,
+        };
+        let (file, snippet) = match diagnostic.span.primary_span() {
+            Some(span) if !span.is_dummy() => self.file_and_snippet(span),
+            _ => (String::new(), String::new()),
+        };
+        let mut hasher = StableHasher::new();
+        snippet.hash(&mut hasher);
+        let entry = BaselineEntry { lint, file, snippet_hash: hasher.finish() };
+
+        let suppressed = match self.known.get_mut(&entry) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        };
+        self.seen.push(entry);
+        suppressed
+    }
+
+    /// Writes a baseline suppressing every lint diagnostic seen so far.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut entries = self.seen.clone();
+        entries.sort();
+
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(file, "# Known lint diagnostics, one `lint<TAB>file<TAB>hash` entry each.")?;
+        for entry in entries {
+            writeln!(file, "{}\t{}\t{:016x}", entry.lint, entry.file, entry.snippet_hash)?;
+        }
+        file.flush()
+    }
+
+    /// The part of the first line of `span` it covers, with each run of
+    /// whitespace collapsed into a single space. Only the first line is used
+    /// because lints like `missing_docs` point at whole items, whose bodies
+    /// change much more often than their signatures.
+    fn file_and_snippet(&self, span: Span) -> (String, String) {
+        let lo = self.sm.lookup_char_pos(span.lo());
+        let hi = self.sm.lookup_char_pos(span.hi());
+        let file = lo.file.name.to_string();
+        let line = match lo.file.get_line(lo.line - 1) {
+            Some(line) => line,
+            None => return (file, String::new()),
+        };
+        let end = if hi.line == lo.line { hi.col.0 } else { line.chars().count() };
+        let text = line.chars()
+                       .skip(lo.col.0)
+                       .take(end.saturating_sub(lo.col.0))
+                       .collect::<String>();
+        (file, text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
@@ -0,0 +1,195 @@
+//! Applying machine-applicable suggestions to the source files.
+//!
+//! Suggestions are collected from the diagnostics as they are emitted. Only
+//! suggestions with a single, machine-applicable substitution are used, and
+//! only when every part of it points at the source of a local file rather than
+//! into a macro expansion.
+//!
+//! Suggestions can overlap, for instance when two lints want to rewrite the
+//! same expression. They are applied in source order and a suggestion that
+//! overlaps one applied before is skipped as a whole, leaving it for the next
+//! compilation to suggest again if it still applies.
+
+use crate::{Applicability, CodeSuggestion, SourceMapperDyn};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use syntax_pos::{FileName, Span, SyntaxContext};
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Clone, PartialEq, Eq)]
+struct Replacement {
+    path: PathBuf,
+    /// Byte range of the replaced text in the file, as read by the compiler.
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+impl Replacement {
+    /// Whether applying both replacements could give a different result
+    /// depending on the order they are applied in.
+    fn conflicts_with(&self, other: &Replacement) -> bool {
+        if self.path != other.path {
+            return false;
+        }
+        let (first, second) = if self.start <= other.start { (self, other) } else { (other, self) };
+        first.start == second.start || second.start < first.end
+    }
+}
+
+/// A file changed by `Fixes::apply`.
+pub struct FixedFile {
+    pub path: PathBuf,
+    /// The contents of the file before the suggestions were applied.
+    pub original: String,
+    /// The number of suggestions applied to this file.
+    pub applied: usize,
+}
+
+/// The result of `Fixes::apply`.
+#[derive(Default)]
+pub struct AppliedFixes {
+    pub files: Vec<FixedFile>,
+    /// Suggestions skipped because they overlapped another suggestion.
+    pub overlapping: usize,
+    /// Files that could not be updated, with the reason why.
+    pub errors: Vec<(PathBuf, io::Error)>,
+}
+
+pub struct Fixes {
+    sm: Lrc<SourceMapperDyn>,
+    /// Each fix is the list of replacements of one suggestion.
+    fixes: Vec<Vec<Replacement>>,
+    /// The source of each file a fix applies to, as the compiler read it.
+    sources: FxHashMap<PathBuf, Lrc<String>>,
+}
+
+impl Fixes {
+    pub fn new(sm: Lrc<SourceMapperDyn>) -> Fixes {
+        Fixes {
+            sm,
+            fixes: Vec::new(),
+            sources: Default::default(),
+        }
+    }
+
+    pub fn collect(&mut self, suggestions: &[CodeSuggestion]) {
+        for suggestion in suggestions {
+            if suggestion.applicability != Applicability::MachineApplicable ||
+               suggestion.substitutions.len() != 1 {
+                continue;
+            }
+            let fix = suggestion.substitutions[0].parts.iter().map(|part| {
+                self.replacement(part.span, &part.snippet)
+            }).collect::<Option<Vec<_>>>();
+            if let Some(fix) = fix {
+                if !fix.is_empty() && !self.fixes.contains(&fix) {
+                    self.fixes.push(fix);
+                }
+            }
+        }
+    }
+
+    fn replacement(&mut self, span: Span, text: &str) -> Option<Replacement> {
+        if span.is_dummy() || span.ctxt() != SyntaxContext::empty() {
+            return None;
+        }
+        let lo = self.sm.lookup_char_pos(span.lo());
+        let file = lo.file;
+        let path = match file.unmapped_path {
+            Some(FileName::Real(ref path)) => path.clone(),
+            _ => return None,
+        };
+        let src = file.src.clone()?;
+        self.sources.entry(path.clone()).or_insert(src);
+        Some(Replacement {
+            path,
+            start: (span.lo() - file.start_pos).0 as usize,
+            end: (span.hi() - file.start_pos).0 as usize,
+            text: text.to_string(),
+        })
+    }
+
+    /// Rewrites the source files with the collected suggestions.
+    pub fn apply(&self) -> AppliedFixes {
+        let mut result = AppliedFixes::default();
+
+        let mut fixes = self.fixes.iter().collect::<Vec<_>>();
+        fixes.sort_by(|a, b| first_position(a).cmp(&first_position(b)));
+
+        let mut accepted: Vec<&Replacement> = Vec::new();
+        let mut applied_per_file: Vec<(&PathBuf, usize)> = Vec::new();
+        for fix in fixes {
+            if fix.iter().any(|r| accepted.iter().any(|a| a.conflicts_with(r))) {
+                result.overlapping += 1;
+                continue;
+            }
+            accepted.extend(fix.iter());
+
+            let mut paths = fix.iter().map(|r| &r.path).collect::<Vec<_>>();
+            paths.sort();
+            paths.dedup();
+            for path in paths {
+                match applied_per_file.iter_mut().find(|&&mut (p, _)| p == path) {
+                    Some(&mut (_, ref mut applied)) => *applied += 1,
+                    None => applied_per_file.push((path, 1)),
+                }
+            }
+        }
+
+        applied_per_file.sort();
+        for (path, applied) in applied_per_file {
+            let mut replacements = accepted.iter()
+                                           .cloned()
+                                           .filter(|r| r.path == *path)
+                                           .collect::<Vec<_>>();
+            replacements.sort_by_key(|r| (r.start, r.end));
+
+            match self.apply_to_file(path, &replacements) {
+                Ok(original) => result.files.push(FixedFile {
+                    path: path.clone(),
+                    original,
+                    applied,
+                }),
+                Err(e) => result.errors.push((path.clone(), e)),
+            }
+        }
+        result
+    }
+
+    fn apply_to_file(&self, path: &PathBuf, replacements: &[&Replacement]) -> io::Result<String> {
+        let original = fs::read_to_string(path)?;
+        // The compiler drops the byte order mark, so positions do not count it.
+        let (bom, on_disk) = if original.starts_with('\u{feff}') {
+            original.split_at(3)
+        } else {
+            ("", &original[..])
+        };
+        let src = &self.sources[path];
+        if on_disk != &src[..] {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      "the file changed since it was compiled"));
+        }
+
+        let mut fixed = String::with_capacity(original.len());
+        fixed.push_str(bom);
+        let mut pos = 0;
+        for r in replacements {
+            fixed.push_str(&src[pos..r.start]);
+            fixed.push_str(&r.text);
+            pos = r.end;
+        }
+        fixed.push_str(&src[pos..]);
+
+        fs::write(path, fixed)?;
+        Ok(original)
+    }
+}
+
+fn first_position(fix: &[Replacement]) -> Option<(&PathBuf, usize)> {
+    fix.iter().map(|r| (&r.path, r.start)).min()
+}
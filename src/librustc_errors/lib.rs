@@ -15,6 +15,9 @@ pub use emitter::ColorConfig;
 
 use Level::*;
 
+use baseline::Baseline;
+use fixes::{AppliedFixes, Fixes};
+use summary::Summary;
 use emitter::{Emitter, EmitterWriter};
 use registry::Registry;
 
@@ -24,16 +27,19 @@ use rustc_data_structures::stable_hasher::StableHasher;
 
 use std::borrow::Cow;
 use std::cell::Cell;
-use std::{error, fmt};
+use std::{error, fmt, io, mem};
 use std::panic;
 use std::path::Path;
 
 use termcolor::{ColorSpec, Color};
 
+pub mod baseline;
 mod diagnostic;
 mod diagnostic_builder;
 pub mod emitter;
+pub mod fixes;
 mod snippet;
+pub mod summary;
 pub mod registry;
 mod styled_buffer;
 mod lock;
@@ -318,6 +324,26 @@ pub struct Handler {
     /// this handler. These hashes is used to avoid emitting the same error
     /// twice.
     emitted_diagnostics: Lock<FxHashSet<u128>>,
+
+    /// Known lint diagnostics that are not emitted again, if any.
+    /// (rustc: see `-Z lint-baseline` and `-Z write-lint-baseline`)
+    baseline: Lock<Option<Baseline>>,
+
+    /// Machine-applicable suggestions of the emitted diagnostics, if they are
+    /// to be applied to the source files.
+    /// (rustc: see `-Z apply-suggestions`)
+    fixes: Lock<Option<Fixes>>,
+
+    /// Statistics about the emitted diagnostics, if they are to be printed
+    /// at the end of compilation.
+    /// (rustc: see `-Z diagnostic-summary`)
+    summary: Lock<Option<Summary>>,
+
+    /// Diagnostics held back to be emitted in source order, when
+    /// `flags.sort_diagnostics` is set. Once they have been emitted, later
+    /// diagnostics are emitted right away.
+    sorted_diagnostics: Lock<Vec<Diagnostic>>,
+    sorted_diagnostics_emitted: AtomicBool,
 }
 
 fn default_track_diagnostic(_: &Diagnostic) {}
@@ -342,10 +368,15 @@ pub struct HandlerFlags {
     /// show macro backtraces even for non-local macros.
     /// (rustc: see `-Z external-macro-backtrace`)
     pub external_macro_backtrace: bool,
+    /// If true, diagnostics are held back until the end of compilation and
+    /// emitted in the order of their primary span, which groups them by file.
+    /// (rustc: see `-Z sort-diagnostics`)
+    pub sort_diagnostics: bool,
 }
 
 impl Drop for Handler {
     fn drop(&mut self) {
+        self.emit_sorted_diagnostics();
         if self.err_count() == 0 {
             let mut bugs = self.delayed_span_bugs.borrow_mut();
             let has_bugs = !bugs.is_empty();
@@ -407,6 +438,43 @@ impl Handler {
             taught_diagnostics: Default::default(),
             emitted_diagnostic_codes: Default::default(),
             emitted_diagnostics: Default::default(),
+            baseline: Lock::new(None),
+            fixes: Lock::new(None),
+            summary: Lock::new(None),
+            sorted_diagnostics: Lock::new(Vec::new()),
+            sorted_diagnostics_emitted: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_baseline(&self, baseline: Baseline) {
+        *self.baseline.borrow_mut() = Some(baseline);
+    }
+
+    /// Writes a baseline suppressing every lint diagnostic emitted so far,
+    /// including the ones that the current baseline suppressed.
+    pub fn write_baseline(&self, path: &Path) -> io::Result<()> {
+        match *self.baseline.borrow() {
+            Some(ref baseline) => baseline.write(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts collecting the suggestions to apply with `apply_fixes`.
+    pub fn set_fixes(&self, fixes: Fixes) {
+        *self.fixes.borrow_mut() = Some(fixes);
+    }
+
+    /// Starts collecting the statistics printed by `print_error_count`.
+    pub fn set_summary(&self, summary: Summary) {
+        *self.summary.borrow_mut() = Some(summary);
+    }
+
+    /// Applies the machine-applicable suggestions collected so far to the
+    /// source files.
+    pub fn apply_fixes(&self) -> AppliedFixes {
+        match *self.fixes.borrow() {
+            Some(ref fixes) => fixes.apply(),
+            None => AppliedFixes::default(),
         }
     }
 
@@ -653,7 +721,35 @@ impl Handler {
         self.err_count() > 0
     }
 
+    /// Emits the diagnostics held back to be sorted, which cannot be sorted
+    /// any further once they are.
+    fn emit_sorted_diagnostics(&self) {
+        if self.sorted_diagnostics_emitted.swap(true, SeqCst) {
+            return;
+        }
+        let mut diagnostics = mem::replace(&mut *self.sorted_diagnostics.borrow_mut(), Vec::new());
+        // Diagnostics without a span come last, the sort keeps them in order.
+        diagnostics.sort_by_key(|diagnostic| match diagnostic.span.primary_span() {
+            Some(span) if !span.is_dummy() => (false, span.lo()),
+            _ => (true, BytePos(0)),
+        });
+        for diagnostic in diagnostics {
+            // These were counted and deduplicated when they were first emitted,
+            // so they go straight to the emitter.
+            let mut db = DiagnosticBuilder::new_diagnostic(self, diagnostic);
+            self.emitter.borrow_mut().emit_diagnostic(&db);
+            db.cancel();
+        }
+    }
+
+    /// Ends the output of diagnostics: emits the diagnostics held back to be
+    /// sorted, the summary if one was requested, and the number of errors.
     pub fn print_error_count(&self, registry: &Registry) {
+        self.emit_sorted_diagnostics();
+        if let Some(summary) = self.summary.borrow().as_ref().and_then(|s| s.render()) {
+            self.failure(&summary);
+        }
+
         let s = match self.err_count() {
             0 => return,
             1 => "aborting due to previous error".to_string(),
@@ -762,7 +858,22 @@ impl Handler {
         // Only emit the diagnostic if we haven't already emitted an equivalent
         // one:
         if self.emitted_diagnostics.borrow_mut().insert(diagnostic_hash) {
-            self.emitter.borrow_mut().emit_diagnostic(db);
+            if let Some(ref mut baseline) = *self.baseline.borrow_mut() {
+                if baseline.suppresses(diagnostic) {
+                    return;
+                }
+            }
+            if let Some(ref mut fixes) = *self.fixes.borrow_mut() {
+                fixes.collect(&diagnostic.suggestions);
+            }
+            if let Some(ref mut summary) = *self.summary.borrow_mut() {
+                summary.record(diagnostic);
+            }
+            if self.flags.sort_diagnostics && !self.sorted_diagnostics_emitted.load(SeqCst) {
+                self.sorted_diagnostics.borrow_mut().push(diagnostic.clone());
+            } else {
+                self.emitter.borrow_mut().emit_diagnostic(db);
+            }
             if db.is_error() {
                 self.bump_err_count();
             }
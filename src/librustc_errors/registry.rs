@@ -1,13 +1,24 @@
 use rustc_data_structures::fx::FxHashMap;
+use std::borrow::Cow;
 
 #[derive(Clone)]
 pub struct Registry {
-    descriptions: FxHashMap<&'static str, &'static str>,
+    descriptions: FxHashMap<Cow<'static, str>, &'static str>,
 }
 
 impl Registry {
     pub fn new(descriptions: &[(&'static str, &'static str)]) -> Registry {
-        Registry { descriptions: descriptions.iter().cloned().collect() }
+        Registry {
+            descriptions: descriptions.iter()
+                                      .map(|&(code, description)| (code.into(), description))
+                                      .collect(),
+        }
+    }
+
+    /// Adds the description of a code that is only known at runtime, like the
+    /// name of a lint.
+    pub fn add_description(&mut self, code: String, description: &'static str) {
+        self.descriptions.insert(code.into(), description);
     }
 
     pub fn find_description(&self, code: &str) -> Option<&'static str> {
@@ -0,0 +1,104 @@
+//! Statistics about the emitted errors and warnings, printed at the end of
+//! compilation to give an overview of large amounts of diagnostics.
+
+use crate::{Diagnostic, DiagnosticId, Level, SourceMapperDyn};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+
+use std::fmt::Write;
+
+/// The number of files listed in the summary.
+const MAX_FILES: usize = 10;
+
+pub struct Summary {
+    sm: Lrc<SourceMapperDyn>,
+    errors: usize,
+    warnings: usize,
+    /// Number of diagnostics for each error code or lint name, diagnostics
+    /// without one are counted under `None`.
+    per_code: FxHashMap<Option<String>, usize>,
+    per_file: FxHashMap<String, usize>,
+}
+
+impl Summary {
+    pub fn new(sm: Lrc<SourceMapperDyn>) -> Summary {
+        Summary {
+            sm,
+            errors: 0,
+            warnings: 0,
+            per_code: Default::default(),
+            per_file: Default::default(),
+        }
+    }
+
+    pub fn record(&mut self, diagnostic: &Diagnostic) {
+        if diagnostic.is_error() {
+            self.errors += 1;
+        } else if diagnostic.level == Level::Warning {
+            self.warnings += 1;
+        } else {
+            return;
+        }
+
+        let code = diagnostic.code.as_ref().map(|code| match *code {
+            DiagnosticId::Error(ref s) | DiagnosticId::Lint(ref s) => s.clone(),
+        });
+        *self.per_code.entry(code).or_insert(0) += 1;
+
+        if let Some(span) = diagnostic.span.primary_span() {
+            if !span.is_dummy() {
+                let file = self.sm.span_to_filename(span).to_string();
+                *self.per_file.entry(file).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Renders the summary as a table, or returns `None` if there were neither
+    /// errors nor warnings.
+    pub fn render(&self) -> Option<String> {
+        if self.errors == 0 && self.warnings == 0 {
+            return None;
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(out, "summary: {} error{}, {} warning{}",
+                         self.errors, if self.errors == 1 { "" } else { "s" },
+                         self.warnings, if self.warnings == 1 { "" } else { "s" });
+
+        let mut per_code = self.per_code.iter()
+            .map(|(code, &count)| (count, code.as_ref().map_or("(no code)", |c| &c[..])))
+            .collect::<Vec<_>>();
+        sort_by_count(&mut per_code);
+        let _ = writeln!(out, "\n{:>7}  code", "count");
+        for (count, code) in per_code {
+            let _ = writeln!(out, "{:>7}  {}", count, code);
+        }
+
+        let mut per_file = self.per_file.iter()
+            .map(|(file, &count)| (count, &file[..]))
+            .collect::<Vec<_>>();
+        sort_by_count(&mut per_file);
+        if !per_file.is_empty() {
+            let _ = writeln!(out, "\n{:>7}  file", "count");
+            for &(count, file) in per_file.iter().take(MAX_FILES) {
+                let _ = writeln!(out, "{:>7}  {}", count, file);
+            }
+            if per_file.len() > MAX_FILES {
+                let more = per_file.len() - MAX_FILES;
+                let _ = writeln!(out, "{:>7}  ... and {} more files", "", more);
+            }
+        }
+
+        // The trailing newline is added when the message is printed.
+        out.pop();
+        Some(out)
+    }
+}
+
+/// Sorts the most frequent entries first, then by name.
+fn sort_by_count(entries: &mut Vec<(usize, &str)>) {
+    entries.sort_by(|&(count_a, name_a), &(count_b, name_b)| {
+        count_b.cmp(&count_a).then(name_a.cmp(name_b))
+    });
+}
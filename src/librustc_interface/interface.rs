@@ -87,6 +87,9 @@ pub fn run_compiler_in_existing_thread_pool<F, R>(config: Config, f: F) -> R
 where
     F: FnOnce(&Compiler) -> R,
 {
+    // Building the registry collects every builtin lint, so it is done once
+    // and shared between the session and the final error count.
+    let registry = util::diagnostics_registry();
     let (sess, codegen_backend, source_map) = util::create_session(
         config.opts,
         config.crate_cfg,
@@ -94,6 +97,7 @@ where
         config.file_loader,
         config.input_path.clone(),
         config.lint_caps,
+        registry.clone(),
     );
 
     let cstore = Lrc::new(CStore::new(codegen_backend.metadata_loader()));
@@ -112,7 +116,8 @@ where
     };
 
     let _sess_abort_error = OnDrop(|| {
-        compiler.sess.diagnostic().print_error_count(&util::diagnostics_registry());
+        compiler.sess.write_lint_baseline();
+        compiler.sess.diagnostic().print_error_count(&registry);
     });
 
     if compiler.sess.profile_queries() {
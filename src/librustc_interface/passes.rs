@@ -468,7 +468,7 @@ fn configure_and_expand_inner<'a>(
 
     // If we're actually rustdoc then there's no need to actually compile
     // anything, so switch everything to just looping
-    if sess.opts.actually_rustdoc {
+    if sess.opts.actually_rustdoc && !sess.opts.rustdoc_keep_bodies {
         util::ReplaceBodyWithLoop::new(sess).visit_crate(&mut krate);
     }
 
@@ -543,9 +543,18 @@ fn configure_and_expand_inner<'a>(
     // Add all buffered lints from the `ParseSess` to the `Session`.
     sess.parse_sess.buffered_lints.with_lock(|buffered_lints| {
         info!("{} parse sess buffered_lints", buffered_lints.len());
-        for BufferedEarlyLint{id, span, msg, lint_id} in buffered_lints.drain(..) {
+        for BufferedEarlyLint{id, span, msg, lint_id, diagnostic} in buffered_lints.drain(..) {
             let lint = lint::Lint::from_parser_lint_id(lint_id);
-            sess.buffer_lint(lint, id, span, &msg);
+            match diagnostic {
+                Some(diagnostic) => sess.buffer_lint_with_diagnostic(
+                    lint,
+                    id,
+                    span,
+                    &msg,
+                    lint::builtin::BuiltinLintDiagnostics::Diagnostic(diagnostic),
+                ),
+                None => sess.buffer_lint(lint, id, span, &msg),
+            }
         }
     });
 
@@ -661,6 +670,22 @@ fn escape_dep_filename(filename: &FileName) -> String {
     filename.to_string().replace(" ", "\\ ")
 }
 
+// Makefile comments only need escaping newlines and `\`.
+// The result can be unescaped by anything able to unescape `escape_default` and friends.
+fn escape_dep_env(symbol: Symbol) -> String {
+    let s = symbol.as_str();
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\\' => escaped.push_str(r"\\"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 fn write_out_deps(sess: &Session, outputs: &OutputFilenames, out_filenames: &[PathBuf]) {
     // Write out dependency rules to the dep-info file if requested
     if !sess.opts.output_types.contains_key(&OutputType::DepInfo) {
@@ -671,13 +696,22 @@ fn write_out_deps(sess: &Session, outputs: &OutputFilenames, out_filenames: &[Pa
     let result = (|| -> io::Result<()> {
         // Build a list of files used to compile the output and
         // write Makefile-compatible dependency rules
-        let files: Vec<String> = sess.source_map()
+        let mut files: Vec<String> = sess.source_map()
             .files()
             .iter()
             .filter(|fmap| fmap.is_real_file())
             .filter(|fmap| !fmap.is_imported())
             .map(|fmap| escape_dep_filename(&fmap.name))
             .collect();
+
+        // Files read by proc macros through `proc_macro::tracked_path`.
+        let mut extra_files = sess.parse_sess.file_depinfo.borrow().iter()
+            .map(|path| escape_dep_filename(&FileName::Real(PathBuf::from(&*path.as_str()))))
+            .filter(|path| !files.contains(path))
+            .collect::<Vec<_>>();
+        extra_files.sort();
+        files.extend(extra_files);
+
         let mut file = fs::File::create(&deps_filename)?;
         for path in out_filenames {
             writeln!(file, "{}: {}\n", path.display(), files.join(" "))?;
@@ -689,6 +723,21 @@ fn write_out_deps(sess: &Session, outputs: &OutputFilenames, out_filenames: &[Pa
         for path in files {
             writeln!(file, "{}:", path)?;
         }
+
+        // Emit special comments with information about accessed environment variables.
+        let mut env_depinfo = sess.parse_sess.env_depinfo.borrow().iter()
+            .map(|&(var, value)| match value {
+                Some(value) => format!("{}={}", escape_dep_env(var), escape_dep_env(value)),
+                None => escape_dep_env(var),
+            })
+            .collect::<Vec<_>>();
+        if !env_depinfo.is_empty() {
+            env_depinfo.sort();
+            writeln!(file)?;
+            for env in env_depinfo {
+                writeln!(file, "# env-dep:{}", env)?;
+            }
+        }
         Ok(())
     })();
 
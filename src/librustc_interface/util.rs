@@ -54,7 +54,18 @@ pub fn diagnostics_registry() -> Registry {
     all_errors.extend_from_slice(&rustc_mir::DIAGNOSTICS);
     all_errors.extend_from_slice(&syntax::DIAGNOSTICS);
 
-    Registry::new(&all_errors)
+    let mut registry = Registry::new(&all_errors);
+
+    // Lints are explained under their name, as used in attributes.
+    let mut lint_store = lint::LintStore::new();
+    rustc_lint::register_builtins(&mut lint_store, None);
+    for &(lint, _) in lint_store.get_lints() {
+        if let Some(explanation) = lint.explanation {
+            registry.add_description(lint.name_lower(), explanation);
+        }
+    }
+
+    registry
 }
 
 /// Adds `target_feature = "..."` cfgs for a variety of platform
@@ -88,9 +99,8 @@ pub fn create_session(
     file_loader: Option<Box<dyn FileLoader + Send + Sync + 'static>>,
     input_path: Option<PathBuf>,
     lint_caps: FxHashMap<lint::LintId, lint::Level>,
+    descriptions: Registry,
 ) -> (Lrc<Session>, Lrc<Box<dyn CodegenBackend>>, Lrc<SourceMap>) {
-    let descriptions = diagnostics_registry();
-
     let loader = file_loader.unwrap_or(box RealFileLoader);
     let source_map = Lrc::new(SourceMap::with_file_loader(
         loader,
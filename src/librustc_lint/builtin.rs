@@ -271,7 +271,39 @@ declare_lint! {
     pub MISSING_DOCS,
     Allow,
     "detects missing documentation for public members",
-    report_in_external_macro: true
+    report_in_external_macro: true,
+    explanation: r##"
+A public item that is reachable from outside the crate has no documentation.
+
+Example:
+
+```
+#![warn(missing_docs)]
+
+//! A crate computing areas.
+
+pub fn area(width: u32, height: u32) -> u32 { // this function is not documented
+    width * height
+}
+```
+
+The lint applies to the crate itself, public modules, types, fields, traits,
+functions, constants, statics and macros. Items that are not visible from
+outside the crate, and trait implementations, do not need documentation.
+
+Add a doc comment to the item:
+
+```
+#![warn(missing_docs)]
+
+//! A crate computing areas.
+
+/// Computes the area of a `width` by `height` rectangle.
+pub fn area(width: u32, height: u32) -> u32 {
+    width * height
+}
+```
+"##
 }
 
 pub struct MissingDoc {
@@ -989,7 +1021,33 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for UnionsWithDropFields {
 declare_lint! {
     pub UNREACHABLE_PUB,
     Allow,
-    "`pub` items not reachable from crate root"
+    "`pub` items not reachable from crate root",
+    explanation: r##"
+An item is declared `pub` but cannot be named from outside the crate.
+
+Example:
+
+```
+#![warn(unreachable_pub)]
+
+mod shapes {
+    pub struct Square; // `shapes` is private, so `Square` is not reachable
+}
+```
+
+Such an item behaves like a `pub(crate)` one, but reads as if it was part of
+the public API of the crate. Declaring it `pub(crate)` makes its visibility
+obvious where it is defined, and an item that later becomes public by
+accident is then caught by this lint:
+
+```
+#![warn(unreachable_pub)]
+
+mod shapes {
+    pub(crate) struct Square;
+}
+```
+"##
 }
 
 declare_lint_pass!(
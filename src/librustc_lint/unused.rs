@@ -25,7 +25,44 @@ declare_lint! {
     pub UNUSED_MUST_USE,
     Warn,
     "unused result of a type flagged as #[must_use]",
-    report_in_external_macro: true
+    report_in_external_macro: true,
+    explanation: r##"
+The result of an expression whose type or function is marked `#[must_use]`
+was discarded.
+
+Erroneous code example:
+
+```
+fn parse(s: &str) -> Result<u32, std::num::ParseIntError> {
+    s.parse()
+}
+
+fn main() {
+    parse("12"); // the error, if any, is silently ignored
+}
+```
+
+Types like `Result` are marked `#[must_use]` because ignoring them is usually a
+mistake: an error would go unnoticed, or a lazy value like an iterator adapter
+or a future would never do any work. Functions can be marked as well, when
+calling them only for their side effects makes no sense.
+
+Handle the value, for example by propagating the error with `?` or by calling
+`unwrap` or `expect` if failing is a bug:
+
+```
+fn parse(s: &str) -> Result<u32, std::num::ParseIntError> {
+    s.parse()
+}
+
+fn main() {
+    parse("12").expect("not a number");
+}
+```
+
+If discarding the value really is intended, say so explicitly with
+`let _ = parse("12");`.
+"##
 }
 
 declare_lint! {
@@ -480,6 +480,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             hash: tcx.crate_hash(LOCAL_CRATE),
             disambiguator: tcx.sess.local_crate_disambiguator(),
             panic_strategy: tcx.sess.panic_strategy(),
+            symbol_mangling_version: tcx.sess.opts.debugging_opts.symbol_mangling_version,
             edition: hygiene::default_edition(),
             has_global_allocator: has_global_allocator,
             has_panic_handler: has_panic_handler,
@@ -131,6 +131,24 @@ impl<'a> base::Resolver for Resolver<'a> {
         mark
     }
 
+    fn lint_node_id(&mut self, mark: Mark) -> ast::NodeId {
+        let mut def_index = match self.invocations.get(&mark) {
+            Some(invoc) => invoc.def_index,
+            None => return ast::CRATE_NODE_ID,
+        };
+        // Anonymous constants and `impl Trait` types are not lint-checked on their own,
+        // so their lints go to the item containing them.
+        loop {
+            let key = self.definitions.def_key(def_index);
+            match (key.disambiguated_data.data, key.parent) {
+                (map::DefPathData::AnonConst, Some(parent)) |
+                (map::DefPathData::ImplTrait, Some(parent)) => def_index = parent,
+                _ => break,
+            }
+        }
+        self.definitions.as_local_node_id(DefId::local(def_index)).unwrap_or(ast::CRATE_NODE_ID)
+    }
+
     fn resolve_dollar_crates(&mut self, fragment: &AstFragment) {
         struct ResolveDollarCrates<'a, 'b: 'a> {
             resolver: &'a mut Resolver<'b>
@@ -0,0 +1,423 @@
+//! Comparison of the public API of two versions of a crate, listing the changes that can break
+//! code depending on it.
+//!
+//! Both versions are documented as usual, and the public items left by the passes are flattened
+//! into a map from their path to the parts of their declaration that users can rely on, printed
+//! as Rust code. Paths are relative to the crate root, so that both versions can be compared even
+//! if their crate names differ.
+//!
+//! The following changes are considered breaking:
+//!
+//! * removing an item or a re-export, or changing its kind or declaration,
+//! * adding a field to a struct or struct variant whose fields were all public, unless it is
+//!   `#[non_exhaustive]`,
+//! * adding a variant to an enum that is not `#[non_exhaustive]`,
+//! * adding a required item to a trait, or removing the default of a trait item,
+//! * a type no longer implementing a trait, including auto traits like `Send`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+use std::path::PathBuf;
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::util::nodemap::FxHashMap;
+
+use crate::clean::{self, GetDefId};
+use crate::config::Options;
+use crate::doctree;
+use crate::html::format::{AbiSpace, AsyncSpace, ConstnessSpace, GenericBounds, UnsafetySpace};
+use crate::html::format::{MutableSpace, WhereClause};
+use crate::html::item_type::ItemType;
+
+/// Documents the crate at `baseline` and the crate given in `options`, and prints the changes
+/// between their public APIs. Returns the exit code, which is a failure if there are breaking
+/// changes.
+pub fn run(options: Options, baseline: PathBuf) -> i32 {
+    let mut baseline_options = options.clone();
+    baseline_options.input = baseline;
+    let old = crate::rust_input(baseline_options, |out| Api::new(&out.krate));
+    let new = crate::rust_input(options, |out| Api::new(&out.krate));
+
+    let report = Report::new(&old, &new);
+    report.print(&new.crate_name);
+    if report.breaking.is_empty() {
+        rustc_driver::EXIT_SUCCESS
+    } else {
+        rustc_driver::EXIT_FAILURE
+    }
+}
+
+/// An item is identified by its path and namespace, so that a tuple struct and its constructor
+/// function, or a macro and a module with the same name, are not confused.
+type ItemKey = (String, &'static str);
+
+/// The public API of a version of a crate.
+struct Api {
+    crate_name: String,
+    items: BTreeMap<ItemKey, ApiItem>,
+}
+
+struct ApiItem {
+    kind: ItemType,
+    /// The parts of the declaration of the item users can rely on, printed as Rust code.
+    signature: String,
+    /// The item containing this one, for fields, variants and associated items.
+    parent: Option<ItemKey>,
+    /// Whether this is a trait item without a default.
+    required: bool,
+    /// Whether this is a `#[non_exhaustive]` type or variant.
+    non_exhaustive: bool,
+    /// Whether this is a struct or struct variant with fields hidden from users.
+    private_fields: bool,
+    /// For types, the traits they implement, including auto traits.
+    impls: BTreeSet<String>,
+}
+
+impl Api {
+    fn new(krate: &clean::Crate) -> Api {
+        let mut collector = ApiCollector {
+            items: BTreeMap::new(),
+            type_keys: FxHashMap::default(),
+            impls: Vec::new(),
+        };
+        if let Some(ref module) = krate.module {
+            if let clean::ModuleItem(ref m) = module.inner {
+                for item in &m.items {
+                    collector.collect(item, "", None);
+                }
+            }
+        }
+
+        // Impls can appear before the types they are for, so they are only attached to their
+        // types once everything has been collected.
+        for (did, impl_) in mem::replace(&mut collector.impls, Vec::new()) {
+            let key = match collector.type_keys.get(&did) {
+                Some(key) => key.clone(),
+                None => continue,
+            };
+            match impl_.trait_ {
+                Some(ref trait_) => {
+                    if impl_.polarity != Some(clean::ImplPolarity::Negative) &&
+                       impl_.blanket_impl.is_none() {
+                        let trait_ = format!("{:#}", trait_);
+                        collector.items.get_mut(&key).unwrap().impls.insert(trait_);
+                    }
+                }
+                None => {
+                    for item in &impl_.items {
+                        collector.collect(item, &key.0, Some(key.clone()));
+                    }
+                }
+            }
+        }
+
+        Api {
+            crate_name: krate.name.clone(),
+            items: collector.items,
+        }
+    }
+}
+
+struct ApiCollector {
+    items: BTreeMap<ItemKey, ApiItem>,
+    /// The key of every type, for attaching impls to them.
+    type_keys: FxHashMap<DefId, ItemKey>,
+    impls: Vec<(DefId, clean::Impl)>,
+}
+
+impl ApiCollector {
+    fn collect(&mut self, item: &clean::Item, parent_path: &str, parent: Option<ItemKey>) {
+        if item.is_stripped() {
+            return;
+        }
+        if let clean::ImplItem(ref impl_) = item.inner {
+            if let Some(did) = impl_.for_.def_id() {
+                self.impls.push((did, impl_.clone()));
+            }
+            return;
+        }
+
+        let signature = match signature(item) {
+            Some(signature) => signature,
+            None => return,
+        };
+        // Re-exports that rustdoc doesn't inline have no name of their own.
+        let name = match (&item.name, &item.inner) {
+            (Some(name), _) => name.clone(),
+            (None, clean::ImportItem(clean::Import::Simple(name, _))) => name.clone(),
+            (None, clean::ImportItem(clean::Import::Glob(source))) => {
+                format!("{:#}::*", source.path)
+            }
+            (None, _) => return,
+        };
+        // Whether a trait method has a default is tracked by `required`, so that removing the
+        // default is not also reported as a change of kind.
+        let kind = match item.type_() {
+            ItemType::TyMethod => ItemType::Method,
+            kind => kind,
+        };
+        let path = if parent_path.is_empty() {
+            name
+        } else {
+            format!("{}::{}", parent_path, name)
+        };
+        let key = (path.clone(), kind.name_space().to_static_str());
+
+        let children: &[clean::Item] = match item.inner {
+            clean::ModuleItem(ref m) => &m.items,
+            clean::StructItem(ref s) => &s.fields,
+            clean::UnionItem(ref u) => &u.fields,
+            clean::EnumItem(ref e) => &e.variants.raw,
+            clean::VariantItem(clean::Variant {
+                kind: clean::VariantKind::Struct(ref s),
+            }) => &s.fields,
+            clean::TraitItem(ref t) => &t.items,
+            _ => &[],
+        };
+        // Modules only group items, they are not their parent in the sense of `ApiItem::parent`.
+        let child_parent = if kind == ItemType::Module { None } else { Some(key.clone()) };
+        for child in children {
+            self.collect(child, &path, child_parent.clone());
+        }
+
+        match kind {
+            ItemType::Struct | ItemType::Union | ItemType::Enum | ItemType::Typedef |
+            ItemType::ForeignType => {
+                self.type_keys.insert(item.def_id, key.clone());
+            }
+            _ => {}
+        }
+        let required = match item.inner {
+            clean::TyMethodItem(..) => true,
+            clean::AssociatedConstItem(_, ref default) => default.is_none(),
+            clean::AssociatedTypeItem(_, ref default) => default.is_none(),
+            _ => false,
+        };
+        let private_fields = match item.inner {
+            clean::StructItem(ref s) => s.fields_stripped,
+            clean::VariantItem(clean::Variant {
+                kind: clean::VariantKind::Struct(ref s),
+            }) => s.fields_stripped,
+            _ => false,
+        };
+        self.items.insert(key, ApiItem {
+            kind,
+            signature,
+            parent,
+            required,
+            non_exhaustive: item.is_non_exhaustive(),
+            private_fields,
+            impls: BTreeSet::new(),
+        });
+    }
+}
+
+/// Prints the parts of the declaration of `item` that matter for compatibility, or returns `None`
+/// if the item is not part of the API by itself.
+fn signature(item: &clean::Item) -> Option<String> {
+    Some(match item.inner {
+        clean::ModuleItem(..) => "mod".to_string(),
+        clean::StructItem(ref s) => {
+            format!("{}{:#}{:#}{}", struct_type(s.struct_type), s.generics,
+                    where_clause(&s.generics), private_fields(s.fields_stripped))
+        }
+        clean::UnionItem(ref u) => {
+            format!("union{:#}{:#}", u.generics, where_clause(&u.generics))
+        }
+        clean::EnumItem(ref e) => format!("enum{:#}{:#}", e.generics, where_clause(&e.generics)),
+        clean::VariantItem(ref v) => match v.kind {
+            clean::VariantKind::CLike => "unit variant".to_string(),
+            clean::VariantKind::Tuple(ref types) => format!("tuple variant({})", types_list(types)),
+            clean::VariantKind::Struct(ref s) => {
+                format!("struct variant{}", private_fields(s.fields_stripped))
+            }
+        },
+        clean::StructFieldItem(ref ty) => format!("{:#}", ty),
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            fn_signature(&f.header, &f.generics, &f.decl)
+        }
+        clean::MethodItem(ref m) => fn_signature(&m.header, &m.generics, &m.decl),
+        clean::TyMethodItem(ref m) => fn_signature(&m.header, &m.generics, &m.decl),
+        clean::TypedefItem(ref t, _) => {
+            format!("type{:#}{:#} = {:#}", t.generics, where_clause(&t.generics), t.type_)
+        }
+        clean::ExistentialItem(ref e, _) => {
+            format!("existential type{:#}: {:#}", e.generics, GenericBounds(&e.bounds))
+        }
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+            format!("static {}{:#}", MutableSpace(s.mutability), s.type_)
+        }
+        clean::ConstantItem(ref c) => format!("const {:#}", c.type_),
+        clean::TraitItem(ref t) => {
+            format!("{}trait{:#}: {:#}{:#}", UnsafetySpace(t.unsafety), t.generics,
+                    GenericBounds(&t.bounds), where_clause(&t.generics))
+        }
+        clean::TraitAliasItem(ref t) => {
+            format!("trait{:#} = {:#}{:#}", t.generics, GenericBounds(&t.bounds),
+                    where_clause(&t.generics))
+        }
+        clean::AssociatedConstItem(ref ty, _) => format!("const {:#}", ty),
+        clean::AssociatedTypeItem(ref bounds, _) => format!("type: {:#}", GenericBounds(bounds)),
+        clean::ForeignTypeItem => "extern type".to_string(),
+        clean::MacroItem(..) => "macro".to_string(),
+        // The kind of procedural macro is part of the kind of the item.
+        clean::ProcMacroItem(..) => "proc macro".to_string(),
+        // Where a re-export points to can change without affecting users.
+        clean::ImportItem(clean::Import::Simple(..)) => "use".to_string(),
+        clean::ImportItem(clean::Import::Glob(..)) => "use *".to_string(),
+        clean::ExternCrateItem(..) |
+        clean::ImplItem(..) |
+        clean::PrimitiveItem(..) |
+        clean::KeywordItem(..) |
+        clean::StrippedItem(..) => return None,
+    })
+}
+
+/// The signature of a function, leaving out the names of the arguments, which can change without
+/// affecting callers.
+fn fn_signature(header: &hir::FnHeader,
+                generics: &clean::Generics,
+                decl: &clean::FnDecl) -> String {
+    let inputs = decl.inputs.values.iter().map(|arg| &arg.type_).cloned().collect::<Vec<_>>();
+    format!("{}{}{}{:#}fn{:#}({}){:#}{:#}",
+            ConstnessSpace(header.constness),
+            UnsafetySpace(header.unsafety),
+            AsyncSpace(header.asyncness),
+            AbiSpace(header.abi),
+            generics,
+            types_list(&inputs),
+            decl.output,
+            where_clause(generics))
+}
+
+/// Private fields prevent users from building the type or matching it exhaustively.
+fn private_fields(fields_stripped: bool) -> &'static str {
+    if fields_stripped { " { /* private fields */ }" } else { "" }
+}
+
+fn types_list(types: &[clean::Type]) -> String {
+    types.iter().map(|ty| format!("{:#}", ty)).collect::<Vec<_>>().join(", ")
+}
+
+fn where_clause(generics: &clean::Generics) -> WhereClause<'_> {
+    WhereClause { gens: generics, indent: 0, end_newline: false }
+}
+
+fn struct_type(struct_type: doctree::StructType) -> &'static str {
+    match struct_type {
+        doctree::Plain => "struct",
+        doctree::Tuple => "tuple struct",
+        doctree::Unit => "unit struct",
+    }
+}
+
+/// The differences between two versions of an API.
+struct Report {
+    breaking: Vec<String>,
+    additions: Vec<String>,
+}
+
+impl Report {
+    fn new(old: &Api, new: &Api) -> Report {
+        let mut report = Report {
+            breaking: Vec::new(),
+            additions: Vec::new(),
+        };
+
+        for (key, old_item) in &old.items {
+            let new_item = match new.items.get(key) {
+                Some(new_item) => new_item,
+                None => {
+                    // Only the removal of the parent is reported.
+                    let parent_removed = old_item.parent.as_ref().map_or(false, |parent| {
+                        !new.items.contains_key(parent)
+                    });
+                    if !parent_removed {
+                        report.breaking.push(format!("removed {} `{}`", old_item.kind, key.0));
+                    }
+                    continue;
+                }
+            };
+            if old_item.kind != new_item.kind || old_item.signature != new_item.signature {
+                report.breaking.push(format!(
+                    "changed {} `{}`\n    old: {}\n    new: {}",
+                    old_item.kind, key.0, old_item.signature, new_item.signature,
+                ));
+            }
+            if new_item.required && !old_item.required {
+                report.breaking.push(format!("{} `{}` no longer has a default",
+                                             new_item.kind, key.0));
+            }
+            for trait_ in old_item.impls.difference(&new_item.impls) {
+                report.breaking.push(format!("`{}` no longer implements `{}`", key.0, trait_));
+            }
+        }
+
+        for (key, new_item) in &new.items {
+            if old.items.contains_key(key) {
+                continue;
+            }
+            let old_parent = new_item.parent.as_ref().and_then(|parent| old.items.get(parent));
+            match old_parent {
+                Some(parent) if new_item.kind == ItemType::Variant && !parent.non_exhaustive => {
+                    report.breaking.push(format!(
+                        "added variant `{}` to enum `{}`, which is not `#[non_exhaustive]`",
+                        key.0, new_item.parent.as_ref().unwrap().0,
+                    ));
+                }
+                // Struct literals and exhaustive patterns have to list every field, unlike
+                // the ones of unions, which name a single field.
+                Some(parent) if new_item.kind == ItemType::StructField &&
+                                parent.kind != ItemType::Union &&
+                                !parent.non_exhaustive && !parent.private_fields => {
+                    report.breaking.push(format!(
+                        "added field `{}` to {} `{}`, which has no private fields and is not \
+                         `#[non_exhaustive]`",
+                        key.0, parent.kind, new_item.parent.as_ref().unwrap().0,
+                    ));
+                }
+                Some(parent) if new_item.required && parent.kind == ItemType::Trait => {
+                    report.breaking.push(format!(
+                        "added required {} `{}` to trait `{}`",
+                        new_item.kind, key.0, new_item.parent.as_ref().unwrap().0,
+                    ));
+                }
+                Some(_) => report.additions.push(format!("added {} `{}`", new_item.kind, key.0)),
+                None if new_item.parent.is_none() => {
+                    report.additions.push(format!("added {} `{}`", new_item.kind, key.0));
+                }
+                // Items added along with their parent are not listed separately.
+                None => {}
+            }
+        }
+
+        report
+    }
+
+    fn print(&self, crate_name: &str) {
+        if self.breaking.is_empty() && self.additions.is_empty() {
+            println!("no changes to the public API of `{}`", crate_name);
+        }
+        if !self.breaking.is_empty() {
+            println!("breaking changes to the public API of `{}`:", crate_name);
+            for change in &self.breaking {
+                println!("  {}", change);
+            }
+        }
+        if !self.additions.is_empty() {
+            println!("additions to the public API of `{}`:", crate_name);
+            for change in &self.additions {
+                println!("  {}", change);
+            }
+        }
+        let bump = if !self.breaking.is_empty() {
+            "major"
+        } else if !self.additions.is_empty() {
+            "minor"
+        } else {
+            "patch"
+        };
+        println!("required version bump: {}", bump);
+    }
+}
@@ -22,6 +22,7 @@ use crate::html::{static_files};
 use crate::html::markdown::{IdMap};
 use crate::opts;
 use crate::passes::{self, DefaultPassOption};
+use crate::scrape_examples::ScrapeExamplesOptions;
 use crate::theme;
 
 /// Configuration options for rustdoc.
@@ -71,6 +72,9 @@ pub struct Options {
     /// Optional path to persist the doctest executables to, defaults to a
     /// temporary directory if not set.
     pub persist_doctests: Option<PathBuf>,
+    /// Whether to compile the doctests that allow it into a single binary per edition, instead of
+    /// one binary per doctest.
+    pub merge_doctests: bool,
 
     // Options that affect the documentation process
 
@@ -88,6 +92,17 @@ pub struct Options {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     pub show_coverage: bool,
+    /// If present, the percentage of documented items below which `--show-coverage` fails.
+    pub coverage_threshold: Option<f64>,
+    /// If present, the crate root of a previous version of the crate, whose public API should be
+    /// compared with the one of `input` instead of generating documentation.
+    pub api_baseline: Option<PathBuf>,
+    /// If present, where to write the calls made by the crate to the functions of other crates,
+    /// instead of generating documentation.
+    pub scrape_examples: Option<ScrapeExamplesOptions>,
+    /// Calls files written while scraping examples, whose calls are shown on the pages of the
+    /// functions they call.
+    pub with_examples: Vec<PathBuf>,
 
     // Options that alter generated documentation pages
 
@@ -128,10 +143,15 @@ impl fmt::Debug for Options {
             .field("should_test", &self.should_test)
             .field("test_args", &self.test_args)
             .field("persist_doctests", &self.persist_doctests)
+            .field("merge_doctests", &self.merge_doctests)
             .field("default_passes", &self.default_passes)
             .field("manual_passes", &self.manual_passes)
             .field("display_warnings", &self.display_warnings)
             .field("show_coverage", &self.show_coverage)
+            .field("coverage_threshold", &self.coverage_threshold)
+            .field("api_baseline", &self.api_baseline)
+            .field("scrape_examples", &self.scrape_examples)
+            .field("with_examples", &self.with_examples)
             .field("crate_version", &self.crate_version)
             .field("render_options", &self.render_options)
             .finish()
@@ -198,6 +218,20 @@ pub struct RenderOptions {
     pub generate_search_filter: bool,
     /// Option (disabled by default) to generate files used by RLS and some other tools.
     pub generate_redirect_pages: bool,
+    /// The format of the generated documentation. `Html` by default.
+    pub output_format: OutputFormat,
+    /// Whether to generate an index of the complete documentation of the items, for the search to
+    /// look into.
+    pub full_text_search: bool,
+}
+
+/// The formats rustdoc can generate documentation in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// A set of HTML pages, the default.
+    Html,
+    /// A single JSON file describing the crate, for use by other tools.
+    Json,
 }
 
 impl Options {
@@ -267,10 +301,17 @@ impl Options {
                 json_rendered: HumanReadableErrorType::Default(ColorConfig::Never),
             },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") if nightly_options::is_unstable_enabled(matches) => {
+                ErrorOutputType::Sarif
+            }
+            Some("sarif") => {
+                early_error(ErrorOutputType::default(),
+                            "--error-format=sarif is unstable");
+            }
             Some(arg) => {
                 early_error(ErrorOutputType::default(),
-                            &format!("argument for --error-format must be `human`, `json` or \
-                                      `short` (instead was `{}`)", arg));
+                            &format!("argument for --error-format must be `human`, `json`, \
+                                      `short` or `sarif` (instead was `{}`)", arg));
             }
         };
 
@@ -415,13 +456,20 @@ impl Options {
             }
         }
 
-        match matches.opt_str("w").as_ref().map(|s| &**s) {
-            Some("html") | None => {}
+        let output_format = match matches.opt_str("w").as_ref().map(|s| &**s) {
+            Some("html") | None => OutputFormat::Html,
+            Some("json") if nightly_options::is_unstable_enabled(matches) => OutputFormat::Json,
+            Some("json") => {
+                diag.struct_err("the `json` output format is unstable")
+                    .note("the `-Z unstable-options` flag must be passed to enable it")
+                    .emit();
+                return Err(1);
+            }
             Some(s) => {
                 diag.struct_err(&format!("unknown output format: {}", s)).emit();
                 return Err(1);
             }
-        }
+        };
 
         let index_page = matches.opt_str("index-page").map(|s| PathBuf::from(&s));
         if let Some(ref index_page) = index_page {
@@ -440,9 +488,51 @@ impl Options {
         });
 
         let show_coverage = matches.opt_present("show-coverage");
+        let coverage_threshold = match matches.opt_str("coverage-threshold") {
+            Some(_) if !show_coverage => {
+                diag.struct_err("`--coverage-threshold` requires `--show-coverage`").emit();
+                return Err(1);
+            }
+            Some(threshold) => match threshold.parse::<f64>() {
+                Ok(threshold) if threshold >= 0.0 && threshold <= 100.0 => Some(threshold),
+                _ => {
+                    diag.struct_err(&format!("invalid coverage threshold `{}`, expected a \
+                                              percentage between 0 and 100", threshold))
+                        .emit();
+                    return Err(1);
+                }
+            },
+            None => None,
+        };
+        let api_baseline = matches.opt_str("api-baseline").map(PathBuf::from);
         let document_private = matches.opt_present("document-private-items");
 
-        let default_passes = if matches.opt_present("no-defaults") {
+        let scrape_output_path = matches.opt_str("scrape-examples-output-path").map(PathBuf::from);
+        let scrape_target_crates = matches.opt_strs("scrape-examples-target-crate");
+        let scrape_examples = match (scrape_output_path, scrape_target_crates.is_empty()) {
+            (Some(output_path), false) => Some(ScrapeExamplesOptions {
+                output_path,
+                target_crates: scrape_target_crates,
+            }),
+            (None, true) => None,
+            (Some(_), true) => {
+                diag.struct_err("`--scrape-examples-output-path` requires at least one \
+                                 `--scrape-examples-target-crate`")
+                    .emit();
+                return Err(1);
+            }
+            (None, false) => {
+                diag.struct_err("`--scrape-examples-target-crate` requires \
+                                 `--scrape-examples-output-path`")
+                    .emit();
+                return Err(1);
+            }
+        };
+        let with_examples = matches.opt_strs("with-examples").into_iter()
+                                                             .map(PathBuf::from)
+                                                             .collect();
+
+        let default_passes = if matches.opt_present("no-defaults") || scrape_examples.is_some() {
             passes::DefaultPassOption::None
         } else if show_coverage && document_private {
             passes::DefaultPassOption::PrivateCoverage
@@ -471,7 +561,9 @@ impl Options {
         let static_root_path = matches.opt_str("static-root-path");
         let generate_search_filter = !matches.opt_present("disable-per-crate-search");
         let persist_doctests = matches.opt_str("persist-doctests").map(PathBuf::from);
+        let merge_doctests = matches.opt_present("merge-doctests");
         let generate_redirect_pages = matches.opt_present("generate-redirect-pages");
+        let full_text_search = matches.opt_present("full-text-search");
 
         let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(matches, error_format);
 
@@ -497,8 +589,13 @@ impl Options {
             manual_passes,
             display_warnings,
             show_coverage,
+            coverage_threshold,
+            api_baseline,
+            scrape_examples,
+            with_examples,
             crate_version,
             persist_doctests,
+            merge_doctests,
             render_options: RenderOptions {
                 output,
                 external_html,
@@ -518,6 +615,8 @@ impl Options {
                 markdown_playground_url,
                 generate_search_filter,
                 generate_redirect_pages,
+                output_format,
+                full_text_search,
             }
         })
     }
@@ -533,7 +632,6 @@ impl Options {
 fn check_deprecated_options(matches: &getopts::Matches, diag: &errors::Handler) {
     let deprecated_flags = [
        "input-format",
-       "output-format",
        "no-defaults",
        "passes",
     ];
@@ -18,6 +18,7 @@ use rustc_target::spec::TargetTriple;
 use syntax::source_map;
 use syntax::feature_gate::UnstableFeatures;
 use syntax::json::JsonEmitter;
+use syntax::sarif::SarifEmitter;
 use syntax::symbol::sym;
 use errors;
 use errors::emitter::{Emitter, EmitterWriter};
@@ -30,7 +31,7 @@ use std::sync::Arc;
 use std::rc::Rc;
 
 use crate::visit_ast::RustdocVisitor;
-use crate::config::{Options as RustdocOptions, RenderOptions};
+use crate::config::{Options as RustdocOptions, OutputFormat, RenderOptions};
 use crate::clean;
 use crate::clean::{Clean, MAX_DEF_ID, AttributesExt};
 use crate::html::render::RenderInfo;
@@ -74,6 +75,10 @@ pub struct DocContext<'tcx> {
     // FIXME(eddyb) make this a `ty::TraitRef<'tcx>` set.
     pub generated_synthetics: RefCell<FxHashSet<(Ty<'tcx>, DefId)>>,
     pub all_traits: Vec<DefId>,
+    /// The format of the output, used for the report of `--show-coverage` as well.
+    pub output_format: OutputFormat,
+    /// If present, the percentage of documented items below which `--show-coverage` fails.
+    pub coverage_threshold: Option<f64>,
 }
 
 impl<'tcx> DocContext<'tcx> {
@@ -181,8 +186,8 @@ impl DocAccessLevels for AccessLevels<DefId> {
 
 /// Creates a new diagnostic `Handler` that can be used to emit warnings and errors.
 ///
-/// If the given `error_format` is `ErrorOutputType::Json` and no `SourceMap` is given, a new one
-/// will be created for the handler.
+/// If the given `error_format` is `ErrorOutputType::Json` or `ErrorOutputType::Sarif` and no
+/// `SourceMap` is given, a new one will be created for the handler.
 pub fn new_handler(error_format: ErrorOutputType,
                    source_map: Option<Lrc<source_map::SourceMap>>,
                    treat_err_as_bug: Option<usize>,
@@ -215,6 +220,11 @@ pub fn new_handler(error_format: ErrorOutputType,
                 ).ui_testing(ui_testing)
             )
         },
+        ErrorOutputType::Sarif => {
+            let source_map = source_map.unwrap_or_else(
+                || Lrc::new(source_map::SourceMap::new(sessopts.file_path_mapping())));
+            Box::new(SarifEmitter::stderr(None, source_map))
+        },
     };
 
     errors::Handler::with_emitter_and_flags(
@@ -250,6 +260,9 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
         mut default_passes,
         mut manual_passes,
         display_warnings,
+        scrape_examples,
+        with_examples,
+        coverage_threshold,
         render_options,
         ..
     } = options;
@@ -317,6 +330,8 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
         // Ensure that rustdoc works even if rustc is feature-staged
         unstable_features: UnstableFeatures::Allow,
         actually_rustdoc: true,
+        // The calls scraped from examples are found in the bodies of their functions.
+        rustdoc_keep_bodies: scrape_examples.is_some(),
         debugging_opts: debugging_options.clone(),
         error_format,
         edition,
@@ -358,6 +373,11 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
             // Abort if there were any errors so far
             sess.abort_if_errors();
 
+            if let Some(ref scrape_examples) = scrape_examples {
+                crate::scrape_examples::run(tcx, scrape_examples);
+                sess.abort_if_errors();
+            }
+
             let access_levels = tcx.privacy_access_levels(LOCAL_CRATE);
             // Convert from a HirId set to a DefId set since we don't always have easy access
             // to the map from defid -> hirid
@@ -375,6 +395,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
 
             let mut renderinfo = RenderInfo::default();
             renderinfo.access_levels = access_levels;
+            renderinfo.call_examples = crate::scrape_examples::load(tcx, &with_examples);
 
             let ctxt = DocContext {
                 tcx,
@@ -393,6 +414,8 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
                 all_fake_def_ids: Default::default(),
                 generated_synthetics: Default::default(),
                 all_traits: tcx.all_traits(LOCAL_CRATE).to_vec(),
+                output_format: render_options.output_format,
+                coverage_threshold,
             };
             debug!("crate: {:?}", tcx.hir().krate());
 
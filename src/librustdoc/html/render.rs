@@ -70,6 +70,7 @@ use crate::html::format::fmt_impl_for_trait_page;
 use crate::html::item_type::ItemType;
 use crate::html::markdown::{self, Markdown, MarkdownHtml, MarkdownSummaryLine, ErrorCodes, IdMap};
 use crate::html::{highlight, layout, static_files};
+use crate::scrape_examples::CallExample;
 
 use minifier;
 
@@ -363,6 +364,14 @@ pub struct Cache {
     /// Aliases added through `#[doc(alias = "...")]`. Since a few items can have the same alias,
     /// we need the alias element to have an array of items.
     aliases: FxHashMap<String, Vec<IndexItem>>,
+
+    /// Calls to the local functions scraped from the examples and tests of the crate, shown on the
+    /// pages of the functions.
+    call_examples: FxHashMap<DefId, Vec<CallExample>>,
+
+    /// Whether the complete documentation of the items in the search index is kept, to build the
+    /// full-text index.
+    full_text_search: bool,
 }
 
 /// Temporary storage for data obtained during `RustdocVisitor::clean()`.
@@ -377,6 +386,7 @@ pub struct RenderInfo {
     pub deref_trait_did: Option<DefId>,
     pub deref_mut_trait_did: Option<DefId>,
     pub owned_box_did: Option<DefId>,
+    pub call_examples: FxHashMap<DefId, Vec<CallExample>>,
 }
 
 /// Helper struct to render all source code to HTML pages
@@ -410,6 +420,8 @@ struct IndexItem {
     name: String,
     path: String,
     desc: String,
+    /// The complete documentation of the item, only kept for the full-text index.
+    full_doc: Option<String>,
     parent: Option<DefId>,
     parent_idx: Option<usize>,
     search_type: Option<IndexItemFunctionType>,
@@ -530,6 +542,7 @@ pub fn run(mut krate: clean::Crate,
         static_root_path,
         generate_search_filter,
         generate_redirect_pages,
+        full_text_search,
         ..
     } = options;
 
@@ -618,6 +631,7 @@ pub fn run(mut krate: clean::Crate,
         deref_trait_did,
         deref_mut_trait_did,
         owned_box_did,
+        call_examples,
     } = renderinfo;
 
     let external_paths = external_paths.into_iter()
@@ -648,6 +662,8 @@ pub fn run(mut krate: clean::Crate,
         masked_crates: mem::replace(&mut krate.masked_crates, Default::default()),
         param_names: external_param_names,
         aliases: Default::default(),
+        call_examples,
+        full_text_search,
     };
 
     // Cache where all our extern crates are located
@@ -693,6 +709,11 @@ pub fn run(mut krate: clean::Crate,
 
     // Build our search index
     let index = build_index(&krate, &mut cache);
+    let full_text_index = if full_text_search {
+        Some(build_full_text_index(&cache.search_index))
+    } else {
+        None
+    };
 
     // Freeze the cache now that the index has been built. Put an Arc into TLS
     // for future parallelization opportunities
@@ -701,6 +722,9 @@ pub fn run(mut krate: clean::Crate,
     CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
 
     write_shared(&cx, &krate, &*cache, index, &md_opts, diag)?;
+    if let Some(ref full_text_index) = full_text_index {
+        write_full_text_index(&cx, &krate, full_text_index)?;
+    }
 
     // And finally render the whole crate's documentation
     cx.krate(krate)
@@ -714,7 +738,8 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
 
     let Cache { ref mut search_index,
                 ref orphan_impl_items,
-                ref mut paths, .. } = *cache;
+                ref mut paths,
+                full_text_search, .. } = *cache;
 
     // Attach all orphan items to the type's definition if the type
     // has since been learned.
@@ -725,6 +750,11 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 name: item.name.clone().unwrap(),
                 path: fqp[..fqp.len() - 1].join("::"),
                 desc: plain_summary_line_short(item.doc_value()),
+                full_doc: if full_text_search {
+                    item.doc_value().map(str::to_string)
+                } else {
+                    None
+                },
                 parent: Some(did),
                 parent_idx: None,
                 search_type: get_index_search_type(&item),
@@ -769,6 +799,9 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
     crate_data.insert("doc".to_owned(), Json::String(crate_doc));
     crate_data.insert("i".to_owned(), Json::Array(crate_items));
     crate_data.insert("p".to_owned(), Json::Array(crate_paths));
+    if full_text_search {
+        crate_data.insert("f".to_owned(), Json::Boolean(true));
+    }
 
     // Collect the index into a string
     format!("searchIndex[{}] = {};",
@@ -776,6 +809,95 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
             Json::Object(crate_data))
 }
 
+/// Returns the key of the chunk of the full-text index containing `word`. The index is split by the
+/// first two characters of the words so that a search only loads the chunks of the words it looks
+/// for. This, and how `is_full_text_word_char` splits the documentation into words, must be kept
+/// in sync with `fullTextKey` and the splitting of the query in `execFullTextSearch` in `main.js`.
+fn full_text_key(word: &str) -> String {
+    let key = word.chars().take(2).collect::<String>();
+    if key.len() == 2 && key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit()) {
+        key
+    } else {
+        "other".to_string()
+    }
+}
+
+/// Whether `c` is part of a word in the full-text index. Words are made of ASCII letters and digits
+/// and of the characters that aren't ASCII, except for the ranges of punctuation and symbols
+/// U+0080 to U+00BF, U+2000 to U+206F and U+3000 to U+303F, so that dashes or quotes like `—`
+/// and `’` separate words. Only ranges are used, so that `main.js` can split queries the same way
+/// with a regular expression.
+fn is_full_text_word_char(c: char) -> bool {
+    match c {
+        'a'..='z' | 'A'..='Z' | '0'..='9' => true,
+        '\u{c0}'..='\u{1fff}' | '\u{2070}'..='\u{2fff}' | '\u{3040}'..='\u{10ffff}' => true,
+        _ => false,
+    }
+}
+
+/// Builds the full-text index of the documentation of the items in the search index, mapping the
+/// key of each chunk to its words, and each word to the positions of the items whose
+/// documentation contains it. Code blocks are left out, as they are rarely what one searches for.
+fn build_full_text_index(
+    search_index: &[IndexItem],
+) -> BTreeMap<String, BTreeMap<String, Vec<usize>>> {
+    let mut index = BTreeMap::<String, BTreeMap<String, Vec<usize>>>::new();
+    for (pos, item) in search_index.iter().enumerate() {
+        let doc = match item.full_doc {
+            Some(ref doc) => doc,
+            None => continue,
+        };
+        let mut in_code_block = false;
+        for line in doc.lines() {
+            let line = line.trim_start();
+            if line.starts_with("```") || line.starts_with("~~~") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+            if in_code_block {
+                continue;
+            }
+            for word in line.split(|c: char| !is_full_text_word_char(c)) {
+                if word.chars().nth(1).is_none() {
+                    continue;
+                }
+                let word = word.to_lowercase();
+                let positions = index.entry(full_text_key(&word))
+                                     .or_default()
+                                     .entry(word)
+                                     .or_default();
+                if positions.last() != Some(&pos) {
+                    positions.push(pos);
+                }
+            }
+        }
+    }
+    index
+}
+
+/// Writes the chunks of the full-text index of the crate to `full-text/<crate>/`, replacing the
+/// ones of a previous run.
+fn write_full_text_index(
+    cx: &Context,
+    krate: &clean::Crate,
+    index: &BTreeMap<String, BTreeMap<String, Vec<usize>>>,
+) -> Result<(), Error> {
+    let dir = cx.dst.join("full-text").join(&krate.name);
+    if dir.exists() {
+        try_err!(fs::remove_dir_all(&dir), &dir);
+    }
+    try_err!(fs::create_dir_all(&dir), &dir);
+    for (key, words) in index {
+        let words = words.iter()
+                         .map(|(word, positions)| (word.clone(), positions.to_json()))
+                         .collect();
+        write(dir.join(format!("{}.js", key)),
+              format!("addFullTextChunk({},{},{});",
+                      as_json(&krate.name), as_json(key), Json::Object(words)).as_bytes())?;
+    }
+    Ok(())
+}
+
 fn write_shared(
     cx: &Context,
     krate: &clean::Crate,
@@ -1600,6 +1722,11 @@ impl DocFolder for Cache {
                             name: s.to_string(),
                             path: path.join("::"),
                             desc: plain_summary_line_short(item.doc_value()),
+                            full_doc: if self.full_text_search {
+                                item.doc_value().map(str::to_string)
+                            } else {
+                                None
+                            },
                             parent,
                             parent_idx: None,
                             search_type: get_index_search_type(&item),
@@ -2592,6 +2719,36 @@ fn document_full(w: &mut fmt::Formatter<'_>, item: &clean::Item,
     Ok(())
 }
 
+/// The number of scraped calls to a function shown with their code.
+const MAX_CALL_EXAMPLES: usize = 3;
+
+/// Renders a few of the calls to `item` scraped from the examples and tests of the crate, followed
+/// by the locations of the others.
+fn render_call_examples(w: &mut fmt::Formatter<'_>, item: &clean::Item,
+                        is_hidden: bool) -> fmt::Result {
+    let cache = cache();
+    let examples = match cache.call_examples.get(&item.def_id) {
+        Some(examples) if !examples.is_empty() => examples,
+        _ => return Ok(()),
+    };
+    write!(w, "<div class='docblock scraped-examples{}'><h4>Examples found in the crate</h4>",
+           if is_hidden { " hidden" } else { "" })?;
+    for example in examples.iter().take(MAX_CALL_EXAMPLES) {
+        write!(w, "<div class='scraped-example'><div class='scraped-example-title'>{}:{}</div>{}\
+                   </div>",
+               Escape(&example.file), example.line,
+               highlight::render_with_highlighting(&example.code, Some("rust"), None, None))?;
+    }
+    if examples.len() > MAX_CALL_EXAMPLES {
+        write!(w, "<p>Also called from:</p><ul class='scraped-example-locations'>")?;
+        for example in &examples[MAX_CALL_EXAMPLES..] {
+            write!(w, "<li>{}:{}</li>", Escape(&example.file), example.line)?;
+        }
+        write!(w, "</ul>")?;
+    }
+    write!(w, "</div>")
+}
+
 fn document_stability(w: &mut fmt::Formatter<'_>, cx: &Context, item: &clean::Item,
                       is_hidden: bool) -> fmt::Result {
     let stabilities = short_stability(item, cx);
@@ -3045,7 +3202,8 @@ fn item_function(w: &mut fmt::Formatter<'_>, cx: &Context, it: &clean::Item,
               indent: 0,
               asyncness: f.header.asyncness,
            })?;
-    document(w, cx, it)
+    document(w, cx, it)?;
+    render_call_examples(w, it, false)
 }
 
 fn render_implementor(cx: &Context, implementor: &Impl, w: &mut fmt::Formatter<'_>,
@@ -4278,6 +4436,7 @@ fn render_impl(w: &mut fmt::Formatter<'_>, cx: &Context, i: &Impl, link: AssocIt
                     document_stability(w, cx, item, is_hidden)?;
                     if show_def_docs {
                         document_full(w, item, cx, "", is_hidden)?;
+                        render_call_examples(w, item, is_hidden)?;
                     }
                 }
             } else {
@@ -426,6 +426,11 @@ if (!DOMTokenList.prototype.remove) {
 
     function initSearch(rawSearchIndex) {
         var currentResults, index, searchIndex;
+        // The chunks of the full-text indexes loaded so far, by crate and key. The chunks being
+        // loaded are `null`.
+        var fullTextChunks = {};
+        // The position in `searchIndex` of the first item of each crate with a full-text index.
+        var fullTextOffsets = {};
         var MAX_LEV_DISTANCE = 3;
         var MAX_RESULTS = 200;
         var GENERICS_DATA = 1;
@@ -1213,7 +1218,7 @@ if (!DOMTokenList.prototype.remove) {
             search_input.onkeydown = function(e) {
                 // "actives" references the currently highlighted item in each search tab.
                 // Each array in "actives" represents a tab.
-                var actives = [[], [], []];
+                var actives = [[], [], [], []];
                 // "current" is used to know which tab we're looking into.
                 var current = 0;
                 onEachLazy(document.getElementById("results").childNodes, function(e) {
@@ -1250,10 +1255,11 @@ if (!DOMTokenList.prototype.remove) {
                             actives[currentTab][0].getElementsByTagName("a")[0].href;
                     }
                 } else if (e.which === 9) { // tab
+                    var nbTabs = document.getElementById("titles").childNodes.length;
                     if (e.shiftKey) {
-                        printTab(currentTab > 0 ? currentTab - 1 : 2);
+                        printTab(currentTab > 0 ? currentTab - 1 : nbTabs - 1);
                     } else {
-                        printTab(currentTab > 1 ? 0 : currentTab + 1);
+                        printTab(currentTab < nbTabs - 1 ? currentTab + 1 : 0);
                     }
                     e.preventDefault();
                 } else if (e.which === 16) { // shift
@@ -1398,9 +1404,11 @@ if (!DOMTokenList.prototype.remove) {
 
             currentResults = query.id;
 
+            var hasFullText = Object.keys(fullTextOffsets).length > 0;
             var ret_others = addTab(results.others, query);
             var ret_in_args = addTab(results.in_args, query, false);
             var ret_returned = addTab(results.returned, query, false);
+            var ret_docs = hasFullText ? addTab(results.docs, query, false) : ["", 0];
 
             var output = "<h1>Results for " + escape(query.query) +
                 (query.type ? " (type: " + escape(query.type) + ")" : "") + "</h1>" +
@@ -1408,8 +1416,9 @@ if (!DOMTokenList.prototype.remove) {
                 makeTabHeader(0, "In Names", ret_others[1]) +
                 makeTabHeader(1, "In Parameters", ret_in_args[1]) +
                 makeTabHeader(2, "In Return Types", ret_returned[1]) +
+                (hasFullText ? makeTabHeader(3, "In Docs", ret_docs[1]) : "") +
                 "</div><div id=\"results\">" +
-                ret_others[0] + ret_in_args[0] + ret_returned[0] + "</div>";
+                ret_others[0] + ret_in_args[0] + ret_returned[0] + ret_docs[0] + "</div>";
 
             addClass(main, "hidden");
             var search = document.getElementById("search");
@@ -1429,6 +1438,9 @@ if (!DOMTokenList.prototype.remove) {
             elems[0].onclick = function() { printTab(0); };
             elems[1].onclick = function() { printTab(1); };
             elems[2].onclick = function() { printTab(2); };
+            if (hasFullText) {
+                elems[3].onclick = function() { printTab(3); };
+            }
             printTab(currentTab);
         }
 
@@ -1505,6 +1517,96 @@ if (!DOMTokenList.prototype.remove) {
             }
         }
 
+        // This, and the splitting of the query into words in `execFullTextSearch`, must be kept
+        // in sync with `full_text_key` and `is_full_text_word_char` in `render.rs`.
+        function fullTextKey(word) {
+            var key = word.substring(0, 2);
+            return /^[a-z0-9]{2}$/.test(key) ? key : "other";
+        }
+
+        function loadFullTextChunk(crate, key) {
+            fullTextChunks[crate + "/" + key] = null;
+            var script = document.createElement("script");
+            script.src = rootPath + "full-text/" + crate + "/" + key + ".js";
+            // There is no chunk for the keys that none of the words of the crate have.
+            script.onerror = function() {
+                window.addFullTextChunk(crate, key, {});
+            };
+            document.getElementsByTagName("head")[0].appendChild(script);
+        }
+
+        window.addFullTextChunk = function(crate, key, words) {
+            fullTextChunks[crate + "/" + key] = words;
+            if (search_input.value.trim().length > 0) {
+                search(undefined, true);
+            }
+        };
+
+        /**
+         * Looks for the items whose documentation contains words starting with each of the words
+         * of the query, loading the chunks of the full-text indexes it needs. The crates whose
+         * chunks are not loaded yet are skipped, the search is run again once they are.
+         */
+        function execFullTextSearch(raw, filterCrates) {
+            var words = raw.toLowerCase()
+                .split(/[^a-z0-9\u00c0-\u1fff\u2070-\u2fff\u3040-\uffff]+/)
+                .filter(function(w) {
+                    return w.length > 1;
+                });
+            var out = [];
+            if (words.length === 0) {
+                return out;
+            }
+            for (var crate in fullTextOffsets) {
+                if (!fullTextOffsets.hasOwnProperty(crate) ||
+                    (filterCrates !== undefined && crate !== filterCrates)) {
+                    continue;
+                }
+                var matches = null;
+                var complete = true;
+                for (var i = 0; i < words.length; ++i) {
+                    var key = fullTextKey(words[i]);
+                    var chunk = fullTextChunks[crate + "/" + key];
+                    if (chunk === undefined) {
+                        loadFullTextChunk(crate, key);
+                    }
+                    if (!chunk) {
+                        complete = false;
+                        continue;
+                    }
+                    var found = {};
+                    for (var word in chunk) {
+                        if (chunk.hasOwnProperty(word) && word.indexOf(words[i]) === 0) {
+                            chunk[word].forEach(function(pos) {
+                                if (matches === null || matches[pos] === true) {
+                                    found[pos] = true;
+                                }
+                            });
+                        }
+                    }
+                    matches = found;
+                }
+                if (!complete) {
+                    continue;
+                }
+                for (var pos in matches) {
+                    if (!matches.hasOwnProperty(pos)) {
+                        continue;
+                    }
+                    var obj = searchIndex[fullTextOffsets[crate] + parseInt(pos, 10)];
+                    var res = buildHrefAndPath(obj);
+                    obj.displayPath = pathSplitter(res[0]);
+                    obj.fullPath = obj.displayPath + obj.name + "|" + obj.ty;
+                    obj.href = res[1];
+                    out.push(obj);
+                    if (out.length >= MAX_RESULTS) {
+                        return out;
+                    }
+                }
+            }
+            return out;
+        }
+
         function getFilterCrates() {
             var elem = document.getElementById("crate-search");
 
@@ -1546,7 +1648,9 @@ if (!DOMTokenList.prototype.remove) {
             }
 
             var filterCrates = getFilterCrates();
-            showResults(execSearch(query, index, filterCrates), filterCrates);
+            var results = execSearch(query, index, filterCrates);
+            results.docs = execFullTextSearch(query.raw, filterCrates);
+            showResults(results, filterCrates);
         }
 
         function buildIndex(rawSearchIndex) {
@@ -1566,6 +1670,9 @@ if (!DOMTokenList.prototype.remove) {
                     desc: rawSearchIndex[crate].doc,
                     type: null,
                 });
+                if (rawSearchIndex[crate].f) {
+                    fullTextOffsets[crate] = searchIndex.length;
+                }
 
                 // an array of [(Number) item type,
                 //              (String) name,
@@ -2390,7 +2497,7 @@ if (!DOMTokenList.prototype.remove) {
 
     // In the search display, allows to switch between tabs.
     function printTab(nb) {
-        if (nb === 0 || nb === 1 || nb === 2) {
+        if (nb >= 0 && nb <= 3) {
             currentTab = nb;
         }
         var nb_copy = nb;
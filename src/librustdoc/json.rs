@@ -0,0 +1,529 @@
+//! Rendering of the documentation as a single JSON file, for tools that need
+//! the documented API without scraping the HTML pages.
+//!
+//! Every item of the crate is listed once in the `index` object, keyed by its
+//! id, and refers to its children and to other items through their ids. The
+//! `paths` object gives the full path and kind of every item that can be
+//! referred to, including the items of other crates, which have no entry in
+//! `index`. Types and generics are described structurally rather than printed,
+//! so that they can be compared without parsing Rust syntax.
+//!
+//! Objects describing a variant of an enumeration, like items, types and
+//! generic bounds, have a `kind` naming the variant and an `inner` value with
+//! its contents.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::middle::stability::StabilityLevel;
+use serialize::json::{Json, ToJson};
+use syntax::ext::base::MacroKind;
+use syntax::print::pprust;
+use syntax::source_map::FileName;
+
+use crate::clean;
+use crate::config::RenderOptions;
+use crate::doctree;
+use crate::html::item_type::ItemType;
+use crate::html::render::{Error, RenderInfo};
+
+/// The version of the format of the generated file. It is increased whenever
+/// the format changes in a way that could break existing consumers.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Writes the documentation of `krate` to `<output>/<crate name>.json`.
+pub fn run(mut krate: clean::Crate,
+           options: RenderOptions,
+           renderinfo: RenderInfo) -> Result<(), Error> {
+    if let Some(ref mut module) = krate.module {
+        module.name = Some(krate.name.clone());
+    }
+
+    let mut renderer = JsonRenderer {
+        index: BTreeMap::new(),
+        paths: BTreeMap::new(),
+    };
+
+    let root = match krate.module {
+        Some(ref module) => renderer.item(module, Some(&[])),
+        None => None,
+    };
+
+    for (&did, &(ref path, kind)) in &renderinfo.external_paths {
+        renderer.paths.entry(id(did)).or_insert_with(|| path_entry(did, path, kind.into()));
+    }
+
+    let external_crates = krate.externs.iter().map(|&(cnum, ref krate)| {
+        (cnum.as_u32().to_string(), object(vec![
+            ("name", krate.name.to_json()),
+        ]))
+    }).collect::<BTreeMap<_, _>>();
+
+    let output = object(vec![
+        ("format_version", FORMAT_VERSION.to_json()),
+        ("name", krate.name.to_json()),
+        ("version", krate.version.to_json()),
+        ("root", root.to_json()),
+        ("index", Json::Object(renderer.index)),
+        ("paths", Json::Object(renderer.paths)),
+        ("external_crates", Json::Object(external_crates)),
+    ]);
+
+    fs::create_dir_all(&options.output).map_err(|e| Error::new(e, &options.output))?;
+    let dst = options.output.join(format!("{}.json", krate.name));
+    let mut file = BufWriter::new(File::create(&dst).map_err(|e| Error::new(e, &dst))?);
+    write!(file, "{}", output).and_then(|_| file.flush()).map_err(|e| Error::new(e, &dst))
+}
+
+struct JsonRenderer {
+    /// Every item of the local crate, keyed by id.
+    index: BTreeMap<String, Json>,
+    /// The path and kind of every item that has one, keyed by id.
+    paths: BTreeMap<String, Json>,
+}
+
+impl JsonRenderer {
+    /// Adds `item` and its children to the index, returning its id. `parent`
+    /// is the path of the item containing it, which is empty for the crate
+    /// root, or `None` if its items can't be named by a path, like those of
+    /// impls. Items removed by the passes are left out.
+    fn item(&mut self, item: &clean::Item, parent: Option<&[String]>) -> Option<String> {
+        if item.is_stripped() {
+            return None;
+        }
+
+        let item_id = id(item.def_id);
+        let kind = item.type_();
+        let path = match (parent, &item.name) {
+            (Some(parent), &Some(ref name)) if kind != ItemType::Impl &&
+                                               kind != ItemType::Import &&
+                                               kind != ItemType::ExternCrate => {
+                let mut path = parent.to_vec();
+                path.push(name.clone());
+                self.paths.insert(item_id.clone(), path_entry(item.def_id, &path, kind));
+                Some(path)
+            }
+            _ => parent.map(|parent| parent.to_vec()),
+        };
+
+        let inner = self.item_inner(&item.inner, path.as_ref().map(|path| &path[..]));
+        let links = item.attrs.links.iter().filter_map(|&(ref text, did, _)| {
+            did.map(|did| (text.clone(), id(did).to_json()))
+        }).collect::<BTreeMap<_, _>>();
+
+        self.index.insert(item_id.clone(), object(vec![
+            ("id", item_id.to_json()),
+            ("crate_id", item.def_id.krate.as_u32().to_json()),
+            ("name", item.name.to_json()),
+            ("span", span(&item.source)),
+            ("visibility", visibility(&item.visibility)),
+            ("docs", item.collapsed_doc_value().to_json()),
+            ("links", Json::Object(links)),
+            ("attrs", item.attrs.other_attrs.iter()
+                                            .map(pprust::attribute_to_string)
+                                            .collect::<Vec<_>>()
+                                            .to_json()),
+            ("deprecation", item.deprecation().map(deprecation).to_json()),
+            ("stability", item.stability.as_ref().map(stability).to_json()),
+            ("kind", kind.css_class().to_json()),
+            ("inner", inner),
+        ]));
+        Some(item_id)
+    }
+
+    fn items(&mut self, items: &[clean::Item], parent: Option<&[String]>) -> Json {
+        items.iter().filter_map(|item| self.item(item, parent)).collect::<Vec<_>>().to_json()
+    }
+
+    fn item_inner(&mut self, inner: &clean::ItemEnum, path: Option<&[String]>) -> Json {
+        use crate::clean::ItemEnum::*;
+
+        match *inner {
+            ExternCrateItem(ref name, ref original) => object(vec![
+                ("name", original.as_ref().unwrap_or(name).to_json()),
+                ("rename", original.as_ref().map(|_| name.to_json()).to_json()),
+            ]),
+            ImportItem(clean::Import::Simple(ref name, ref source)) => object(vec![
+                ("source", path_to_string(&source.path).to_json()),
+                ("name", name.to_json()),
+                ("id", source.did.map(id).to_json()),
+                ("glob", false.to_json()),
+            ]),
+            ImportItem(clean::Import::Glob(ref source)) => object(vec![
+                ("source", path_to_string(&source.path).to_json()),
+                ("name", Json::Null),
+                ("id", source.did.map(id).to_json()),
+                ("glob", true.to_json()),
+            ]),
+            StructItem(clean::Struct { struct_type, ref generics, ref fields, fields_stripped }) |
+            UnionItem(clean::Union { struct_type, ref generics, ref fields, fields_stripped }) => {
+                object(vec![
+                    ("struct_type", struct_type_name(struct_type).to_json()),
+                    ("generics", self.generics(generics)),
+                    ("fields", self.items(fields, path)),
+                    ("fields_stripped", fields_stripped.to_json()),
+                ])
+            }
+            EnumItem(ref e) => object(vec![
+                ("generics", self.generics(&e.generics)),
+                ("variants", self.items(&e.variants.raw, path)),
+                ("variants_stripped", e.variants_stripped.to_json()),
+            ]),
+            VariantItem(ref variant) => match variant.kind {
+                clean::VariantKind::CLike => tagged("plain", Json::Null),
+                clean::VariantKind::Tuple(ref types) => tagged("tuple", self.types(types)),
+                clean::VariantKind::Struct(ref s) => tagged("struct", object(vec![
+                    ("fields", self.items(&s.fields, path)),
+                    ("fields_stripped", s.fields_stripped.to_json()),
+                ])),
+            },
+            FunctionItem(ref f) | ForeignFunctionItem(ref f) => object(vec![
+                ("decl", self.fn_decl(&f.decl)),
+                ("generics", self.generics(&f.generics)),
+                ("header", fn_header(&f.header)),
+            ]),
+            MethodItem(ref m) => object(vec![
+                ("decl", self.fn_decl(&m.decl)),
+                ("generics", self.generics(&m.generics)),
+                ("header", fn_header(&m.header)),
+                ("has_body", true.to_json()),
+            ]),
+            TyMethodItem(ref m) => object(vec![
+                ("decl", self.fn_decl(&m.decl)),
+                ("generics", self.generics(&m.generics)),
+                ("header", fn_header(&m.header)),
+                ("has_body", false.to_json()),
+            ]),
+            ModuleItem(ref m) => object(vec![
+                ("is_crate", m.is_crate.to_json()),
+                ("items", self.items(&m.items, path)),
+            ]),
+            TypedefItem(ref t, _) => object(vec![
+                ("type", self.type_(&t.type_)),
+                ("generics", self.generics(&t.generics)),
+            ]),
+            ExistentialItem(ref e, _) => object(vec![
+                ("bounds", self.bounds(&e.bounds)),
+                ("generics", self.generics(&e.generics)),
+            ]),
+            StaticItem(ref s) | ForeignStaticItem(ref s) => object(vec![
+                ("type", self.type_(&s.type_)),
+                ("mutable", (s.mutability == clean::Mutable).to_json()),
+                ("expr", s.expr.to_json()),
+            ]),
+            ConstantItem(ref c) => self.constant(c),
+            TraitItem(ref t) => object(vec![
+                ("is_auto", t.is_auto.to_json()),
+                ("is_unsafe", (t.unsafety == hir::Unsafety::Unsafe).to_json()),
+                ("items", self.items(&t.items, path)),
+                ("generics", self.generics(&t.generics)),
+                ("bounds", self.bounds(&t.bounds)),
+            ]),
+            TraitAliasItem(ref t) => object(vec![
+                ("generics", self.generics(&t.generics)),
+                ("bounds", self.bounds(&t.bounds)),
+            ]),
+            ImplItem(ref i) => {
+                let mut provided = i.provided_trait_methods.iter().cloned().collect::<Vec<_>>();
+                provided.sort();
+                object(vec![
+                    ("is_unsafe", (i.unsafety == hir::Unsafety::Unsafe).to_json()),
+                    ("generics", self.generics(&i.generics)),
+                    ("provided_trait_methods", provided.to_json()),
+                    ("trait", i.trait_.as_ref().map(|t| self.type_(t)).to_json()),
+                    ("for", self.type_(&i.for_)),
+                    // Items of impls have no path of their own.
+                    ("items", self.items(&i.items, None)),
+                    ("negative", (i.polarity == Some(clean::ImplPolarity::Negative)).to_json()),
+                    ("synthetic", i.synthetic.to_json()),
+                    ("blanket_impl", i.blanket_impl.as_ref().map(|t| self.type_(t)).to_json()),
+                ])
+            }
+            StructFieldItem(ref ty) => self.type_(ty),
+            ForeignTypeItem => Json::Null,
+            MacroItem(ref m) => object(vec![
+                ("source", m.source.to_json()),
+            ]),
+            ProcMacroItem(ref m) => object(vec![
+                ("kind", match m.kind {
+                    MacroKind::Bang | MacroKind::ProcMacroStub => "bang",
+                    MacroKind::Attr => "attr",
+                    MacroKind::Derive => "derive",
+                }.to_json()),
+                ("helpers", m.helpers.to_json()),
+            ]),
+            PrimitiveItem(p) => p.as_str().to_json(),
+            AssociatedConstItem(ref ty, ref default) => object(vec![
+                ("type", self.type_(ty)),
+                ("default", default.to_json()),
+            ]),
+            AssociatedTypeItem(ref bounds, ref default) => object(vec![
+                ("bounds", self.bounds(bounds)),
+                ("default", default.as_ref().map(|t| self.type_(t)).to_json()),
+            ]),
+            KeywordItem(ref keyword) => keyword.to_json(),
+            StrippedItem(..) => unreachable!("stripped items are not rendered"),
+        }
+    }
+
+    fn type_(&mut self, ty: &clean::Type) -> Json {
+        use crate::clean::Type::*;
+
+        match *ty {
+            ResolvedPath { ref path, ref param_names, did, is_generic } => {
+                let args = path.segments.last().map(|segment| self.generic_args(&segment.args));
+                tagged("resolved_path", object(vec![
+                    ("name", path_to_string(path).to_json()),
+                    ("id", id(did).to_json()),
+                    ("args", args.to_json()),
+                    ("param_names", param_names.as_ref().map(|b| self.bounds(b)).to_json()),
+                    ("is_generic", is_generic.to_json()),
+                ]))
+            }
+            Generic(ref name) => tagged("generic", name.to_json()),
+            Primitive(p) => tagged("primitive", p.as_str().to_json()),
+            BareFunction(ref f) => tagged("function_pointer", object(vec![
+                ("decl", self.fn_decl(&f.decl)),
+                ("generic_params", self.generic_params(&f.generic_params)),
+                ("is_unsafe", (f.unsafety == hir::Unsafety::Unsafe).to_json()),
+                ("abi", f.abi.name().to_json()),
+            ])),
+            Tuple(ref types) => tagged("tuple", self.types(types)),
+            Slice(ref ty) => tagged("slice", self.type_(ty)),
+            Array(ref ty, ref len) => tagged("array", object(vec![
+                ("type", self.type_(ty)),
+                ("len", len.to_json()),
+            ])),
+            Never => tagged("never", Json::Null),
+            CVarArgs => tagged("c_variadic", Json::Null),
+            Unique(ref ty) => tagged("unique", self.type_(ty)),
+            RawPointer(mutability, ref ty) => tagged("raw_pointer", object(vec![
+                ("mutable", (mutability == clean::Mutable).to_json()),
+                ("type", self.type_(ty)),
+            ])),
+            BorrowedRef { ref lifetime, mutability, ref type_ } => {
+                tagged("borrowed_ref", object(vec![
+                    ("lifetime", lifetime.as_ref().map(|l| l.get_ref().to_json()).to_json()),
+                    ("mutable", (mutability == clean::Mutable).to_json()),
+                    ("type", self.type_(type_)),
+                ]))
+            }
+            QPath { ref name, ref self_type, ref trait_ } => tagged("qualified_path", object(vec![
+                ("name", name.to_json()),
+                ("self_type", self.type_(self_type)),
+                ("trait", self.type_(trait_)),
+            ])),
+            Infer => tagged("infer", Json::Null),
+            ImplTrait(ref bounds) => tagged("impl_trait", self.bounds(bounds)),
+        }
+    }
+
+    fn types(&mut self, types: &[clean::Type]) -> Json {
+        Json::Array(types.iter().map(|ty| self.type_(ty)).collect())
+    }
+
+    fn constant(&mut self, constant: &clean::Constant) -> Json {
+        object(vec![
+            ("type", self.type_(&constant.type_)),
+            ("expr", constant.expr.to_json()),
+        ])
+    }
+
+    fn generic_args(&mut self, args: &clean::GenericArgs) -> Json {
+        match *args {
+            clean::GenericArgs::AngleBracketed { ref args, ref bindings } => {
+                let args = args.iter().map(|arg| match *arg {
+                    clean::GenericArg::Lifetime(ref l) => tagged("lifetime", l.get_ref().to_json()),
+                    clean::GenericArg::Type(ref ty) => tagged("type", self.type_(ty)),
+                    clean::GenericArg::Const(ref c) => tagged("const", self.constant(c)),
+                }).collect::<Vec<_>>();
+                let bindings = bindings.iter().map(|binding| object(vec![
+                    ("name", binding.name.to_json()),
+                    ("type", self.type_(&binding.ty)),
+                ])).collect::<Vec<_>>();
+                tagged("angle_bracketed", object(vec![
+                    ("args", Json::Array(args)),
+                    ("bindings", Json::Array(bindings)),
+                ]))
+            }
+            clean::GenericArgs::Parenthesized { ref inputs, ref output } => {
+                tagged("parenthesized", object(vec![
+                    ("inputs", self.types(inputs)),
+                    ("output", output.as_ref().map(|t| self.type_(t)).to_json()),
+                ]))
+            }
+        }
+    }
+
+    fn bounds(&mut self, bounds: &[clean::GenericBound]) -> Json {
+        Json::Array(bounds.iter().map(|bound| match *bound {
+            clean::GenericBound::TraitBound(ref poly, modifier) => {
+                tagged("trait_bound", object(vec![
+                    ("trait", self.type_(&poly.trait_)),
+                    ("generic_params", self.generic_params(&poly.generic_params)),
+                    ("modifier", match modifier {
+                        hir::TraitBoundModifier::None => "none",
+                        hir::TraitBoundModifier::Maybe => "maybe",
+                    }.to_json()),
+                ]))
+            }
+            clean::GenericBound::Outlives(ref l) => tagged("outlives", l.get_ref().to_json()),
+        }).collect())
+    }
+
+    fn generic_params(&mut self, params: &[clean::GenericParamDef]) -> Json {
+        Json::Array(params.iter().map(|param| {
+            let kind = match param.kind {
+                clean::GenericParamDefKind::Lifetime => tagged("lifetime", Json::Null),
+                clean::GenericParamDefKind::Type { ref bounds, ref default, ref synthetic, .. } => {
+                    tagged("type", object(vec![
+                        ("bounds", self.bounds(bounds)),
+                        ("default", default.as_ref().map(|t| self.type_(t)).to_json()),
+                        ("synthetic", synthetic.is_some().to_json()),
+                    ]))
+                }
+                clean::GenericParamDefKind::Const { ref ty, .. } => tagged("const", self.type_(ty)),
+            };
+            object(vec![
+                ("name", param.name.to_json()),
+                ("kind", kind),
+            ])
+        }).collect())
+    }
+
+    fn generics(&mut self, generics: &clean::Generics) -> Json {
+        let predicates = generics.where_predicates.iter().map(|predicate| match *predicate {
+            clean::WherePredicate::BoundPredicate { ref ty, ref bounds } => {
+                tagged("bound_predicate", object(vec![
+                    ("type", self.type_(ty)),
+                    ("bounds", self.bounds(bounds)),
+                ]))
+            }
+            clean::WherePredicate::RegionPredicate { ref lifetime, ref bounds } => {
+                tagged("region_predicate", object(vec![
+                    ("lifetime", lifetime.get_ref().to_json()),
+                    ("bounds", self.bounds(bounds)),
+                ]))
+            }
+            clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => {
+                tagged("eq_predicate", object(vec![
+                    ("lhs", self.type_(lhs)),
+                    ("rhs", self.type_(rhs)),
+                ]))
+            }
+        }).collect::<Vec<_>>();
+        object(vec![
+            ("params", self.generic_params(&generics.params)),
+            ("where_predicates", Json::Array(predicates)),
+        ])
+    }
+
+    fn fn_decl(&mut self, decl: &clean::FnDecl) -> Json {
+        let inputs = decl.inputs.values.iter().map(|arg| {
+            Json::Array(vec![arg.name.to_json(), self.type_(&arg.type_)])
+        }).collect::<Vec<_>>();
+        object(vec![
+            ("inputs", Json::Array(inputs)),
+            ("output", match decl.output {
+                clean::Return(ref ty) => self.type_(ty),
+                clean::DefaultReturn => Json::Null,
+            }),
+        ])
+    }
+}
+
+/// The id of an item, unique within the documentation of a crate.
+fn id(did: DefId) -> String {
+    format!("{}:{}", did.krate.as_u32(), did.index.as_array_index())
+}
+
+crate fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn tagged(kind: &str, inner: Json) -> Json {
+    object(vec![
+        ("kind", kind.to_json()),
+        ("inner", inner),
+    ])
+}
+
+fn path_entry(did: DefId, path: &[String], kind: ItemType) -> Json {
+    object(vec![
+        ("crate_id", did.krate.as_u32().to_json()),
+        ("path", path.to_json()),
+        ("kind", kind.css_class().to_json()),
+    ])
+}
+
+fn path_to_string(path: &clean::Path) -> String {
+    let names = path.segments.iter().map(|s| &s.name[..]).collect::<Vec<_>>().join("::");
+    if path.global { format!("::{}", names) } else { names }
+}
+
+crate fn span(span: &clean::Span) -> Json {
+    if span.loline == 0 {
+        return Json::Null;
+    }
+    let filename = match span.filename {
+        FileName::Real(ref path) => path.display().to_string(),
+        ref other => other.to_string(),
+    };
+    object(vec![
+        ("filename", filename.to_json()),
+        ("begin", (span.loline, span.locol).to_json()),
+        ("end", (span.hiline, span.hicol).to_json()),
+    ])
+}
+
+fn visibility(visibility: &Option<clean::Visibility>) -> Json {
+    match *visibility {
+        Some(clean::Public) => "public".to_json(),
+        Some(clean::Inherited) | None => "default".to_json(),
+        Some(clean::Visibility::Crate) => "crate".to_json(),
+        Some(clean::Visibility::Restricted(did, ref path)) => tagged("restricted", object(vec![
+            ("parent", id(did).to_json()),
+            ("path", path_to_string(path).to_json()),
+        ])),
+    }
+}
+
+fn deprecation(deprecation: &clean::Deprecation) -> Json {
+    object(vec![
+        ("since", deprecation.since.to_json()),
+        ("note", deprecation.note.to_json()),
+    ])
+}
+
+fn stability(stability: &clean::Stability) -> Json {
+    object(vec![
+        ("level", match stability.level {
+            StabilityLevel::Stable => "stable",
+            StabilityLevel::Unstable => "unstable",
+        }.to_json()),
+        ("feature", stability.feature.to_json()),
+        ("since", Some(&stability.since).filter(|s| !s.is_empty()).map(|s| s.to_json()).to_json()),
+        ("reason", stability.unstable_reason.to_json()),
+        ("issue", stability.issue.to_json()),
+    ])
+}
+
+fn fn_header(header: &hir::FnHeader) -> Json {
+    object(vec![
+        ("is_const", (header.constness == hir::Constness::Const).to_json()),
+        ("is_unsafe", (header.unsafety == hir::Unsafety::Unsafe).to_json()),
+        ("is_async", (header.asyncness == hir::IsAsync::Async).to_json()),
+        ("abi", header.abi.name().to_json()),
+    ])
+}
+
+fn struct_type_name(struct_type: doctree::StructType) -> &'static str {
+    match struct_type {
+        doctree::Plain => "plain",
+        doctree::Tuple => "tuple",
+        doctree::Unit => "unit",
+    }
+}
@@ -55,6 +55,7 @@ use rustc::session::config::{ErrorOutputType, RustcOptGroup};
 #[macro_use]
 mod externalfiles;
 
+mod api_diff;
 mod clean;
 mod config;
 mod core;
@@ -71,8 +72,10 @@ pub mod html {
     crate mod static_files;
     crate mod toc;
 }
+mod json;
 mod markdown;
 mod passes;
+mod scrape_examples;
 mod visit_ast;
 mod visit_lib;
 mod test;
@@ -132,7 +135,7 @@ fn opts() -> Vec<RustcOptGroup> {
                      "[rust]")
         }),
         stable("w", |o| {
-            o.optopt("w", "output-format", "the output type to write", "[html]")
+            o.optopt("w", "output-format", "the output type to write", "[html|json]")
         }),
         stable("o", |o| o.optopt("o", "output", "where to place the output", "PATH")),
         stable("crate-name", |o| {
@@ -346,16 +349,61 @@ fn opts() -> Vec<RustcOptGroup> {
                        "Directory to persist doctest executables into",
                        "PATH")
         }),
+        unstable("merge-doctests", |o| {
+            o.optflag("",
+                      "merge-doctests",
+                      "compile the doctests without crate attributes into one binary per edition")
+        }),
         unstable("generate-redirect-pages", |o| {
             o.optflag("",
                       "generate-redirect-pages",
                       "Generate extra pages to support legacy URLs and tool links")
         }),
+        unstable("full-text-search", |o| {
+            o.optflag("",
+                      "full-text-search",
+                      "index the complete documentation of the items for the search, instead of \
+                       only their names and summaries")
+        }),
         unstable("show-coverage", |o| {
             o.optflag("",
                       "show-coverage",
                       "calculate percentage of public items with documentation")
         }),
+        unstable("coverage-threshold", |o| {
+            o.optopt("",
+                     "coverage-threshold",
+                     "with `--show-coverage`, fail if the percentage of documented items is \
+                      below this one",
+                     "PERCENT")
+        }),
+        unstable("api-baseline", |o| {
+            o.optopt("",
+                     "api-baseline",
+                     "compare the public API with the one of a previous version of the crate, \
+                      given by its crate root, and report breaking changes",
+                     "PATH")
+        }),
+        unstable("scrape-examples-output-path", |o| {
+            o.optopt("",
+                     "scrape-examples-output-path",
+                     "instead of generating docs, write the calls to the functions of the crates \
+                      given to `--scrape-examples-target-crate` to this file",
+                     "PATH")
+        }),
+        unstable("scrape-examples-target-crate", |o| {
+            o.optmulti("",
+                       "scrape-examples-target-crate",
+                       "crate whose functions are looked for by `--scrape-examples-output-path`",
+                       "CRATE")
+        }),
+        unstable("with-examples", |o| {
+            o.optmulti("",
+                       "with-examples",
+                       "calls file written by `--scrape-examples-output-path`, whose calls are \
+                        shown on the pages of the functions they call",
+                       "PATH")
+        }),
     ]
 }
 
@@ -395,16 +443,21 @@ fn main_args(args: &[String]) -> i32 {
         (false, false) => {}
     }
 
+    if let Some(baseline) = options.api_baseline.clone() {
+        return api_diff::run(options, baseline);
+    }
+
     // need to move these items separately because we lose them by the time the closure is called,
     // but we can't crates the Handler ahead of time because it's not Send
     let diag_opts = (options.error_format,
                      options.debugging_options.treat_err_as_bug,
                      options.debugging_options.ui_testing);
     let show_coverage = options.show_coverage;
+    let scrape_examples = options.scrape_examples.is_some();
     rust_input(options, move |out| {
-        if show_coverage {
-            // if we ran coverage, bail early, we don't need to also generate docs at this point
-            // (also we didn't load in any of the useful passes)
+        if show_coverage || scrape_examples {
+            // if we ran coverage or scraped examples, bail early, we don't need to also generate
+            // docs at this point (also we didn't load in any of the useful passes)
             return rustc_driver::EXIT_SUCCESS;
         }
 
@@ -412,13 +465,17 @@ fn main_args(args: &[String]) -> i32 {
         info!("going to format");
         let (error_format, treat_err_as_bug, ui_testing) = diag_opts;
         let diag = core::new_handler(error_format, None, treat_err_as_bug, ui_testing);
-        match html::render::run(
-            krate,
-            renderopts,
-            passes.into_iter().collect(),
-            renderinfo,
-            &diag,
-        ) {
+        let result = match renderopts.output_format {
+            config::OutputFormat::Html => html::render::run(
+                krate,
+                renderopts,
+                passes.into_iter().collect(),
+                renderinfo,
+                &diag,
+            ),
+            config::OutputFormat::Json => json::run(krate, renderopts, renderinfo),
+        };
+        match result {
             Ok(_) => rustc_driver::EXIT_SUCCESS,
             Err(e) => {
                 diag.struct_err(&format!("couldn't generate documentation: {}", e.error))
@@ -140,7 +140,8 @@ pub fn test(mut options: Options, diag: &errors::Handler) -> i32 {
                                        options.libs, options.codegen_options, options.externs,
                                        true, opts, options.maybe_sysroot, None,
                                        Some(options.input),
-                                       options.linker, options.edition, options.persist_doctests);
+                                       options.linker, options.edition, options.persist_doctests,
+                                       options.merge_doctests);
     collector.set_position(DUMMY_SP);
     let codes = ErrorCodes::from(UnstableFeatures::from_environment().is_nightly_build());
 
@@ -1,8 +1,12 @@
 use crate::clean;
+use crate::config::OutputFormat;
 use crate::core::DocContext;
 use crate::fold::{self, DocFolder};
-use crate::passes::Pass;
+use crate::html::item_type::ItemType;
+use crate::json;
+use crate::passes::{count_doctests, Pass};
 
+use serialize::json::{Json, ToJson};
 use syntax::attr;
 use syntax_pos::FileName;
 use syntax::symbol::sym;
@@ -16,11 +20,24 @@ pub const CALCULATE_DOC_COVERAGE: Pass = Pass {
     description: "counts the number of items with and without documentation",
 };
 
-fn calculate_doc_coverage(krate: clean::Crate, _: &DocContext<'_>) -> clean::Crate {
+fn calculate_doc_coverage(krate: clean::Crate, cx: &DocContext<'_>) -> clean::Crate {
     let mut calc = CoverageCalculator::default();
+    calc.path.push(krate.name.clone());
     let krate = calc.fold_crate(krate);
 
-    calc.print_results();
+    match cx.output_format {
+        OutputFormat::Html => calc.print_results(),
+        OutputFormat::Json => println!("{}", calc.to_json()),
+    }
+
+    if let Some(threshold) = cx.coverage_threshold {
+        // a crate without any items to document can't lack documentation
+        let percentage = calc.total().percentage().unwrap_or(100.0);
+        if percentage < threshold {
+            cx.sess().err(&format!("{:.1}% of the items are documented, which is below the \
+                                    threshold of {}%", percentage, threshold));
+        }
+    }
 
     krate
 }
@@ -29,15 +46,19 @@ fn calculate_doc_coverage(krate: clean::Crate, _: &DocContext<'_>) -> clean::Cra
 struct ItemCount {
     total: u64,
     with_docs: u64,
+    with_examples: u64,
 }
 
 impl ItemCount {
-    fn count_item(&mut self, has_docs: bool) {
+    fn count_item(&mut self, has_docs: bool, has_examples: bool) {
         self.total += 1;
 
         if has_docs {
             self.with_docs += 1;
         }
+        if has_examples {
+            self.with_examples += 1;
+        }
     }
 
     fn percentage(&self) -> Option<f64> {
@@ -56,6 +77,7 @@ impl ops::Sub for ItemCount {
         ItemCount {
             total: self.total - rhs.total,
             with_docs: self.with_docs - rhs.with_docs,
+            with_examples: self.with_examples - rhs.with_examples,
         }
     }
 }
@@ -64,15 +86,70 @@ impl ops::AddAssign for ItemCount {
     fn add_assign(&mut self, rhs: Self) {
         self.total += rhs.total;
         self.with_docs += rhs.with_docs;
+        self.with_examples += rhs.with_examples;
+    }
+}
+
+impl ToJson for ItemCount {
+    fn to_json(&self) -> Json {
+        json::object(vec![
+            ("total", self.total.to_json()),
+            ("with_docs", self.with_docs.to_json()),
+            ("with_examples", self.with_examples.to_json()),
+        ])
+    }
+}
+
+/// An item counted as lacking documentation or code examples.
+struct UncoveredItem {
+    path: String,
+    kind: ItemType,
+    source: clean::Span,
+}
+
+impl ToJson for UncoveredItem {
+    fn to_json(&self) -> Json {
+        json::object(vec![
+            ("path", self.path.to_json()),
+            ("kind", self.kind.to_string().to_json()),
+            ("span", json::span(&self.source)),
+        ])
     }
 }
 
 #[derive(Default)]
 struct CoverageCalculator {
     items: BTreeMap<FileName, ItemCount>,
+    /// The path of the parent of the current item, starting with the crate name.
+    path: Vec<String>,
+    undocumented: Vec<UncoveredItem>,
+    /// Documented items without code examples, as reported by `missing_doc_code_examples`.
+    missing_examples: Vec<UncoveredItem>,
 }
 
 impl CoverageCalculator {
+    fn total(&self) -> ItemCount {
+        let mut total = ItemCount::default();
+        for &count in self.items.values() {
+            total += count;
+        }
+        total
+    }
+
+    fn to_json(&self) -> Json {
+        let files = self.items.iter()
+            .filter(|(_, count)| count.total > 0)
+            .map(|(file, count)| (file.to_string(), count.to_json()))
+            .collect();
+        json::object(vec![
+            ("files", Json::Object(files)),
+            ("total", self.total().to_json()),
+            ("undocumented", Json::Array(self.undocumented.iter().map(|i| i.to_json()).collect())),
+            ("missing_examples",
+             Json::Array(self.missing_examples.iter().map(|i| i.to_json()).collect())),
+        ])
+    }
+
     fn print_results(&self) {
         let mut total = ItemCount::default();
 
@@ -156,12 +233,50 @@ impl fold::DocFolder for CoverageCalculator {
             }
             _ => {
                 debug!("counting {} {:?} in {}", i.type_(), i.name, i.source.filename);
+                let has_examples = i.attrs.collapsed_doc_value()
+                                          .map_or(false, |dox| count_doctests(&dox) > 0);
                 self.items.entry(i.source.filename.clone())
                           .or_default()
-                          .count_item(has_docs);
+                          .count_item(has_docs, has_examples);
+
+                if !has_docs || !has_examples {
+                    let mut path = self.path.join("::");
+                    if let Some(ref name) = i.name {
+                        if !name.is_empty() {
+                            path.push_str("::");
+                            path.push_str(name);
+                        }
+                    }
+                    let item = UncoveredItem {
+                        path,
+                        kind: i.type_(),
+                        source: i.source.clone(),
+                    };
+                    if has_docs {
+                        self.missing_examples.push(item);
+                    } else {
+                        self.undocumented.push(item);
+                    }
+                }
             }
         }
 
-        self.fold_item_recur(i)
+        // the items inside impls are named after the implementing type, the others after their
+        // parent item
+        let segment = match i.inner {
+            clean::ImplItem(ref impl_) => Some(format!("{:#}", impl_.for_)),
+            clean::ModuleItem(..) | clean::StructItem(..) | clean::UnionItem(..) |
+            clean::EnumItem(..) | clean::TraitItem(..) => i.name.clone(),
+            _ => None,
+        };
+        match segment {
+            Some(segment) if !segment.is_empty() => {
+                self.path.push(segment);
+                let i = self.fold_item_recur(i);
+                self.path.pop();
+                i
+            }
+            _ => self.fold_item_recur(i),
+        }
     }
 }
@@ -319,23 +319,9 @@ pub fn look_for_tests<'tcx>(
         return;
     }
 
-    struct Tests {
-        found_tests: usize,
-    }
-
-    impl crate::test::Tester for Tests {
-        fn add_test(&mut self, _: String, _: LangString, _: usize) {
-            self.found_tests += 1;
-        }
-    }
-
-    let mut tests = Tests {
-        found_tests: 0,
-    };
-
-    find_testable_code(&dox, &mut tests, ErrorCodes::No);
+    let found_tests = count_doctests(dox);
 
-    if check_missing_code == true && tests.found_tests == 0 {
+    if check_missing_code == true && found_tests == 0 {
         let mut diag = cx.tcx.struct_span_lint_hir(
             lint::builtin::MISSING_DOC_CODE_EXAMPLES,
             hir::CRATE_HIR_ID,
@@ -343,7 +329,7 @@ pub fn look_for_tests<'tcx>(
             "Missing code example in this documentation");
         diag.emit();
     } else if check_missing_code == false &&
-              tests.found_tests > 0 &&
+              found_tests > 0 &&
               !cx.renderinfo.borrow().access_levels.is_doc_reachable(item.def_id) {
         let mut diag = cx.tcx.struct_span_lint_hir(
             lint::builtin::PRIVATE_DOC_TESTS,
@@ -354,6 +340,27 @@ pub fn look_for_tests<'tcx>(
     }
 }
 
+/// Returns the number of code examples in `dox` that would be run as doctests.
+crate fn count_doctests(dox: &str) -> usize {
+    struct Tests {
+        found_tests: usize,
+    }
+
+    impl crate::test::Tester for Tests {
+        fn add_test(&mut self, _: String, _: LangString, _: usize) {
+            self.found_tests += 1;
+        }
+    }
+
+    let mut tests = Tests {
+        found_tests: 0,
+    };
+
+    find_testable_code(dox, &mut tests, ErrorCodes::No);
+
+    tests.found_tests
+}
+
 /// Returns a span encompassing all the given attributes.
 crate fn span_of_attrs(attrs: &clean::Attributes) -> Span {
     if attrs.doc_strings.is_empty() {
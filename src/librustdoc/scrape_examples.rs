@@ -0,0 +1,221 @@
+//! Collecting calls to the functions of a crate from its examples and tests, to show them on the
+//! pages of the functions.
+//!
+//! Scraping happens in two steps. First, each example or test crate is given to rustdoc with
+//! `--scrape-examples-output-path` and `--scrape-examples-target-crate`: instead of generating
+//! documentation, rustdoc walks the bodies of the crate and writes every call to a function of
+//! the target crates, along with the lines of code around it, to a calls file. The documentation
+//! of the target crate is then generated with each calls file passed to `--with-examples`.
+//!
+//! Functions are identified by their `DefPathHash`, which is the same in the crate defining them
+//! and in the crates using them.
+
+use rustc::hir::{self, intravisit::{self, FnKind, NestedVisitorMap, Visitor}};
+use rustc::hir::def_id::DefId;
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::{FxHashMap, FxHashSet};
+use serialize::json;
+use syntax_pos::{Span, SyntaxContext};
+
+use std::fs;
+use std::path::PathBuf;
+
+/// The number of lines shown before and after a call, if the function containing it has them.
+const CONTEXT_LINES: usize = 3;
+
+/// Options for scraping the calls from an example or test crate.
+#[derive(Clone, Debug)]
+pub struct ScrapeExamplesOptions {
+    /// Where to write the calls file.
+    pub output_path: PathBuf,
+    /// The names of the crates whose functions are looked for.
+    pub target_crates: Vec<String>,
+}
+
+/// A call to a function, with the code around it.
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub struct CallExample {
+    /// The file containing the call, as given to the compiler.
+    pub file: String,
+    /// The line of the call, starting at 1.
+    pub line: usize,
+    /// The lines around the call, stripped of their common indentation.
+    pub code: String,
+}
+
+#[derive(RustcEncodable, RustcDecodable)]
+struct Call {
+    /// The `DefPathHash` of the called function, in hexadecimal.
+    function: String,
+    example: CallExample,
+}
+
+#[derive(RustcEncodable, RustcDecodable)]
+struct CallsFile {
+    calls: Vec<Call>,
+}
+
+struct CallFinder<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    tables: &'a ty::TypeckTables<'tcx>,
+    target_crates: &'a [String],
+    /// The span of the function whose body is being walked.
+    enclosing_fn: Option<Span>,
+    /// The functions already called from each function, only the first call is kept since the
+    /// examples of the others would mostly show the same code.
+    seen: FxHashSet<(DefId, Option<Span>)>,
+    calls: Vec<Call>,
+}
+
+impl<'a, 'tcx> CallFinder<'a, 'tcx> {
+    fn record(&mut self, def_id: DefId, span: Span) {
+        if def_id.is_local() || span.ctxt() != SyntaxContext::empty() {
+            return;
+        }
+        let krate = self.tcx.crate_name(def_id.krate);
+        if !self.target_crates.iter().any(|name| *name == *krate.as_str()) {
+            return;
+        }
+        if !self.seen.insert((def_id, self.enclosing_fn)) {
+            return;
+        }
+
+        let sm = self.tcx.sess.source_map();
+        let lo = sm.lookup_char_pos(span.lo());
+        let hi = sm.lookup_char_pos(span.hi());
+        let (mut first, mut last) = (lo.line, hi.line);
+        if let Some(enclosing) = self.enclosing_fn {
+            let fn_lo = sm.lookup_char_pos(enclosing.lo());
+            let fn_hi = sm.lookup_char_pos(enclosing.hi());
+            if fn_lo.file.name == lo.file.name {
+                first = fn_lo.line.max(lo.line.saturating_sub(CONTEXT_LINES));
+                last = fn_hi.line.min(hi.line + CONTEXT_LINES);
+            }
+        }
+        let lines = (first..=last).filter_map(|line| lo.file.get_line(line - 1))
+                                  .collect::<Vec<_>>();
+        let indent = lines.iter()
+                          .filter(|line| !line.trim().is_empty())
+                          .map(|line| line.len() - line.trim_start().len())
+                          .min()
+                          .unwrap_or(0);
+        let code = lines.iter()
+                        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+
+        self.calls.push(Call {
+            function: self.tcx.def_path_hash(def_id).0.to_hex(),
+            example: CallExample {
+                file: lo.file.name.to_string(),
+                line: lo.line,
+                code,
+            },
+        });
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for CallFinder<'a, 'tcx> {
+    fn nested_visit_map<'this>(&'this mut self) -> NestedVisitorMap<'this, 'tcx> {
+        NestedVisitorMap::All(&self.tcx.hir())
+    }
+
+    fn visit_nested_body(&mut self, body: hir::BodyId) {
+        let old_tables = self.tables;
+        self.tables = self.tcx.body_tables(body);
+        let body = self.tcx.hir().body(body);
+        self.visit_body(body);
+        self.tables = old_tables;
+    }
+
+    fn visit_fn(&mut self, kind: FnKind<'tcx>, decl: &'tcx hir::FnDecl, body: hir::BodyId,
+                span: Span, id: hir::HirId) {
+        let old_enclosing_fn = self.enclosing_fn;
+        match kind {
+            // Closures are shown along with the function containing them.
+            FnKind::Closure(_) => {}
+            _ => self.enclosing_fn = Some(span),
+        }
+        intravisit::walk_fn(self, kind, decl, body, span, id);
+        self.enclosing_fn = old_enclosing_fn;
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr) {
+        let callee = match expr.node {
+            hir::ExprKind::Call(ref callee, _) => {
+                match self.tables.node_type_opt(callee.hir_id).map(|ty| &ty.sty) {
+                    Some(&ty::FnDef(def_id, _)) => Some(def_id),
+                    _ => None,
+                }
+            }
+            hir::ExprKind::MethodCall(..) => self.tables.type_dependent_def_id(expr.hir_id),
+            _ => None,
+        };
+        if let Some(def_id) = callee {
+            self.record(def_id, expr.span);
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
+
+/// Writes the calls to the functions of the target crates made by the local crate to the calls
+/// file.
+pub fn run(tcx: TyCtxt<'_, '_, '_>, options: &ScrapeExamplesOptions) {
+    let mut finder = CallFinder {
+        tcx,
+        tables: &ty::TypeckTables::empty(None),
+        target_crates: &options.target_crates,
+        enclosing_fn: None,
+        seen: Default::default(),
+        calls: Vec::new(),
+    };
+    intravisit::walk_crate(&mut finder, tcx.hir().krate());
+
+    let calls = CallsFile { calls: finder.calls };
+    let result = json::encode(&calls)
+        .map_err(|e| e.to_string())
+        .and_then(|calls| fs::write(&options.output_path, calls).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tcx.sess.err(&format!("failed to write the calls file `{}`: {}",
+                              options.output_path.display(), e));
+    }
+}
+
+/// Reads the calls files passed to `--with-examples`, returning the calls to each function of the
+/// local crate.
+pub fn load(tcx: TyCtxt<'_, '_, '_>, paths: &[PathBuf]) -> FxHashMap<DefId, Vec<CallExample>> {
+    let mut by_hash = FxHashMap::<String, Vec<CallExample>>::default();
+    for path in paths {
+        let calls = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|calls| json::decode::<CallsFile>(&calls).map_err(|e| e.to_string()));
+        match calls {
+            Ok(calls) => {
+                for call in calls.calls {
+                    by_hash.entry(call.function).or_default().push(call.example);
+                }
+            }
+            Err(e) => {
+                tcx.sess.err(&format!("failed to read the calls file `{}`: {}",
+                                      path.display(), e));
+            }
+        }
+    }
+
+    let mut examples = FxHashMap::default();
+    if by_hash.is_empty() {
+        return examples;
+    }
+    let krate = tcx.hir().krate();
+    let owners = krate.items.keys().cloned()
+        .chain(krate.trait_items.keys().map(|id| id.hir_id))
+        .chain(krate.impl_items.keys().map(|id| id.hir_id));
+    for hir_id in owners {
+        let def_id = tcx.hir().local_def_id_from_hir_id(hir_id);
+        if let Some(mut calls) = by_hash.remove(&tcx.def_path_hash(def_id).0.to_hex()) {
+            calls.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+            examples.insert(def_id, calls);
+        }
+    }
+    examples
+}
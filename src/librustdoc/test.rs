@@ -15,7 +15,7 @@ use std::env;
 use std::io::prelude::*;
 use std::io;
 use std::panic::{self, AssertUnwindSafe};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str;
 use std::sync::{Arc, Mutex};
@@ -95,6 +95,7 @@ pub fn run(options: Options) -> i32 {
             options.linker,
             options.edition,
             options.persist_doctests,
+            options.merge_doctests,
         );
 
         let mut global_ctxt = compiler.global_ctxt()?.take();
@@ -159,6 +160,20 @@ fn scrape_test_config(krate: &::rustc::hir::Crate) -> TestOptions {
     opts
 }
 
+enum DirState {
+    Temp(tempfile::TempDir),
+    Perm(PathBuf),
+}
+
+impl DirState {
+    fn path(&self) -> &std::path::Path {
+        match self {
+            DirState::Temp(t) => t.path(),
+            DirState::Perm(p) => p.as_path(),
+        }
+    }
+}
+
 fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
             cfgs: Vec<String>, libs: Vec<SearchPath>,
             cg: CodegenOptions, externs: Externs,
@@ -241,20 +256,6 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
     let old = io::set_panic(Some(box Sink(data.clone())));
     let _bomb = Bomb(data.clone(), Some(old.unwrap_or(box io::stdout())));
 
-    enum DirState {
-        Temp(tempfile::TempDir),
-        Perm(PathBuf),
-    }
-
-    impl DirState {
-        fn path(&self) -> &std::path::Path {
-            match self {
-                DirState::Temp(t) => t.path(),
-                DirState::Perm(p) => p.as_path(),
-            }
-        }
-    }
-
     let outdir = if let Some(mut path) = persist_doctests {
         path.push(format!("{}_{}",
             filename
@@ -327,8 +328,12 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
     if no_run { return }
 
     // Run the code!
-    let mut cmd = Command::new(output_file);
+    run_test_binary(Command::new(output_file), should_panic, &|output| output.to_string());
+}
 
+/// Runs the executable of a doctest, panicking if it didn't exit the way it should have. The
+/// output of the test is passed through `map_output` before being reported.
+fn run_test_binary(mut cmd: Command, should_panic: bool, map_output: &dyn Fn(&str) -> String) {
     match cmd.output() {
         Err(e) => panic!("couldn't run the test: {}{}", e,
                         if e.kind() == io::ErrorKind::PermissionDenied {
@@ -339,13 +344,204 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
                 panic!("test executable succeeded when it should have failed");
             } else if !should_panic && !out.status.success() {
                 panic!("test executable failed:\n{}\n{}\n",
-                       str::from_utf8(&out.stdout).unwrap_or(""),
-                       str::from_utf8(&out.stderr).unwrap_or(""));
+                       map_output(str::from_utf8(&out.stdout).unwrap_or("")),
+                       map_output(str::from_utf8(&out.stderr).unwrap_or("")));
             }
         }
     }
 }
 
+/// Doctests of one edition compiled together into a single binary, instead of compiling and
+/// linking a binary for each of them. Each test is made into a module of the merged crate, and
+/// the binary runs the test whose index it is given, so that every test still runs in its own
+/// process.
+///
+/// The merged crate is built when the first of its tests is run. If it doesn't compile, for
+/// instance because two of the tests define the same macro, each test is compiled on its own.
+struct MergedDoctests {
+    edition: Edition,
+    cratename: String,
+    cfgs: Vec<String>,
+    libs: Vec<SearchPath>,
+    cg: CodegenOptions,
+    externs: Externs,
+    opts: TestOptions,
+    maybe_sysroot: Option<PathBuf>,
+    linker: Option<PathBuf>,
+    persist_doctests: Option<PathBuf>,
+    tests: Mutex<Vec<MergedDoctest>>,
+    /// The merged binary once the crate was compiled, or `Some(None)` if it failed to compile.
+    binary: Mutex<Option<Option<MergedBinary>>>,
+}
+
+struct MergedDoctest {
+    /// The test as returned by `make_test`, which becomes the body of a module.
+    module: String,
+    /// The number of lines `make_test` added before the code of the test.
+    line_offset: usize,
+}
+
+struct MergedBinary {
+    /// The directory containing the binary, removed along with it if temporary.
+    dir: DirState,
+    /// The number of lines of the merged crate before the body of each module.
+    module_lines: Vec<usize>,
+}
+
+impl MergedDoctests {
+    /// The file name of the merged crate, as used in its diagnostics and panic messages.
+    fn source_name(&self) -> FileName {
+        FileName::Custom(format!("{}-merged-doctests-{}", self.cratename, self.edition))
+    }
+
+    fn source(&self) -> (String, Vec<usize>) {
+        let tests = self.tests.lock().unwrap();
+        let mut source = String::new();
+        if self.opts.attrs.is_empty() && !self.opts.display_warnings {
+            source.push_str("#![allow(unused)]\n");
+        }
+        for attr in &self.opts.attrs {
+            source.push_str(&format!("#![{}]\n", attr));
+        }
+        // In the 2015 edition, paths in `use` declarations start at the crate root, which is where
+        // the tests expect their `extern crate` to be.
+        if !self.opts.no_crate_inject && self.cratename != "std" &&
+           tests.iter().any(|test| test.module.contains(&self.cratename[..])) {
+            source.push_str(&format!("extern crate {};\n", self.cratename));
+        }
+
+        let mut lines = source.matches('\n').count();
+        let mut module_lines = Vec::with_capacity(tests.len());
+        for (i, test) in tests.iter().enumerate() {
+            source.push_str(&format!("mod __doctest_{} {{\n", i));
+            module_lines.push(lines + 1);
+            source.push_str(&test.module);
+            source.push_str("\npub fn __run() { main() }\n}\n");
+            lines += test.module.matches('\n').count() + 4;
+        }
+
+        source.push_str("fn main() {\n");
+        source.push_str("    match ::std::env::args().nth(1).as_ref().map(|arg| &arg[..]) {\n");
+        for i in 0..tests.len() {
+            source.push_str(&format!("        Some(\"{0}\") => __doctest_{0}::__run(),\n", i));
+        }
+        source.push_str("        _ => panic!(\"expected the index of a doctest\"),\n");
+        source.push_str("    }\n}\n");
+        (source, module_lines)
+    }
+
+    fn build(&self) -> Option<MergedBinary> {
+        let (source, module_lines) = self.source();
+
+        let dir = if let Some(ref path) = self.persist_doctests {
+            let path = path.join(format!("merged_doctests_{}", self.edition));
+            std::fs::create_dir_all(&path)
+                .expect("Couldn't create directory for doctest executables");
+            DirState::Perm(path)
+        } else {
+            DirState::Temp(TempFileBuilder::new()
+                            .prefix("rustdoctest")
+                            .tempdir()
+                            .expect("rustdoc needs a tempdir"))
+        };
+
+        let sessopts = config::Options {
+            maybe_sysroot: self.maybe_sysroot.clone().or_else(|| {
+                Some(env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf())
+            }),
+            search_paths: self.libs.clone(),
+            crate_types: vec![config::CrateType::Executable],
+            output_types: OutputTypes::new(&[(OutputType::Exe, None)]),
+            externs: self.externs.clone(),
+            cg: config::CodegenOptions {
+                linker: self.linker.clone(),
+                ..self.cg.clone()
+            },
+            unstable_features: UnstableFeatures::from_environment(),
+            debugging_opts: config::basic_debugging_options(),
+            edition: self.edition,
+            ..config::Options::default()
+        };
+
+        // The errors aren't shown: the tests are compiled on their own instead, which reports them
+        // for the test they come from.
+        let config = interface::Config {
+            opts: sessopts,
+            crate_cfg: config::parse_cfgspecs(self.cfgs.clone()),
+            input: config::Input::Str {
+                name: self.source_name(),
+                input: source,
+            },
+            input_path: None,
+            output_file: Some(dir.path().join("rust_out")),
+            output_dir: None,
+            file_loader: None,
+            diagnostic_output: DiagnosticOutput::Raw(box io::sink()),
+            stderr: Some(Arc::new(Mutex::new(Vec::new()))),
+            crate_name: None,
+            lint_caps: Default::default(),
+        };
+
+        let compile_result = panic::catch_unwind(AssertUnwindSafe(|| {
+            interface::run_compiler(config, |compiler| {
+                compiler.compile().ok();
+                compiler.session().compile_status()
+            })
+        }));
+        match compile_result {
+            Ok(Ok(())) => Some(MergedBinary { dir, module_lines }),
+            _ => None,
+        }
+    }
+
+    /// Runs the test at `index` from the merged binary, building it first if needed. Returns
+    /// `false` without running the test if the merged crate doesn't compile.
+    fn run_test(&self, index: usize, filename: &FileName, line: usize,
+                should_panic: bool) -> bool {
+        let mut binary = self.binary.lock().unwrap_or_else(|e| e.into_inner());
+        if binary.is_none() {
+            *binary = Some(self.build());
+        }
+        let (path, module_line) = match *binary {
+            Some(Some(ref binary)) => (binary.dir.path().join("rust_out"),
+                                       binary.module_lines[index]),
+            _ => return false,
+        };
+        drop(binary);
+
+        // Report the lines of the test in its file, as is done for the tests compiled on their own.
+        let line_offset = self.tests.lock().unwrap()[index].line_offset;
+        let delta = line as isize - line_offset as isize - module_line as isize;
+        let source_name = format!("{}:", self.source_name());
+        let filename = filename.to_string();
+
+        let mut cmd = Command::new(path);
+        cmd.arg(index.to_string());
+        run_test_binary(cmd, should_panic, &|output| {
+            map_merged_lines(output, &source_name, &filename, delta)
+        });
+        true
+    }
+}
+
+/// Replaces each `<source_name><line>` in `output` with `<filename>:<line + delta>`.
+fn map_merged_lines(output: &str, source_name: &str, filename: &str, delta: isize) -> String {
+    let mut mapped = String::with_capacity(output.len());
+    let mut rest = output;
+    while let Some(pos) = rest.find(source_name) {
+        mapped.push_str(&rest[..pos]);
+        rest = &rest[pos + source_name.len()..];
+        let digits = rest.bytes().take_while(|b| b.is_ascii_digit()).count();
+        match rest[..digits].parse::<isize>() {
+            Ok(line) => mapped.push_str(&format!("{}:{}", filename, line + delta)),
+            Err(_) => mapped.push_str(source_name),
+        }
+        rest = &rest[digits..];
+    }
+    mapped.push_str(rest);
+    mapped
+}
+
 /// Transforms a test into code that can be compiled into a Rust binary, and returns the number of
 /// lines before the test code begins.
 ///
@@ -628,6 +824,9 @@ pub struct Collector {
     linker: Option<PathBuf>,
     edition: Edition,
     persist_doctests: Option<PathBuf>,
+    merge_doctests: bool,
+    /// The merged doctests of each edition, when `merge_doctests` is set.
+    merged: Vec<Arc<MergedDoctests>>,
 }
 
 impl Collector {
@@ -635,7 +834,7 @@ impl Collector {
                externs: Externs, use_headers: bool, opts: TestOptions,
                maybe_sysroot: Option<PathBuf>, source_map: Option<Lrc<SourceMap>>,
                filename: Option<PathBuf>, linker: Option<PathBuf>, edition: Edition,
-               persist_doctests: Option<PathBuf>) -> Collector {
+               persist_doctests: Option<PathBuf>, merge_doctests: bool) -> Collector {
         Collector {
             tests: Vec::new(),
             names: Vec::new(),
@@ -653,6 +852,8 @@ impl Collector {
             linker,
             edition,
             persist_doctests,
+            merge_doctests,
+            merged: Vec::new(),
         }
     }
 
@@ -681,6 +882,60 @@ impl Collector {
             FileName::Custom("input".to_owned())
         }
     }
+
+    /// Adds the test to the merged doctests of its edition if it can be compiled along with the
+    /// other tests, returning them and the index of the test among them.
+    fn merge_test(&mut self, test: &str, config: &LangString,
+                  edition: Edition) -> Option<(Arc<MergedDoctests>, usize)> {
+        // Tests that aren't compiled and run as usual, or that could change how the crate they
+        // are in is compiled, are left on their own.
+        if config.ignore || config.no_run || config.compile_fail || config.test_harness ||
+           !config.error_codes.is_empty() {
+            return None;
+        }
+        let (crate_attrs, _, _) = partition_source(test);
+        if crate_attrs.contains("#![") || test.contains("fn main") {
+            return None;
+        }
+        let opts = TestOptions {
+            no_crate_inject: self.opts.no_crate_inject,
+            // The lints are configured for the whole merged crate.
+            display_warnings: true,
+            attrs: Vec::new(),
+        };
+        let cratename = &self.cratename[..];
+        let (module, line_offset) = panic::catch_unwind(|| {
+            make_test(test, Some(cratename), false, &opts)
+        }).ok()?;
+
+        let group = match self.merged.iter().find(|group| group.edition == edition) {
+            Some(group) => group.clone(),
+            None => {
+                let group = Arc::new(MergedDoctests {
+                    edition,
+                    cratename: self.cratename.clone(),
+                    cfgs: self.cfgs.clone(),
+                    libs: self.libs.clone(),
+                    cg: self.cg.clone(),
+                    externs: self.externs.clone(),
+                    opts: self.opts.clone(),
+                    maybe_sysroot: self.maybe_sysroot.clone(),
+                    linker: self.linker.clone(),
+                    persist_doctests: self.persist_doctests.clone(),
+                    tests: Mutex::new(Vec::new()),
+                    binary: Mutex::new(None),
+                });
+                self.merged.push(group.clone());
+                group
+            }
+        };
+        let index = {
+            let mut tests = group.tests.lock().unwrap();
+            tests.push(MergedDoctest { module, line_offset });
+            tests.len() - 1
+        };
+        Some((group, index))
+    }
 }
 
 impl Tester for Collector {
@@ -697,6 +952,11 @@ impl Tester for Collector {
         let linker = self.linker.clone();
         let edition = config.edition.unwrap_or(self.edition);
         let persist_doctests = self.persist_doctests.clone();
+        let merged = if self.merge_doctests {
+            self.merge_test(&test, &config, edition)
+        } else {
+            None
+        };
 
         debug!("Creating test {}: {}", name, test);
         self.tests.push(testing::TestDescAndFn {
@@ -706,8 +966,14 @@ impl Tester for Collector {
                 // compiler failures are test failures
                 should_panic: testing::ShouldPanic::No,
                 allow_fail: config.allow_fail,
+                timeout: None,
             },
             testfn: testing::DynTestFn(box move || {
+                if let Some((group, index)) = merged {
+                    if group.run_test(index, &filename, line, config.should_panic) {
+                        return;
+                    }
+                }
                 run_test(
                     &test,
                     &cratename,
@@ -1177,4 +1443,14 @@ test_wrapper! {
         let output = make_test(input, Some("my_crate"), false, &opts);
         assert_eq!(output, (expected, 1));
     }
+
+    #[test]
+    fn map_merged_lines_to_test() {
+        let output = "thread 'main' panicked at 'oops', <foo-merged-doctests-2015>:12:5\n\
+                      <foo-merged-doctests-2015>:x";
+        let expected = "thread 'main' panicked at 'oops', src/lib.rs:40:5\n\
+                        <foo-merged-doctests-2015>:x";
+        assert_eq!(map_merged_lines(output, "<foo-merged-doctests-2015>:", "src/lib.rs", 28),
+                   expected);
+    }
 }
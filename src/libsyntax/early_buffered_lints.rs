@@ -4,6 +4,7 @@
 //! redundant. Later, these types can be converted to types for use by the rest of the compiler.
 
 use crate::syntax::ast::NodeId;
+use errors::Diagnostic;
 use syntax_pos::MultiSpan;
 
 /// Since we cannot import `LintId`s from `rustc::lint`, we define some Ids here which can later be
@@ -12,6 +13,8 @@ pub enum BufferedEarlyLintId {
     /// Usage of `?` as a macro separator is deprecated.
     QuestionMarkMacroSep,
     IllFormedAttributeInput,
+    /// A warning reported by a procedural macro with `proc_macro::Level::Lint`.
+    ProcMacroWarnings,
 }
 
 /// Stores buffered lint info which can later be passed to `librustc`.
@@ -27,4 +30,8 @@ pub struct BufferedEarlyLint {
 
    /// A lint Id that can be passed to `rustc::lint::Lint::from_parser_lint_id`.
    pub lint_id: BufferedEarlyLintId,
+
+   /// The complete diagnostic, when the lint is reported with the children and suggestions of
+   /// an existing diagnostic rather than just a message.
+   pub diagnostic: Option<Diagnostic>,
 }
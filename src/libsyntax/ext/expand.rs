@@ -1657,6 +1657,7 @@ impl<'feat> ExpansionConfig<'feat> {
     feature_tests! {
         fn enable_asm = asm,
         fn enable_custom_test_frameworks = custom_test_frameworks,
+        fn enable_test_timeout = test_timeout,
         fn enable_global_asm = global_asm,
         fn enable_log_syntax = log_syntax,
         fn enable_concat_idents = concat_idents,
@@ -554,6 +554,9 @@ declare_features! (
     // Allows using C-variadics.
     (active, c_variadic, "1.34.0", Some(44930), None),
 
+    // Allows `#[test(timeout = "secs")]`.
+    (active, test_timeout, "1.36.0", None, None),
+
     // -------------------------------------------------------------------------
     // feature-group-end: actual feature gates
     // -------------------------------------------------------------------------
@@ -1744,6 +1747,9 @@ pub const EXPLAIN_GLOBAL_ASM: &str =
 pub const EXPLAIN_CUSTOM_TEST_FRAMEWORKS: &str =
     "custom test frameworks are an unstable feature";
 
+pub const EXPLAIN_TEST_TIMEOUT: &str =
+    "`#[test(timeout = \"...\")]` is currently unstable";
+
 pub const EXPLAIN_LOG_SYNTAX: &str =
     "`log_syntax!` is not stable enough for use and is subject to change";
 
@@ -126,6 +126,7 @@ pub mod util {
 }
 
 pub mod json;
+pub mod sarif;
 
 pub mod syntax {
     pub use crate::ext;
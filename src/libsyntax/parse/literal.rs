@@ -30,7 +30,7 @@ impl LitKind {
     /// Works speculatively and may return `None` if diagnostic handler is not passed.
     /// If diagnostic handler is passed, always returns `Some`,
     /// possibly after reporting non-fatal errors and recovery.
-    fn from_lit_token(
+    pub fn from_lit_token(
         lit: token::Lit,
         suf: Option<Symbol>,
         diag: Option<(Span, &Handler)>
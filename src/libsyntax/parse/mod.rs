@@ -9,6 +9,7 @@ use crate::syntax::parse::parser::emit_unclosed_delims;
 use crate::tokenstream::{TokenStream, TokenTree};
 use crate::diagnostics::plugin::ErrorMap;
 use crate::print::pprust::token_to_string;
+use crate::symbol::Symbol;
 
 use errors::{Applicability, FatalError, Level, Handler, ColorConfig, Diagnostic, DiagnosticBuilder};
 use rustc_data_structures::sync::{Lrc, Lock};
@@ -51,6 +52,11 @@ pub struct ParseSess {
     /// operation token that followed it, but that the parser cannot identify without further
     /// analysis.
     pub ambiguous_block_expr_parse: Lock<FxHashMap<Span, Span>>,
+    /// Environment variables accessed during the build and their values when they exist,
+    /// written to dep-info.
+    pub env_depinfo: Lock<FxHashSet<(Symbol, Option<Symbol>)>>,
+    /// Additional files accessed during the build, written to dep-info.
+    pub file_depinfo: Lock<FxHashSet<Symbol>>,
 }
 
 impl ParseSess {
@@ -75,6 +81,8 @@ impl ParseSess {
             source_map,
             buffered_lints: Lock::new(vec![]),
             ambiguous_block_expr_parse: Lock::new(FxHashMap::default()),
+            env_depinfo: Lock::new(FxHashSet::default()),
+            file_depinfo: Lock::new(FxHashSet::default()),
         }
     }
 
@@ -95,6 +103,25 @@ impl ParseSess {
                 id,
                 msg: msg.into(),
                 lint_id,
+                diagnostic: None,
+            });
+        });
+    }
+
+    /// Buffers a lint reported with the message, span, children and suggestions of an
+    /// existing diagnostic.
+    pub fn buffer_lint_with_diagnostic(&self,
+        lint_id: BufferedEarlyLintId,
+        id: NodeId,
+        diagnostic: Diagnostic,
+    ) {
+        self.buffered_lints.with_lock(|buffered_lints| {
+            buffered_lints.push(BufferedEarlyLint{
+                span: diagnostic.span.clone(),
+                id,
+                msg: diagnostic.message(),
+                lint_id,
+                diagnostic: Some(diagnostic),
             });
         });
     }
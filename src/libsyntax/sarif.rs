@@ -0,0 +1,359 @@
+//! A SARIF emitter for errors.
+//!
+//! SARIF (Static Analysis Results Interchange Format, version 2.1.0) is the
+//! format code scanning services ingest. Unlike the JSON emitter, which prints
+//! one object per diagnostic, a SARIF log is a single document: diagnostics
+//! are converted as they are emitted and the whole log is written out when the
+//! emitter is dropped, that is when the session ends.
+//!
+//! Each diagnostic becomes a `result`:
+//!
+//! * primary spans are its `locations`, other labelled spans, the spans of
+//!   notes and help messages and the macro backtrace are `relatedLocations`,
+//! * notes and help messages without a span are appended to the message,
+//! * suggestions are `fixes`, one per alternative substitution,
+//! * the error code or lint name is the `ruleId`, described in the `rules` of
+//!   the tool.
+//!
+//! See <https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>.
+
+use crate::source_map::{SourceMap, FilePathMapping};
+
+use errors::registry::Registry;
+use errors::{DiagnosticBuilder, SubDiagnostic, CodeSuggestion, SourceMapper};
+use errors::{DiagnosticId, Applicability, Level};
+use errors::emitter::Emitter;
+
+use syntax_pos::{FileName, MultiSpan, Span};
+use rustc_data_structures::sync::{self, Lrc};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+
+use rustc_serialize::json::{Json, ToJson};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<dyn SourceMapper + sync::Send + sync::Sync>,
+    /// Every code seen so far, in order of first use; results refer to the
+    /// rule describing their code by its index in this list.
+    rules: Vec<DiagnosticId>,
+    results: Vec<Json>,
+    /// Whether an error was emitted, which makes the invocation unsuccessful.
+    has_errors: bool,
+}
+
+impl SarifEmitter {
+    pub fn stderr(registry: Option<Registry>, source_map: Lrc<SourceMap>) -> SarifEmitter {
+        SarifEmitter::new(Box::new(io::stderr()), registry, source_map)
+    }
+
+    pub fn basic() -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(None, Lrc::new(SourceMap::new(file_path_mapping)))
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            rules: Vec::new(),
+            results: Vec::new(),
+            has_errors: false,
+        }
+    }
+
+    fn result(&mut self, db: &DiagnosticBuilder<'_>) -> Json {
+        let mut message = db.message();
+        let mut locations = Vec::new();
+        let mut related_locations = Vec::new();
+
+        for span_label in db.span.span_labels() {
+            if span_label.is_primary {
+                locations.extend(self.location(span_label.span, span_label.label));
+                self.push_macro_backtrace(span_label.span, &mut related_locations);
+            } else if let Some(label) = span_label.label {
+                related_locations.extend(self.location(span_label.span, Some(label)));
+            }
+        }
+
+        for child in &db.children {
+            self.push_sub_diagnostic(child, &mut message, &mut related_locations);
+        }
+
+        let fixes = db.suggestions.iter().flat_map(|sugg| self.fixes(sugg)).collect::<Vec<_>>();
+
+        let rule_index = db.code.as_ref().map(|code| {
+            match self.rules.iter().position(|rule| rule == code) {
+                Some(index) => index,
+                None => {
+                    self.rules.push(code.clone());
+                    self.rules.len() - 1
+                }
+            }
+        });
+
+        object(vec![
+            ("ruleId", db.code.as_ref().map(code_str).to_json()),
+            ("ruleIndex", rule_index.to_json()),
+            ("level", result_level(db.level).to_json()),
+            ("message", text(message)),
+            ("locations", non_empty(locations)),
+            ("relatedLocations", non_empty(related_locations)),
+            ("fixes", non_empty(fixes)),
+        ])
+    }
+
+    fn push_sub_diagnostic(&self,
+                           sub: &SubDiagnostic,
+                           message: &mut String,
+                           related_locations: &mut Vec<Json>) {
+        let sub_message = format!("{}: {}", sub.level, sub.message());
+        let span = sub.render_span.as_ref().unwrap_or(&sub.span);
+        let span_labels = span.span_labels()
+            .into_iter()
+            .filter(|span_label| !span_label.span.is_dummy())
+            .collect::<Vec<_>>();
+
+        if span_labels.is_empty() {
+            message.push_str("\n");
+            message.push_str(&sub_message);
+            return;
+        }
+
+        for span_label in span_labels {
+            let location_message = match span_label.label {
+                Some(label) => format!("{} ({})", sub_message, label),
+                None => sub_message.clone(),
+            };
+            related_locations.extend(self.location(span_label.span, Some(location_message)));
+        }
+    }
+
+    /// Records where the code at `span` was expanded from, innermost expansion
+    /// first, as the human readable output does.
+    fn push_macro_backtrace(&self, span: Span, related_locations: &mut Vec<Json>) {
+        for bt in span.macro_backtrace() {
+            related_locations.extend(self.location(
+                bt.call_site,
+                Some(format!("in this expansion of `{}`", bt.macro_decl_name)),
+            ));
+            if let Some(def_site_span) = bt.def_site_span {
+                related_locations.extend(self.location(
+                    def_site_span,
+                    Some(format!("`{}` defined here", bt.macro_decl_name)),
+                ));
+            }
+        }
+    }
+
+    fn fixes(&self, sugg: &CodeSuggestion) -> Vec<Json> {
+        sugg.substitutions.iter().map(|substitution| {
+            // SARIF groups the replacements of a fix by the file they apply to.
+            let mut changes: Vec<(String, Vec<Json>)> = Vec::new();
+            for part in &substitution.parts {
+                let uri = self.uri(part.span);
+                let replacement = object(vec![
+                    ("deletedRegion", self.region(part.span)),
+                    ("insertedContent", object(vec![("text", part.snippet.to_json())])),
+                ]);
+                match changes.iter_mut().find(|(change_uri, _)| *change_uri == uri) {
+                    Some((_, replacements)) => replacements.push(replacement),
+                    None => changes.push((uri, vec![replacement])),
+                }
+            }
+
+            object(vec![
+                ("description", text(sugg.msg.clone())),
+                ("artifactChanges", Json::Array(changes.into_iter().map(|(uri, replacements)| {
+                    object(vec![
+                        ("artifactLocation", object(vec![("uri", uri.to_json())])),
+                        ("replacements", Json::Array(replacements)),
+                    ])
+                }).collect())),
+                ("properties", object(vec![
+                    ("applicability", applicability_str(sugg.applicability).to_json()),
+                ])),
+            ])
+        }).collect()
+    }
+
+    fn location(&self, span: Span, message: Option<String>) -> Option<Json> {
+        if span.is_dummy() {
+            return None;
+        }
+        Some(object(vec![
+            ("physicalLocation", object(vec![
+                ("artifactLocation", object(vec![("uri", self.uri(span).to_json())])),
+                ("region", self.region(span)),
+            ])),
+            ("message", message.map(text).to_json()),
+        ]))
+    }
+
+    fn uri(&self, span: Span) -> String {
+        file_uri(&self.sm.span_to_filename(span))
+    }
+
+    fn region(&self, span: Span) -> Json {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        object(vec![
+            ("startLine", start.line.to_json()),
+            ("startColumn", (start.col.0 + 1).to_json()),
+            ("endLine", end.line.to_json()),
+            ("endColumn", (end.col.0 + 1).to_json()),
+            ("byteOffset", (span.lo().0 - start.file.start_pos.0).to_json()),
+            ("byteLength", (span.hi().0 - span.lo().0).to_json()),
+        ])
+    }
+
+    fn rule(&self, code: &DiagnosticId) -> Json {
+        let id = code_str(code);
+        let explanation = self.registry
+                              .as_ref()
+                              .and_then(|registry| registry.find_description(id));
+        let help_uri = match *code {
+            DiagnosticId::Error(ref code) => {
+                Some(format!("https://doc.rust-lang.org/error-index.html#{}", code))
+            }
+            DiagnosticId::Lint(_) => None,
+        };
+        object(vec![
+            ("id", id.to_json()),
+            ("fullDescription", explanation.map(|explanation| object(vec![
+                ("text", explanation.trim().to_json()),
+                ("markdown", explanation.trim().to_json()),
+            ])).to_json()),
+            ("helpUri", help_uri.to_json()),
+        ])
+    }
+
+    fn log(&mut self) -> Json {
+        let rules = self.rules.iter().map(|code| self.rule(code)).collect::<Vec<_>>();
+        let results = std::mem::replace(&mut self.results, Vec::new());
+        let run = object(vec![
+            ("tool", object(vec![
+                ("driver", object(vec![
+                    ("name", "rustc".to_json()),
+                    ("informationUri", "https://www.rust-lang.org/".to_json()),
+                    ("rules", non_empty(rules)),
+                ])),
+            ])),
+            ("invocations", Json::Array(vec![object(vec![
+                ("executionSuccessful", (!self.has_errors).to_json()),
+            ])])),
+            // Columns are counted in characters, not in the default UTF-16 code units.
+            ("columnKind", "unicodeCodePoints".to_json()),
+            ("results", Json::Array(results)),
+        ]);
+        object(vec![
+            ("$schema", SARIF_SCHEMA.to_json()),
+            ("version", SARIF_VERSION.to_json()),
+            ("runs", Json::Array(vec![run])),
+        ])
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, db: &DiagnosticBuilder<'_>) {
+        // Failure notes only point at other output, like `rustc --explain`.
+        if db.level.is_failure_note() {
+            return;
+        }
+        if db.is_error() {
+            self.has_errors = true;
+        }
+        let result = self.result(db);
+        self.results.push(result);
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        let log = self.log();
+        // Panicking here could abort the compiler while it is already unwinding,
+        // and there is nowhere else to report the error to.
+        let _ = writeln!(&mut self.dst, "{}", log.pretty());
+    }
+}
+
+/// Builds a JSON object, leaving out the properties that are `null` as SARIF
+/// does not allow them for optional properties.
+fn object(properties: Vec<(&str, Json)>) -> Json {
+    Json::Object(properties.into_iter()
+                           .filter(|&(_, ref value)| *value != Json::Null)
+                           .map(|(key, value)| (key.to_string(), value))
+                           .collect())
+}
+
+fn text(message: String) -> Json {
+    object(vec![("text", Json::String(message))])
+}
+
+fn non_empty(values: Vec<Json>) -> Json {
+    if values.is_empty() { Json::Null } else { Json::Array(values) }
+}
+
+fn code_str(code: &DiagnosticId) -> &str {
+    match *code {
+        DiagnosticId::Error(ref s) | DiagnosticId::Lint(ref s) => s,
+    }
+}
+
+fn result_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::Fatal | Level::PhaseFatal | Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note | Level::Help | Level::FailureNote | Level::Cancelled => "note",
+    }
+}
+
+fn applicability_str(applicability: Applicability) -> &'static str {
+    match applicability {
+        Applicability::MachineApplicable => "machine-applicable",
+        Applicability::MaybeIncorrect => "maybe-incorrect",
+        Applicability::HasPlaceholders => "has-placeholders",
+        Applicability::Unspecified => "unspecified",
+    }
+}
+
+/// Turns a file name into a URI reference. Relative paths stay relative, to
+/// the directory the compiler was run from; names of virtual files such as
+/// `<std macros>` are percent-encoded like any other.
+fn file_uri(name: &FileName) -> String {
+    let name = name.to_string();
+    let absolute = Path::new(&name).is_absolute();
+    let mut uri = String::with_capacity(name.len());
+    if absolute {
+        uri.push_str(if name.starts_with('/') { "file://" } else { "file:///" });
+    }
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            b'\\' => uri.push('/'),
+            // Keeps drive letters readable, while in a relative reference the
+            // colon could be mistaken for the end of a scheme.
+            b':' if absolute => uri.push(':'),
+            _ => {
+                let _ = write!(uri, "%{:02X}", byte);
+            }
+        }
+    }
+    uri
+}
@@ -1,14 +1,15 @@
-use errors::{Diagnostic, DiagnosticBuilder};
+use errors::{Diagnostic, DiagnosticBuilder, DiagnosticId, SubstitutionPart};
 
 use std::panic;
 
 use proc_macro::bridge::{server, TokenTree};
-use proc_macro::{Delimiter, Level, LineColumn, Spacing};
+use proc_macro::{Applicability, Delimiter, Level, LineColumn, LiteralKind, Spacing};
 
 use rustc_data_structures::sync::Lrc;
 use std::ascii;
 use std::ops::Bound;
 use syntax::ast;
+use syntax::early_buffered_lints::BufferedEarlyLintId;
 use syntax::ext::base::ExtCtxt;
 use syntax::parse::lexer::comments;
 use syntax::parse::{self, token, ParseSess};
@@ -281,11 +282,26 @@ impl ToInternal<errors::Level> for Level {
             Level::Warning => errors::Level::Warning,
             Level::Note => errors::Level::Note,
             Level::Help => errors::Level::Help,
+            Level::Lint => errors::Level::Warning,
             _ => unreachable!("unknown proc_macro::Level variant: {:?}", self),
         }
     }
 }
 
+impl ToInternal<errors::Applicability> for Applicability {
+    fn to_internal(self) -> errors::Applicability {
+        match self {
+            Applicability::MachineApplicable => errors::Applicability::MachineApplicable,
+            Applicability::MaybeIncorrect => errors::Applicability::MaybeIncorrect,
+            Applicability::HasPlaceholders => errors::Applicability::HasPlaceholders,
+            Applicability::Unspecified => errors::Applicability::Unspecified,
+            _ => unreachable!("unknown proc_macro::Applicability variant: {:?}", self),
+        }
+    }
+}
+
+pub struct FreeFunctions;
+
 #[derive(Clone)]
 pub struct TokenStreamIter {
     cursor: tokenstream::Cursor,
@@ -359,14 +375,29 @@ pub struct Literal {
     span: Span,
 }
 
-pub(crate) struct Rustc<'a> {
-    sess: &'a ParseSess,
+impl Literal {
+    /// Parses the value of the literal, as the parser would.
+    fn value(&self) -> Option<ast::LitKind> {
+        // Any suffix can be used in the input of a proc macro, so the value is
+        // parsed without it, except for float suffixes which turn `1f32` into
+        // a float literal.
+        let suffix = self.suffix.filter(|suffix| {
+            suffix.as_str() == "f32" || suffix.as_str() == "f64"
+        });
+        ast::LitKind::from_lit_token(self.lit, suffix, None)
+    }
+}
+
+pub(crate) struct Rustc<'a, 'b> {
+    /// Used to expand the macros in token streams given to `expand_expr`.
+    ecx: &'a mut ExtCtxt<'b>,
+    sess: &'b ParseSess,
     def_site: Span,
     call_site: Span,
 }
 
-impl<'a> Rustc<'a> {
-    pub fn new(cx: &'a ExtCtxt<'_>) -> Self {
+impl<'a, 'b> Rustc<'a, 'b> {
+    pub fn new(cx: &'a mut ExtCtxt<'b>) -> Self {
         // No way to determine def location for a proc macro right now, so use call location.
         let location = cx.current_expansion.mark.expn_info().unwrap().call_site;
         let to_span = |transparency| {
@@ -375,15 +406,19 @@ impl<'a> Rustc<'a> {
                     .apply_mark_with_transparency(cx.current_expansion.mark, transparency),
             )
         };
+        let def_site = to_span(Transparency::Opaque);
+        let call_site = to_span(Transparency::Transparent);
         Rustc {
             sess: cx.parse_sess,
-            def_site: to_span(Transparency::Opaque),
-            call_site: to_span(Transparency::Transparent),
+            ecx: cx,
+            def_site,
+            call_site,
         }
     }
 }
 
-impl server::Types for Rustc<'_> {
+impl server::Types for Rustc<'_, '_> {
+    type FreeFunctions = FreeFunctions;
     type TokenStream = TokenStream;
     type TokenStreamBuilder = tokenstream::TokenStreamBuilder;
     type TokenStreamIter = TokenStreamIter;
@@ -397,7 +432,16 @@ impl server::Types for Rustc<'_> {
     type Span = Span;
 }
 
-impl server::TokenStream for Rustc<'_> {
+impl server::FreeFunctions for Rustc<'_, '_> {
+    fn track_env_var(&mut self, var: &str, value: Option<&str>) {
+        self.sess.env_depinfo.borrow_mut().insert((Symbol::intern(var), value.map(Symbol::intern)));
+    }
+    fn track_path(&mut self, path: &str) {
+        self.sess.file_depinfo.borrow_mut().insert(Symbol::intern(path));
+    }
+}
+
+impl server::TokenStream for Rustc<'_, '_> {
     fn new(&mut self) -> Self::TokenStream {
         TokenStream::empty()
     }
@@ -415,6 +459,42 @@ impl server::TokenStream for Rustc<'_> {
     fn to_string(&mut self, stream: &Self::TokenStream) -> String {
         stream.to_string()
     }
+    fn expand_expr(&mut self, stream: &Self::TokenStream) -> Result<Self::TokenStream, ()> {
+        let mut parser = parse::stream_to_parser(self.sess, stream.clone());
+        let mut expr = match parser.parse_expr() {
+            Ok(expr) => expr,
+            Err(mut err) => {
+                err.emit();
+                return Err(());
+            }
+        };
+        if parser.token != token::Eof {
+            self.sess.span_diagnostic.span_err(parser.span, "expected a single expression");
+            return Err(());
+        }
+
+        // Expands e.g., `concat!(env!("X"), "y")` into a literal.
+        self.ecx.expander().visit_expr(&mut expr);
+
+        let lit_token = |lit: &ast::Lit| {
+            let token = match lit.token {
+                token::Bool(name) => token::Ident(ast::Ident::with_empty_ctxt(name), false),
+                lit_token => token::Literal(lit_token, lit.suffix),
+            };
+            tokenstream::TokenTree::Token(lit.span, token)
+        };
+        match expr.node {
+            ast::ExprKind::Lit(ref lit) => Ok(lit_token(lit).into()),
+            ast::ExprKind::Unary(ast::UnOp::Neg, ref operand) => match operand.node {
+                ast::ExprKind::Lit(ref lit) => Ok(TokenStream::new(vec![
+                    tokenstream::TokenTree::Token(expr.span, token::BinOp(token::Minus)).into(),
+                    lit_token(lit).into(),
+                ])),
+                _ => Err(()),
+            },
+            _ => Err(()),
+        }
+    }
     fn from_token_tree(
         &mut self,
         tree: TokenTree<Self::Group, Self::Punct, Self::Ident, Self::Literal>,
@@ -429,7 +509,7 @@ impl server::TokenStream for Rustc<'_> {
     }
 }
 
-impl server::TokenStreamBuilder for Rustc<'_> {
+impl server::TokenStreamBuilder for Rustc<'_, '_> {
     fn new(&mut self) -> Self::TokenStreamBuilder {
         tokenstream::TokenStreamBuilder::new()
     }
@@ -441,7 +521,7 @@ impl server::TokenStreamBuilder for Rustc<'_> {
     }
 }
 
-impl server::TokenStreamIter for Rustc<'_> {
+impl server::TokenStreamIter for Rustc<'_, '_> {
     fn next(
         &mut self,
         iter: &mut Self::TokenStreamIter,
@@ -468,7 +548,7 @@ impl server::TokenStreamIter for Rustc<'_> {
     }
 }
 
-impl server::Group for Rustc<'_> {
+impl server::Group for Rustc<'_, '_> {
     fn new(&mut self, delimiter: Delimiter, stream: Self::TokenStream) -> Self::Group {
         Group {
             delimiter,
@@ -496,7 +576,7 @@ impl server::Group for Rustc<'_> {
     }
 }
 
-impl server::Punct for Rustc<'_> {
+impl server::Punct for Rustc<'_, '_> {
     fn new(&mut self, ch: char, spacing: Spacing) -> Self::Punct {
         Punct::new(ch, spacing == Spacing::Joint, server::Span::call_site(self))
     }
@@ -518,7 +598,7 @@ impl server::Punct for Rustc<'_> {
     }
 }
 
-impl server::Ident for Rustc<'_> {
+impl server::Ident for Rustc<'_, '_> {
     fn new(&mut self, string: &str, span: Self::Span, is_raw: bool) -> Self::Ident {
         Ident::new(Symbol::intern(string), is_raw, span)
     }
@@ -530,7 +610,7 @@ impl server::Ident for Rustc<'_> {
     }
 }
 
-impl server::Literal for Rustc<'_> {
+impl server::Literal for Rustc<'_, '_> {
     // FIXME(eddyb) `Literal` should not expose internal `Debug` impls.
     fn debug(&mut self, literal: &Self::Literal) -> String {
         format!("{:?}", literal)
@@ -603,6 +683,60 @@ impl server::Literal for Rustc<'_> {
             span: server::Span::call_site(self),
         }
     }
+    fn kind(&mut self, literal: &Self::Literal) -> LiteralKind {
+        match literal.lit {
+            token::Byte(_) => LiteralKind::Byte,
+            token::Char(_) => LiteralKind::Char,
+            token::Integer(_) => LiteralKind::Integer,
+            token::Float(_) => LiteralKind::Float,
+            token::Str_(_) => LiteralKind::Str,
+            token::StrRaw(_, n) => LiteralKind::StrRaw(n),
+            token::ByteStr(_) => LiteralKind::ByteStr,
+            token::ByteStrRaw(_, n) => LiteralKind::ByteStrRaw(n),
+            token::Err(_) => LiteralKind::Err,
+            token::Bool(_) => unreachable!("literal token contains `Lit::Bool`"),
+        }
+    }
+    fn suffix(&mut self, literal: &Self::Literal) -> Option<String> {
+        literal.suffix.map(|suffix| suffix.to_string())
+    }
+    fn str_value(&mut self, literal: &Self::Literal) -> Option<String> {
+        match literal.value() {
+            Some(ast::LitKind::Str(value, _)) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+    fn byte_str_value(&mut self, literal: &Self::Literal) -> Option<Vec<u8>> {
+        match literal.value() {
+            Some(ast::LitKind::ByteStr(value)) => Some(value.to_vec()),
+            _ => None,
+        }
+    }
+    fn char_value(&mut self, literal: &Self::Literal) -> Option<char> {
+        match literal.value() {
+            Some(ast::LitKind::Char(value)) => Some(value),
+            _ => None,
+        }
+    }
+    fn byte_value(&mut self, literal: &Self::Literal) -> Option<u8> {
+        match literal.value() {
+            Some(ast::LitKind::Byte(value)) => Some(value),
+            _ => None,
+        }
+    }
+    fn int_value(&mut self, literal: &Self::Literal) -> Option<u128> {
+        match literal.value() {
+            Some(ast::LitKind::Int(value, _)) => Some(value),
+            _ => None,
+        }
+    }
+    fn float_value(&mut self, literal: &Self::Literal) -> Option<String> {
+        match literal.value() {
+            Some(ast::LitKind::Float(value, _)) |
+            Some(ast::LitKind::FloatUnsuffixed(value)) => Some(value.to_string()),
+            _ => None,
+        }
+    }
     fn span(&mut self, literal: &Self::Literal) -> Self::Span {
         literal.span
     }
@@ -647,7 +781,7 @@ impl server::Literal for Rustc<'_> {
     }
 }
 
-impl server::SourceFile for Rustc<'_> {
+impl server::SourceFile for Rustc<'_, '_> {
     fn eq(&mut self, file1: &Self::SourceFile, file2: &Self::SourceFile) -> bool {
         Lrc::ptr_eq(file1, file2)
     }
@@ -665,7 +799,7 @@ impl server::SourceFile for Rustc<'_> {
     }
 }
 
-impl server::MultiSpan for Rustc<'_> {
+impl server::MultiSpan for Rustc<'_, '_> {
     fn new(&mut self) -> Self::MultiSpan {
         vec![]
     }
@@ -674,10 +808,14 @@ impl server::MultiSpan for Rustc<'_> {
     }
 }
 
-impl server::Diagnostic for Rustc<'_> {
+impl server::Diagnostic for Rustc<'_, '_> {
     fn new(&mut self, level: Level, msg: &str, spans: Self::MultiSpan) -> Self::Diagnostic {
         let mut diag = Diagnostic::new(level.to_internal(), msg);
         diag.set_span(MultiSpan::from_spans(spans));
+        if let Level::Lint = level {
+            // Marks the diagnostic to be reported through the lint in `emit`.
+            diag.code(DiagnosticId::Lint("proc_macro_warnings".to_string()));
+        }
         diag
     }
     fn sub(
@@ -689,12 +827,50 @@ impl server::Diagnostic for Rustc<'_> {
     ) {
         diag.sub(level.to_internal(), msg, MultiSpan::from_spans(spans), None);
     }
-    fn emit(&mut self, diag: Self::Diagnostic) {
-        DiagnosticBuilder::new_diagnostic(&self.sess.span_diagnostic, diag).emit()
+    fn set_code(&mut self, diag: &mut Self::Diagnostic, code: &str) {
+        diag.code(DiagnosticId::Error(code.to_string()));
+    }
+    fn suggestion(
+        &mut self,
+        diag: &mut Self::Diagnostic,
+        msg: &str,
+        applicability: Applicability,
+    ) {
+        diag.multipart_suggestion(msg, vec![], applicability.to_internal());
+    }
+    fn suggestion_part(
+        &mut self,
+        diag: &mut Self::Diagnostic,
+        span: Self::Span,
+        replacement: &str,
+    ) {
+        // A part that doesn't follow a `suggestion` has nothing to belong to.
+        let substitution = diag.suggestions.last_mut()
+            .and_then(|suggestion| suggestion.substitutions.first_mut());
+        if let Some(substitution) = substitution {
+            substitution.parts.push(SubstitutionPart {
+                span,
+                snippet: replacement.to_string(),
+            });
+        }
+    }
+    fn emit(&mut self, mut diag: Self::Diagnostic) {
+        // A suggestion that never got any part can't be displayed.
+        diag.suggestions.retain(|suggestion| {
+            suggestion.substitutions.iter().all(|substitution| !substitution.parts.is_empty())
+        });
+        if let Some(DiagnosticId::Lint(_)) = diag.code {
+            // The lint levels in scope of the macro invocation are only known
+            // once expansion is complete.
+            let id = self.ecx.resolver.lint_node_id(self.ecx.current_expansion.mark);
+            self.sess.buffer_lint_with_diagnostic(BufferedEarlyLintId::ProcMacroWarnings, id, diag);
+        } else {
+            DiagnosticBuilder::new_diagnostic(&self.sess.span_diagnostic, diag).emit()
+        }
     }
 }
 
-impl server::Span for Rustc<'_> {
+impl server::Span for Rustc<'_, '_> {
     fn debug(&mut self, span: Self::Span) -> String {
         format!("{:?} bytes({}..{})", span.ctxt(), span.lo().0, span.hi().0)
     }
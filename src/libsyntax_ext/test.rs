@@ -6,6 +6,7 @@ use syntax::ext::build::AstBuilder;
 use syntax::ext::hygiene::{self, Mark, SyntaxContext};
 use syntax::attr;
 use syntax::ast;
+use syntax::feature_gate;
 use syntax::print::pprust;
 use syntax::symbol::{Symbol, sym};
 use syntax_pos::{DUMMY_SP, Span};
@@ -15,10 +16,11 @@ use std::iter;
 pub fn expand_test(
     cx: &mut ExtCtxt<'_>,
     attr_sp: Span,
-    _meta_item: &ast::MetaItem,
+    meta_item: &ast::MetaItem,
     item: Annotatable,
 ) -> Vec<Annotatable> {
-    expand_test_or_bench(cx, attr_sp, item, false)
+    let timeout = test_timeout(cx, meta_item);
+    expand_test_or_bench(cx, attr_sp, item, false, timeout)
 }
 
 pub fn expand_bench(
@@ -27,14 +29,15 @@ pub fn expand_bench(
     _meta_item: &ast::MetaItem,
     item: Annotatable,
 ) -> Vec<Annotatable> {
-    expand_test_or_bench(cx, attr_sp, item, true)
+    expand_test_or_bench(cx, attr_sp, item, true, None)
 }
 
 pub fn expand_test_or_bench(
     cx: &mut ExtCtxt<'_>,
     attr_sp: Span,
     item: Annotatable,
-    is_bench: bool
+    is_bench: bool,
+    timeout: Option<u64>,
 ) -> Vec<Annotatable> {
     // If we're not in test configuration, remove the annotated item
     if !cx.ecfg.should_test { return vec![]; }
@@ -155,6 +158,13 @@ pub fn expand_test_or_bench(
                     field("ignore", cx.expr_bool(sp, should_ignore(&item))),
                     // allow_fail: true | false
                     field("allow_fail", cx.expr_bool(sp, should_fail(&item))),
+                    // timeout: Some(secs) | None
+                    field("timeout", match timeout {
+                        Some(secs) => cx.expr_some(sp, cx.expr_lit(sp,
+                            ast::LitKind::Int(secs as u128,
+                                              ast::LitIntType::Unsigned(ast::UintTy::U64)))),
+                        None => cx.expr_none(sp),
+                    }),
                     // should_panic: ...
                     field("should_panic", match should_panic(cx, &item) {
                         // test::ShouldPanic::No
@@ -213,6 +223,33 @@ fn should_fail(i: &ast::Item) -> bool {
     attr::contains_name(&i.attrs, sym::allow_fail)
 }
 
+fn test_timeout(cx: &ExtCtxt<'_>, meta_item: &ast::MetaItem) -> Option<u64> {
+    // Handle #[test(timeout = "secs")]
+    let list = meta_item.meta_item_list()?;
+    let timeout = list.iter().find(|mi| mi.check_name(sym::timeout))?;
+
+    if !cx.ecfg.enable_test_timeout() {
+        feature_gate::emit_feature_err(&cx.parse_sess,
+                                       sym::test_timeout,
+                                       timeout.span(),
+                                       feature_gate::GateIssue::Language,
+                                       feature_gate::EXPLAIN_TEST_TIMEOUT);
+    }
+
+    let secs = timeout.value_str()
+        .and_then(|secs| secs.as_str().parse::<u64>().ok())
+        .filter(|&secs| secs > 0);
+    if list.len() != 1 || secs.is_none() {
+        cx.parse_sess.span_diagnostic.span_err(
+            meta_item.span,
+            "argument must be of the form: `timeout = \"secs\"`, \
+             with a non-zero number of seconds"
+        );
+        return None;
+    }
+    secs
+}
+
 fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     match attr::find_by_name(&i.attrs, sym::should_panic) {
         Some(attr) => {
@@ -549,7 +549,9 @@ symbols! {
         test_accepted_feature,
         test_removed_feature,
         test_runner,
+        test_timeout,
         thread_local,
+        timeout,
         tool_attributes,
         tool_lints,
         trace_macros,
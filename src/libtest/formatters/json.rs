@@ -21,8 +21,14 @@ impl<T: Write> JsonFormatter<T> {
         ty: &str,
         name: &str,
         evt: &str,
+        exec_time: Option<&TestExecTime>,
         extra: Option<String>,
     ) -> io::Result<()> {
+        let exec_time = exec_time.map(|t| format!(r#""exec_time": {}"#, t.as_secs_f64()));
+        let extra = match (exec_time, extra) {
+            (Some(exec_time), Some(extra)) => Some(format!("{}, {}", exec_time, extra)),
+            (exec_time, extra) => exec_time.or(extra),
+        };
         if let Some(extras) = extra {
             self.write_message(&*format!(
                 r#"{{ "type": "{}", "name": "{}", "event": "{}", {} }}"#,
@@ -38,10 +44,15 @@ impl<T: Write> JsonFormatter<T> {
 }
 
 impl<T: Write> OutputFormatter for JsonFormatter<T> {
-    fn write_run_start(&mut self, test_count: usize) -> io::Result<()> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        let shuffle_seed_json = if let Some(shuffle_seed) = shuffle_seed {
+            format!(r#", "shuffle_seed": {}"#, shuffle_seed)
+        } else {
+            String::new()
+        };
         self.write_message(&*format!(
-            r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#,
-            test_count
+            r#"{{ "type": "suite", "event": "started", "test_count": {}{} }}"#,
+            test_count, shuffle_seed_json
         ))
     }
 
@@ -56,10 +67,11 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
         &mut self,
         desc: &TestDesc,
         result: &TestResult,
+        exec_time: Option<&TestExecTime>,
         stdout: &[u8],
     ) -> io::Result<()> {
         match *result {
-            TrOk => self.write_event("test", desc.name.as_slice(), "ok", None),
+            TrOk => self.write_event("test", desc.name.as_slice(), "ok", exec_time, None),
 
             TrFailed => {
                 let extra_data = if stdout.len() > 0 {
@@ -71,21 +83,34 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
                     None
                 };
 
-                self.write_event("test", desc.name.as_slice(), "failed", extra_data)
+                self.write_event("test", desc.name.as_slice(), "failed", exec_time, extra_data)
             }
 
             TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
+                exec_time,
                 Some(format!(r#""message": "{}""#, EscapedString(m))),
             ),
 
-            TrIgnored => self.write_event("test", desc.name.as_slice(), "ignored", None),
+            TrTimedFail => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                Some(r#""reason": "time limit exceeded""#.to_owned()),
+            ),
+
+            TrIgnored => self.write_event("test", desc.name.as_slice(), "ignored", None, None),
 
-            TrAllowedFail => {
-                self.write_event("test", desc.name.as_slice(), "allowed_failure", None)
-            }
+            TrAllowedFail => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "allowed_failure",
+                exec_time,
+                None,
+            ),
 
             TrBench(ref bs) => {
                 let median = bs.ns_iter_summ.median as usize;
@@ -0,0 +1,203 @@
+use super::*;
+
+use std::collections::HashMap;
+
+pub(crate) struct JunitFormatter<T> {
+    out: OutputLocation<T>,
+
+    /// Time at which the whole run started, used for the suite `time` attribute
+    run_start: Option<Instant>,
+    /// Start time of every test that has not reported a result yet
+    running: HashMap<String, Instant>,
+    /// Results are buffered, as the suite element needs the final counts
+    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+}
+
+impl<T: Write> JunitFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self {
+            out,
+            run_start: None,
+            running: HashMap::new(),
+            results: Vec::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_message(&mut self, s: &str) -> io::Result<()> {
+        self.out.write_all(s.as_ref())?;
+        self.out.write_all(b"\n")
+    }
+
+    fn write_testcase(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        duration: Duration,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        // Most tools split the `classname` on dots to build a tree, so the module
+        // path of the test goes there and only the last segment is used as name.
+        let full_name = desc.name.as_slice().trim_end();
+        let (class_name, test_name) = match full_name.rfind("::") {
+            Some(idx) => (&full_name[..idx], &full_name[idx + 2..]),
+            None => ("", full_name),
+        };
+        let class_name = class_name.replace("::", ".");
+
+        let open = format!(
+            r#"    <testcase classname="{}" name="{}" time="{}""#,
+            EscapedString(&class_name),
+            EscapedString(test_name),
+            fmt_seconds(duration)
+        );
+
+        let body = match *result {
+            TrOk | TrBench(_) => None,
+            TrIgnored => Some("      <skipped/>".to_owned()),
+            TrAllowedFail => Some(r#"      <skipped message="failed (allowed)"/>"#.to_owned()),
+            TrFailed => Some(r#"      <failure type="failed"/>"#.to_owned()),
+            TrTimedFail => {
+                Some(r#"      <failure type="timeout" message="time limit exceeded"/>"#.to_owned())
+            }
+            TrFailedMsg(ref m) => Some(format!(
+                r#"      <failure type="failed" message="{}"/>"#,
+                EscapedString(m)
+            )),
+        };
+
+        if body.is_none() && stdout.is_empty() {
+            return self.write_message(&format!("{}/>", open));
+        }
+
+        self.write_message(&format!("{}>", open))?;
+        if let Some(body) = body {
+            self.write_message(&body)?;
+        }
+        if !stdout.is_empty() {
+            self.write_message(&format!(
+                "      <system-out>{}</system-out>",
+                EscapedString(String::from_utf8_lossy(stdout))
+            ))?;
+        }
+        self.write_message("    </testcase>")
+    }
+}
+
+impl<T: Write> OutputFormatter for JunitFormatter<T> {
+    fn write_run_start(&mut self, _test_count: usize, _: Option<u64>) -> io::Result<()> {
+        self.run_start = Some(Instant::now());
+        Ok(())
+    }
+
+    fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.running
+            .insert(desc.name.as_slice().to_owned(), Instant::now());
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // The report is only written at the end of the run, so there is
+        // nothing useful to say about a test that is still running.
+        Ok(())
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&TestExecTime>,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        // Prefer the time measured around the test function itself, which
+        // `--report-time` enables, over the time observed by the formatter.
+        let started = self.running.remove(desc.name.as_slice());
+        let duration = match exec_time {
+            Some(exec_time) => exec_time.0,
+            None => started.map(|start| start.elapsed()).unwrap_or_default(),
+        };
+        self.results
+            .push((desc.clone(), result.clone(), duration, stdout.to_vec()));
+        Ok(())
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let duration = self
+            .run_start
+            .map(|start| start.elapsed())
+            .unwrap_or_default();
+
+        self.write_message(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        self.write_message("<testsuites>")?;
+        self.write_message(&format!(
+            concat!(
+                r#"  <testsuite name="test" package="test" id="0" tests="{}" errors="0" "#,
+                r#"failures="{}" skipped="{}" time="{}">"#
+            ),
+            state.total,
+            state.failed,
+            state.ignored + state.allowed_fail,
+            fmt_seconds(duration)
+        ))?;
+
+        let results = std::mem::replace(&mut self.results, Vec::new());
+        for (desc, result, duration, stdout) in &results {
+            self.write_testcase(desc, result, *duration, stdout)?;
+        }
+
+        self.write_message("  </testsuite>")?;
+        self.write_message("</testsuites>")?;
+
+        Ok(state.failed == 0)
+    }
+}
+
+fn fmt_seconds(duration: Duration) -> String {
+    format!("{}.{:03}", duration.as_secs(), duration.subsec_millis())
+}
+
+/// A formatting utility used to print strings as XML text or attribute values.
+/// Characters that cannot appear in an XML 1.0 document at all are replaced.
+struct EscapedString<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> ::std::fmt::Display for EscapedString<S> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        let s = self.0.as_ref();
+        let mut start = 0;
+
+        for (i, byte) in s.bytes().enumerate() {
+            let escaped = match byte {
+                b'<' => "&lt;",
+                b'>' => "&gt;",
+                b'&' => "&amp;",
+                b'"' => "&quot;",
+                b'\'' => "&apos;",
+                b'\t' | b'\n' | b'\r' => {
+                    continue;
+                }
+                b'\x00'..=b'\x1f' => "\u{fffd}",
+                _ => {
+                    continue;
+                }
+            };
+
+            if start < i {
+                f.write_str(&s[start..i])?;
+            }
+
+            f.write_str(escaped)?;
+
+            start = i + 1;
+        }
+
+        if start != s.len() {
+            f.write_str(&s[start..])?;
+        }
+
+        Ok(())
+    }
+}
@@ -8,6 +8,10 @@ pub(crate) struct PrettyFormatter<T> {
     max_name_len: usize,
 
     is_multithreaded: bool,
+
+    time_options: Option<TestTimeOptions>,
+    /// Tests which exceeded the warn threshold, listed at the end of the run
+    slow_tests: Vec<(String, TestExecTime)>,
 }
 
 impl<T: Write> PrettyFormatter<T> {
@@ -16,12 +20,15 @@ impl<T: Write> PrettyFormatter<T> {
         use_color: bool,
         max_name_len: usize,
         is_multithreaded: bool,
+        time_options: Option<TestTimeOptions>,
     ) -> Self {
         PrettyFormatter {
             out,
             use_color,
             max_name_len,
             is_multithreaded,
+            time_options,
+            slow_tests: Vec::new(),
         }
     }
 
@@ -46,6 +53,10 @@ impl<T: Write> PrettyFormatter<T> {
         self.write_short_result("FAILED (allowed)", term::color::YELLOW)
     }
 
+    pub fn write_time_failed(&mut self) -> io::Result<()> {
+        self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -55,8 +66,56 @@ impl<T: Write> PrettyFormatter<T> {
         result: &str,
         color: term::color::Color,
     ) -> io::Result<()> {
-        self.write_pretty(result, color)?;
-        self.write_plain("\n")
+        self.write_pretty(result, color)
+    }
+
+    fn write_time(
+        &mut self,
+        desc: &TestDesc,
+        exec_time: Option<&TestExecTime>,
+    ) -> io::Result<()> {
+        let (opts, exec_time) = match (self.time_options, exec_time) {
+            (Some(opts), Some(exec_time)) => (opts, exec_time),
+            _ => return Ok(()),
+        };
+
+        let time_str = format!(" <{}>", exec_time);
+        if opts.is_warn(exec_time) {
+            self.slow_tests.push((desc.name.to_string(), *exec_time));
+            let color = if opts.is_critical(exec_time) {
+                term::color::RED
+            } else {
+                term::color::YELLOW
+            };
+            self.write_pretty(&time_str, color)
+        } else {
+            self.write_plain(&time_str)
+        }
+    }
+
+    pub fn write_slow_tests(&mut self) -> io::Result<()> {
+        let warn_threshold = match self.time_options {
+            Some(opts) => opts.warn_threshold,
+            None => return Ok(()),
+        };
+
+        let mut slow_tests = std::mem::replace(&mut self.slow_tests, Vec::new());
+        if slow_tests.is_empty() {
+            return Ok(());
+        }
+
+        self.write_plain(&format!(
+            "\nslow tests (over {}):\n",
+            TestExecTime(warn_threshold)
+        ))?;
+        // Slowest first, ties broken by name so the listing is stable.
+        slow_tests.sort_by(|(a_name, a_time), (b_name, b_time)| {
+            b_time.0.cmp(&a_time.0).then_with(|| a_name.cmp(b_name))
+        });
+        for (name, exec_time) in &slow_tests {
+            self.write_plain(&format!("    {} <{}>\n", name, exec_time))?;
+        }
+        Ok(())
     }
 
     pub fn write_pretty(&mut self, word: &str, color: term::color::Color) -> io::Result<()> {
@@ -145,9 +204,14 @@ impl<T: Write> PrettyFormatter<T> {
 }
 
 impl<T: Write> OutputFormatter for PrettyFormatter<T> {
-    fn write_run_start(&mut self, test_count: usize) -> io::Result<()> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
         let noun = if test_count != 1 { "tests" } else { "test" };
-        self.write_plain(&format!("\nrunning {} {}\n", test_count, noun))
+        let shuffle_seed_msg = if let Some(shuffle_seed) = shuffle_seed {
+            format!(" (shuffle seed: {})", shuffle_seed)
+        } else {
+            String::new()
+        };
+        self.write_plain(&format!("\nrunning {} {}{}\n", test_count, noun, shuffle_seed_msg))
     }
 
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()> {
@@ -162,21 +226,31 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
         Ok(())
     }
 
-    fn write_result(&mut self, desc: &TestDesc, result: &TestResult, _: &[u8]) -> io::Result<()> {
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&TestExecTime>,
+        _: &[u8],
+    ) -> io::Result<()> {
         if self.is_multithreaded {
             self.write_test_name(desc)?;
         }
 
         match *result {
-            TrOk => self.write_ok(),
-            TrFailed | TrFailedMsg(_) => self.write_failed(),
-            TrIgnored => self.write_ignored(),
-            TrAllowedFail => self.write_allowed_fail(),
+            TrOk => self.write_ok()?,
+            TrFailed | TrFailedMsg(_) => self.write_failed()?,
+            TrIgnored => self.write_ignored()?,
+            TrAllowedFail => self.write_allowed_fail()?,
+            TrTimedFail => self.write_time_failed()?,
             TrBench(ref bs) => {
                 self.write_bench()?;
-                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
             }
         }
+
+        self.write_time(desc, exec_time)?;
+        self.write_plain("\n")
     }
 
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
@@ -194,6 +268,7 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
+        self.write_slow_tests()?;
         let success = state.failed == 0;
         if !success {
             self.write_failures(state)?;
@@ -66,29 +66,40 @@ use std::io::prelude::*;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::process;
-use std::process::Termination;
+use std::process::{Command, ExitStatus, Stdio, Termination};
 use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const TEST_WARN_TIMEOUT_S: u64 = 60;
+const TEST_WARN_TIME_THRESHOLD_MS: u64 = 1_000; // default for --time-threshold
+const TEST_CRITICAL_TIME_THRESHOLD_MS: u64 = 5_000;
 const QUIET_MODE_MAX_COLUMN: usize = 100; // insert a '\n' after 100 tests in quiet mode
 
+// Environment variables naming the single test a re-executed test binary should
+// run when `--isolate` is used, and the file descriptor of the pipe it reports
+// the outcome of that test through.
+const SECONDARY_TEST_INVOKER_VAR: &str = "__RUST_TEST_INVOKE";
+const SECONDARY_TEST_RESULT_FD_VAR: &str = "__RUST_TEST_RESULT_FD";
+
 // to be used by rustc to compile tests in libtest
 pub mod test {
     pub use crate::{
         assert_test_result, filter_tests, parse_opts, run_test, test_main, test_main_static,
         Bencher, DynTestFn, DynTestName, Metric, MetricMap, Options, RunIgnored, ShouldPanic,
-        StaticBenchFn, StaticTestFn, StaticTestName, TestDesc, TestDescAndFn, TestName, TestOpts,
-        TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk,
+        StaticBenchFn, StaticTestFn, StaticTestName, TestDesc, TestDescAndFn, TestExecTime,
+        TestName, TestOpts, TestResult, TestTimeOptions, TrFailed, TrFailedMsg, TrIgnored, TrOk,
+        TrTimedFail,
     };
 }
 
 mod formatters;
 pub mod stats;
 
-use crate::formatters::{JsonFormatter, OutputFormatter, PrettyFormatter, TerseFormatter};
+use crate::formatters::{
+    JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter,
+};
 
 /// Whether to execute tests concurrently or not
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -232,6 +243,9 @@ pub struct TestDesc {
     pub ignore: bool,
     pub should_panic: ShouldPanic,
     pub allow_fail: bool,
+    /// Number of seconds after which the test is reported as failed, set by
+    /// `#[test(timeout = "secs")]`. Takes precedence over `--test-timeout`.
+    pub timeout: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -274,6 +288,13 @@ impl Options {
 // The default console test runner. It accepts the command line
 // arguments and a vector of test_descs.
 pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Options) {
+    // When running with `--isolate` every test is run by re-executing this
+    // binary, with the name of the test to run in the environment.
+    if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
+        env::remove_var(SECONDARY_TEST_INVOKER_VAR);
+        run_test_in_spawned_subprocess(&name, tests);
+    }
+
     let mut opts = match parse_opts(args) {
         Some(Ok(o)) => o,
         Some(Err(msg)) => {
@@ -352,6 +373,7 @@ pub enum OutputFormat {
     Pretty,
     Terse,
     Json,
+    Junit,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -376,6 +398,11 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
+    pub shuffle: bool,
+    pub shuffle_seed: Option<u64>,
+    pub isolate: bool,
+    pub test_timeout: Option<u64>,
+    pub time_options: Option<TestTimeOptions>,
     pub options: Options,
 }
 
@@ -396,6 +423,11 @@ impl TestOpts {
             format: OutputFormat::Pretty,
             test_threads: None,
             skip: vec![],
+            shuffle: false,
+            shuffle_seed: None,
+            isolate: false,
+            test_timeout: None,
+            time_options: None,
             options: Options::new(),
         }
     }
@@ -466,8 +498,54 @@ fn optgroups() -> getopts::Options {
             "Configure formatting of output:
             pretty = Print verbose output;
             terse  = Display one character per test;
-            json   = Output a json document",
-            "pretty|terse|json",
+            json   = Output a json document
+            junit  = Output a JUnit XML document",
+            "pretty|terse|json|junit",
+        )
+        .optflag(
+            "",
+            "shuffle",
+            "Run tests in random order. The seed used is printed so that the \
+             order can be reproduced with --shuffle-seed",
+        )
+        .optopt(
+            "",
+            "shuffle-seed",
+            "Run tests in random order, using the given seed (implies --shuffle)",
+            "SEED",
+        )
+        .optflag(
+            "",
+            "isolate",
+            "Run each test in its own process, by re-executing the test \
+             binary, so that a test that crashes only fails itself",
+        )
+        .optopt(
+            "",
+            "test-timeout",
+            "Report tests running for longer than SECS seconds as failed and \
+             move on, killing them when --isolate is used. Tests marked \
+             #[test(timeout = \"secs\")] use their own limit instead",
+            "SECS",
+        )
+        .optflag(
+            "",
+            "report-time",
+            "Show execution time of each test. Not available for --format=terse",
+        )
+        .optflag(
+            "",
+            "ensure-time",
+            "Treat excess of the critical execution time threshold as a test failure.
+            Implies --report-time",
+        )
+        .optopt(
+            "",
+            "time-threshold",
+            "Execution time thresholds in milliseconds, used by --report-time to
+            highlight slow tests and by --ensure-time to fail them
+            (default: 1000,5000)",
+            "WARN_MS,CRITICAL_MS",
         )
         .optopt(
             "Z",
@@ -508,7 +586,9 @@ Test Attributes:
     #[ignore]      - When applied to a function which is already attributed as a
                      test, then the test runner will ignore these tests during
                      normal test runs. Running with --ignored or --include-ignored will run
-                     these tests."#,
+                     these tests.
+    #[test(timeout = "secs")] - The test is reported as failed if it runs for
+                     longer than `secs` seconds, overriding --test-timeout."#,
         usage = options.usage(&message)
     );
 }
@@ -585,6 +665,65 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         (false, true) => RunIgnored::Only,
         (false, false) => RunIgnored::No,
     };
+    let time_options = match get_time_options(&matches, allow_unstable) {
+        Ok(time_options) => time_options,
+        Err(e) => return Some(Err(e)),
+    };
+
+    let shuffle = matches.opt_present("shuffle");
+    let shuffle_seed = match matches.opt_str("shuffle-seed") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(n) => Some(n),
+            Err(e) => {
+                return Some(Err(format!(
+                    "argument for --shuffle-seed must be a number \
+                     (error: {})",
+                    e
+                )));
+            }
+        },
+        None => None,
+    };
+    if !allow_unstable && (shuffle || shuffle_seed.is_some()) {
+        return Some(Err(
+            "The \"shuffle\" and \"shuffle-seed\" flags are only accepted on the \
+             nightly compiler"
+                .into(),
+        ));
+    }
+
+    let isolate = matches.opt_present("isolate");
+    if !allow_unstable && isolate {
+        return Some(Err(
+            "The \"isolate\" flag is only accepted on the nightly compiler".into(),
+        ));
+    }
+    if isolate && !cfg!(unix) {
+        return Some(Err(
+            "The \"isolate\" flag is only supported on Unix platforms".into(),
+        ));
+    }
+
+    let test_timeout = match matches.opt_str("test-timeout") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(0) => return Some(Err("argument for --test-timeout must not be 0".to_string())),
+            Ok(n) => Some(n),
+            Err(e) => {
+                return Some(Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {})",
+                    e
+                )));
+            }
+        },
+        None => None,
+    };
+    if !allow_unstable && test_timeout.is_some() {
+        return Some(Err(
+            "The \"test-timeout\" flag is only accepted on the nightly compiler".into(),
+        ));
+    }
+
     let quiet = matches.opt_present("quiet");
     let exact = matches.opt_present("exact");
     let list = matches.opt_present("list");
@@ -644,10 +783,18 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
             }
             OutputFormat::Json
         }
+        Some("junit") => {
+            if !allow_unstable {
+                return Some(Err(
+                    "The \"junit\" format is only accepted on the nightly compiler".into(),
+                ));
+            }
+            OutputFormat::Junit
+        }
 
         Some(v) => {
             return Some(Err(format!(
-                "argument for --format must be pretty, terse, or json (was \
+                "argument for --format must be pretty, terse, json, or junit (was \
                  {})",
                 v
             )));
@@ -668,12 +815,75 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         format,
         test_threads,
         skip: matches.opt_strs("skip"),
+        shuffle,
+        shuffle_seed,
+        isolate,
+        test_timeout,
+        time_options,
         options: Options::new(),
     };
 
     Some(Ok(test_opts))
 }
 
+fn get_time_options(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> Result<Option<TestTimeOptions>, String> {
+    let report_time = matches.opt_present("report-time");
+    let ensure_time = matches.opt_present("ensure-time");
+    let threshold = matches.opt_str("time-threshold");
+
+    if !report_time && !ensure_time {
+        if threshold.is_some() {
+            return Err("--time-threshold requires --report-time or --ensure-time".into());
+        }
+        return Ok(None);
+    }
+    if !allow_unstable {
+        return Err(
+            "The \"report-time\" and \"ensure-time\" flags are only accepted on the \
+             nightly compiler"
+                .into(),
+        );
+    }
+
+    let (warn_ms, critical_ms) = match threshold {
+        Some(threshold) => {
+            let parse = |ms: &str| {
+                ms.trim().parse::<u64>().map_err(|e| {
+                    format!(
+                        "argument for --time-threshold must be WARN_MS,CRITICAL_MS \
+                         (error: {})",
+                        e
+                    )
+                })
+            };
+            let mut parts = threshold.splitn(2, ',');
+            let warn_ms = parse(parts.next().unwrap())?;
+            let critical_ms = match parts.next() {
+                Some(ms) => parse(ms)?,
+                None => cmp::max(warn_ms, TEST_CRITICAL_TIME_THRESHOLD_MS),
+            };
+            if critical_ms < warn_ms {
+                return Err(format!(
+                    "critical time threshold ({}ms) must not be less than the warn \
+                     threshold ({}ms)",
+                    critical_ms, warn_ms
+                ));
+            }
+            (warn_ms, critical_ms)
+        }
+        None => (TEST_WARN_TIME_THRESHOLD_MS, TEST_CRITICAL_TIME_THRESHOLD_MS),
+    };
+
+    Ok(Some(TestTimeOptions {
+        error_on_excess: ensure_time,
+        warn_threshold: Duration::from_millis(warn_ms),
+        critical_threshold: Duration::from_millis(critical_ms),
+    }))
+}
+
 #[derive(Clone, PartialEq)]
 pub struct BenchSamples {
     ns_iter_summ: stats::Summary,
@@ -688,10 +898,47 @@ pub enum TestResult {
     TrIgnored,
     TrAllowedFail,
     TrBench(BenchSamples),
+    TrTimedFail,
 }
 
 unsafe impl Send for TestResult {}
 
+/// The measured execution time of a unit test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TestExecTime(pub Duration);
+
+impl TestExecTime {
+    fn as_secs_f64(&self) -> f64 {
+        self.0.as_secs() as f64 + self.0.subsec_nanos() as f64 / 1_000_000_000.0
+    }
+}
+
+impl fmt::Display for TestExecTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:03}s", self.0.as_secs(), self.0.subsec_millis())
+    }
+}
+
+/// Thresholds used to report and, optionally, fail slow tests.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TestTimeOptions {
+    /// Whether a test that exceeds `critical_threshold` should be reported
+    /// as failed rather than only being highlighted.
+    pub error_on_excess: bool,
+    pub warn_threshold: Duration,
+    pub critical_threshold: Duration,
+}
+
+impl TestTimeOptions {
+    pub fn is_warn(&self, exec_time: &TestExecTime) -> bool {
+        exec_time.0 >= self.warn_threshold
+    }
+
+    pub fn is_critical(&self, exec_time: &TestExecTime) -> bool {
+        exec_time.0 >= self.critical_threshold
+    }
+}
+
 enum OutputLocation<T> {
     Pretty(Box<term::StdoutTerminal>),
     Raw(T),
@@ -769,6 +1016,7 @@ impl ConsoleTestState {
                 TrIgnored => "ignored".to_owned(),
                 TrAllowedFail => "failed (allowed)".to_owned(),
                 TrBench(ref bs) => fmt_bench_samples(bs),
+                TrTimedFail => "failed (time limit exceeded)".to_owned(),
             },
             test.name
         ))
@@ -892,16 +1140,16 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         out: &mut dyn OutputFormatter,
     ) -> io::Result<()> {
         match (*event).clone() {
-            TeFiltered(ref filtered_tests) => {
+            TeFiltered(ref filtered_tests, shuffle_seed) => {
                 st.total = filtered_tests.len();
-                out.write_run_start(filtered_tests.len())
+                out.write_run_start(filtered_tests.len(), shuffle_seed)
             }
             TeFilteredOut(filtered_out) => Ok(st.filtered_out = filtered_out),
             TeWait(ref test) => out.write_test_start(test),
             TeTimeout(ref test) => out.write_timeout(test),
-            TeResult(test, result, stdout) => {
+            TeResult(test, result, exec_time, stdout) => {
                 st.write_log_result(&test, &result)?;
-                out.write_result(&test, &result, &*stdout)?;
+                out.write_result(&test, &result, exec_time.as_ref(), &*stdout)?;
                 match result {
                     TrOk => {
                         st.passed += 1;
@@ -927,6 +1175,20 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                         stdout.extend_from_slice(format!("note: {}", msg).as_bytes());
                         st.failures.push((test, stdout));
                     }
+                    TrTimedFail => {
+                        st.failed += 1;
+                        let mut stdout = stdout;
+                        if let Some(exec_time) = exec_time {
+                            stdout.extend_from_slice(
+                                format!(
+                                    "note: test took {}, exceeding the critical time threshold",
+                                    exec_time
+                                )
+                                .as_bytes(),
+                            );
+                        }
+                        st.failures.push((test, stdout));
+                    }
                 }
                 Ok(())
             }
@@ -952,6 +1214,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             use_color(opts),
             max_name_len,
             is_multithreaded,
+            opts.time_options,
         )),
         OutputFormat::Terse => Box::new(TerseFormatter::new(
             output,
@@ -960,6 +1223,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             is_multithreaded,
         )),
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
+        OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
     fn len_if_padded(t: &TestDescAndFn) -> usize {
@@ -983,6 +1247,7 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
     };
 
     let test_b = TestDesc {
@@ -990,9 +1255,10 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
     };
 
-    let mut out = PrettyFormatter::new(Raw(Vec::new()), false, 10, false);
+    let mut out = PrettyFormatter::new(Raw(Vec::new()), false, 10, false, None);
 
     let st = ConsoleTestState {
         log_out: None,
@@ -1020,6 +1286,62 @@ fn should_sort_failures_before_printing_them() {
     assert!(apos < bpos);
 }
 
+#[test]
+fn junit_report_contains_failures_and_output() {
+    let test_a = TestDesc {
+        name: StaticTestName("module::a"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: false,
+        timeout: None,
+    };
+
+    let test_b = TestDesc {
+        name: StaticTestName("b"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: false,
+        timeout: None,
+    };
+
+    let mut out = JunitFormatter::new(Raw(Vec::new()));
+    out.write_run_start(2, None).unwrap();
+    out.write_test_start(&test_a).unwrap();
+    out.write_result(&test_a, &TrOk, None, b"").unwrap();
+    out.write_test_start(&test_b).unwrap();
+    let exec_time = TestExecTime(Duration::from_millis(1_500));
+    out.write_result(&test_b, &TrFailedMsg("a < b".to_owned()), Some(&exec_time), b"out & about")
+        .unwrap();
+
+    let st = ConsoleTestState {
+        log_out: None,
+        total: 2,
+        passed: 1,
+        failed: 1,
+        ignored: 0,
+        allowed_fail: 0,
+        filtered_out: 0,
+        measured: 0,
+        metrics: MetricMap::new(),
+        failures: Vec::new(),
+        options: Options::new(),
+        not_failures: Vec::new(),
+    };
+
+    assert!(!out.write_run_finish(&st).unwrap());
+    let s = match out.output_location() {
+        &Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        &Pretty(_) => unreachable!(),
+    };
+
+    assert!(s.starts_with("<?xml"));
+    assert!(s.contains(r#"tests="2" errors="0" failures="1" skipped="0""#));
+    assert!(s.contains(r#"<testcase classname="module" name="a" time=""#));
+    assert!(s.contains(r#"<testcase classname="" name="b" time="1.500">"#));
+    assert!(s.contains(r#"<failure type="failed" message="a &lt; b"/>"#));
+    assert!(s.contains("<system-out>out &amp; about</system-out>"));
+}
+
 fn use_color(opts: &TestOpts) -> bool {
     match opts.color {
         AutoColor => !opts.nocapture && stdout_isatty(),
@@ -1062,14 +1384,14 @@ fn stdout_isatty() -> bool {
 
 #[derive(Clone)]
 pub enum TestEvent {
-    TeFiltered(Vec<TestDesc>),
+    TeFiltered(Vec<TestDesc>, Option<u64>),
     TeWait(TestDesc),
-    TeResult(TestDesc, TestResult, Vec<u8>),
+    TeResult(TestDesc, TestResult, Option<TestExecTime>, Vec<u8>),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
 }
 
-pub type MonitorMsg = (TestDesc, TestResult, Vec<u8>);
+pub type MonitorMsg = (TestDesc, TestResult, Option<TestExecTime>, Vec<u8>);
 
 struct Sink(Arc<Mutex<Vec<u8>>>);
 impl Write for Sink {
@@ -1090,7 +1412,23 @@ where
     use std::sync::mpsc::RecvTimeoutError;
     // Use a deterministic hasher
     type TestMap =
-        HashMap<TestDesc, Instant, BuildHasherDefault<collections::hash_map::DefaultHasher>>;
+        HashMap<TestDesc, RunningTest, BuildHasherDefault<collections::hash_map::DefaultHasher>>;
+
+    struct RunningTest {
+        // When to warn that the test is still running, until the warning is given.
+        warn_at: Option<Instant>,
+        // When to give up on the test, and the timeout it was given.
+        deadline: Option<(Instant, Duration)>,
+    }
+
+    impl RunningTest {
+        fn next_event(&self) -> Option<Instant> {
+            match (self.warn_at, self.deadline) {
+                (Some(warn_at), Some((deadline, _))) => Some(cmp::min(warn_at, deadline)),
+                (warn_at, deadline) => warn_at.or(deadline.map(|(deadline, _)| deadline)),
+            }
+        }
+    }
 
     let tests_len = tests.len();
 
@@ -1108,12 +1446,18 @@ where
         filtered_tests
     };
 
+    let shuffle_seed = get_shuffle_seed(opts);
+    let filtered_tests = match shuffle_seed {
+        Some(shuffle_seed) => shuffle_tests(shuffle_seed, filtered_tests),
+        None => filtered_tests,
+    };
+
     let filtered_out = tests_len - filtered_tests.len();
     callback(TeFilteredOut(filtered_out))?;
 
     let filtered_descs = filtered_tests.iter().map(|t| t.desc.clone()).collect();
 
-    callback(TeFiltered(filtered_descs))?;
+    callback(TeFiltered(filtered_descs, shuffle_seed))?;
 
     let (filtered_tests, filtered_benchs): (Vec<_>, _) =
         filtered_tests.into_iter().partition(|e| match e.testfn {
@@ -1131,49 +1475,90 @@ where
 
     let mut running_tests: TestMap = HashMap::default();
 
-    fn get_timed_out_tests(running_tests: &mut TestMap) -> Vec<TestDesc> {
+    // Returns the tests to warn about, and the tests that ran out of time,
+    // which are no longer considered running.
+    fn get_timed_out_tests(
+        running_tests: &mut TestMap,
+    ) -> (Vec<TestDesc>, Vec<(TestDesc, Duration)>) {
         let now = Instant::now();
-        let timed_out = running_tests
-            .iter()
-            .filter_map(|(desc, timeout)| {
-                if &now >= timeout {
-                    Some(desc.clone())
-                } else {
-                    None
+        let mut warned = Vec::new();
+        let mut expired = Vec::new();
+        for (desc, running) in running_tests.iter_mut() {
+            match running.deadline {
+                Some((deadline, timeout)) if now >= deadline => {
+                    expired.push((desc.clone(), timeout));
+                    continue;
                 }
-            })
-            .collect();
-        for test in &timed_out {
+                _ => {}
+            }
+            match running.warn_at {
+                Some(warn_at) if now >= warn_at => {
+                    running.warn_at = None;
+                    warned.push(desc.clone());
+                }
+                _ => {}
+            }
+        }
+        for (test, _) in &expired {
             running_tests.remove(test);
         }
-        timed_out
+        (warned, expired)
     };
 
     fn calc_timeout(running_tests: &TestMap) -> Option<Duration> {
-        running_tests.values().min().map(|next_timeout| {
-            let now = Instant::now();
-            if *next_timeout >= now {
-                *next_timeout - now
-            } else {
-                Duration::new(0, 0)
-            }
-        })
+        running_tests
+            .values()
+            .filter_map(|test| test.next_event())
+            .min()
+            .map(|next_timeout| {
+                let now = Instant::now();
+                if next_timeout >= now {
+                    next_timeout - now
+                } else {
+                    Duration::new(0, 0)
+                }
+            })
     };
 
-    if concurrency == 1 {
+    // With `--isolate` the timeout is enforced by killing the test process,
+    // otherwise the runner stops waiting for the test and moves on.
+    let in_process_timeout = |desc: &TestDesc| {
+        if opts.isolate {
+            None
+        } else {
+            test_timeout(opts, desc)
+        }
+    };
+
+    // Tests that can time out have to run on their own thread, even when
+    // running serially, so that the runner is able to give up on them.
+    let any_timeout = remaining
+        .iter()
+        .any(|test| in_process_timeout(&test.desc).is_some());
+
+    if concurrency == 1 && !any_timeout {
         while !remaining.is_empty() {
             let test = remaining.pop().unwrap();
             callback(TeWait(test.desc.clone()))?;
             run_test(opts, !opts.run_tests, test, tx.clone(), Concurrent::No);
-            let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            let (test, result, exec_time, stdout) = rx.recv().unwrap();
+            callback(TeResult(test, result, exec_time, stdout))?;
         }
     } else {
         while pending > 0 || !remaining.is_empty() {
             while pending < concurrency && !remaining.is_empty() {
                 let test = remaining.pop().unwrap();
-                let timeout = Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S);
-                running_tests.insert(test.desc.clone(), timeout);
+                let now = Instant::now();
+                // When running serially the name of the test is printed as it
+                // starts, so there is no need for a warning.
+                let warn_at = if concurrency > 1 {
+                    Some(now + Duration::from_secs(TEST_WARN_TIMEOUT_S))
+                } else {
+                    None
+                };
+                let deadline =
+                    in_process_timeout(&test.desc).map(|timeout| (now + timeout, timeout));
+                running_tests.insert(test.desc.clone(), RunningTest { warn_at, deadline });
                 callback(TeWait(test.desc.clone()))?; //here no pad
                 run_test(opts, !opts.run_tests, test, tx.clone(), Concurrent::Yes);
                 pending += 1;
@@ -1183,10 +1568,17 @@ where
             loop {
                 if let Some(timeout) = calc_timeout(&running_tests) {
                     res = rx.recv_timeout(timeout);
-                    for test in get_timed_out_tests(&mut running_tests) {
+                    let (warned, expired) = get_timed_out_tests(&mut running_tests);
+                    for test in warned {
                         callback(TeTimeout(test))?;
                     }
-                    if res != Err(RecvTimeoutError::Timeout) {
+                    let any_expired = !expired.is_empty();
+                    for (test, timeout) in expired {
+                        let result = calc_timed_out_result(&test, timeout, false);
+                        callback(TeResult(test, result, None, Vec::new()))?;
+                        pending -= 1;
+                    }
+                    if res != Err(RecvTimeoutError::Timeout) || any_expired {
                         break;
                     }
                 } else {
@@ -1195,11 +1587,17 @@ where
                 }
             }
 
-            let (desc, result, stdout) = res.unwrap();
-            running_tests.remove(&desc);
+            if res == Err(RecvTimeoutError::Timeout) {
+                continue;
+            }
 
-            callback(TeResult(desc, result, stdout))?;
-            pending -= 1;
+            let (desc, result, exec_time, stdout) = res.unwrap();
+            // A test that was already reported as timed out may still finish
+            // later on, its result is ignored then.
+            if running_tests.remove(&desc).is_some() {
+                callback(TeResult(desc, result, exec_time, stdout))?;
+                pending -= 1;
+            }
         }
     }
 
@@ -1208,8 +1606,8 @@ where
         for b in filtered_benchs {
             callback(TeWait(b.desc.clone()))?;
             run_test(opts, false, b, tx.clone(), Concurrent::No);
-            let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            let (test, result, exec_time, stdout) = rx.recv().unwrap();
+            callback(TeResult(test, result, exec_time, stdout))?;
         }
     }
     Ok(())
@@ -1356,6 +1754,11 @@ fn get_concurrency() -> usize {
     }
 }
 
+/// The time after which `desc` is reported as failed, if it has a timeout.
+fn test_timeout(opts: &TestOpts, desc: &TestDesc) -> Option<Duration> {
+    desc.timeout.or(opts.test_timeout).map(Duration::from_secs)
+}
+
 pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     let mut filtered = tests;
     let matches_filter = |test: &TestDescAndFn, filter: &str| {
@@ -1402,6 +1805,42 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     filtered
 }
 
+fn get_shuffle_seed(opts: &TestOpts) -> Option<u64> {
+    match opts.shuffle_seed {
+        Some(seed) => Some(seed),
+        None if opts.shuffle => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time should be after the unix epoch");
+            Some(now.as_secs() ^ u64::from(now.subsec_nanos()))
+        }
+        None => None,
+    }
+}
+
+/// Shuffles the tests with a Fisher-Yates shuffle driven by `shuffle_seed`.
+///
+/// The random numbers come from splitmix64, which is fully specified by its
+/// arithmetic on `u64`s, so the same seed and the same set of filtered tests
+/// always produce the same order, on every platform and with every release.
+pub fn shuffle_tests(shuffle_seed: u64, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
+    fn next_u64(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    let mut tests = tests;
+    let mut state = shuffle_seed;
+    for i in (1..tests.len()).rev() {
+        let j = (next_u64(&mut state) % (i as u64 + 1)) as usize;
+        tests.swap(i, j);
+    }
+    tests
+}
+
 pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     // convert benchmarks to tests, if we're not benchmarking them
     tests
@@ -1438,44 +1877,29 @@ pub fn run_test(
         && desc.should_panic != ShouldPanic::No;
 
     if force_ignore || desc.ignore || ignore_because_panic_abort {
-        monitor_ch.send((desc, TrIgnored, Vec::new())).unwrap();
+        monitor_ch.send((desc, TrIgnored, None, Vec::new())).unwrap();
         return;
     }
 
     fn run_test_inner(
         desc: TestDesc,
         monitor_ch: Sender<MonitorMsg>,
-        nocapture: bool,
+        opts: &TestOpts,
         testfn: Box<dyn FnBox() + Send>,
         concurrency: Concurrent,
     ) {
-        // Buffer for capturing standard I/O
-        let data = Arc::new(Mutex::new(Vec::new()));
-        let data2 = data.clone();
+        let nocapture = opts.nocapture;
+        let time_opts = opts.time_options;
+        let isolate = opts.isolate;
+        let timeout = test_timeout(opts, &desc);
 
         let name = desc.name.clone();
         let runtest = move || {
-            let oldio = if !nocapture {
-                Some((
-                    io::set_print(Some(Box::new(Sink(data2.clone())))),
-                    io::set_panic(Some(Box::new(Sink(data2)))),
-                ))
+            if isolate {
+                spawn_test_subprocess(desc, nocapture, time_opts, timeout, monitor_ch);
             } else {
-                None
-            };
-
-            let result = catch_unwind(AssertUnwindSafe(testfn));
-
-            if let Some((printio, panicio)) = oldio {
-                io::set_print(printio);
-                io::set_panic(panicio);
-            };
-
-            let test_result = calc_result(&desc, result);
-            let stdout = data.lock().unwrap().to_vec();
-            monitor_ch
-                .send((desc.clone(), test_result, stdout))
-                .unwrap();
+                run_test_in_process(desc, nocapture, time_opts, testfn, monitor_ch);
+            }
         };
 
         // If the platform is single-threaded we're just going to run
@@ -1503,26 +1927,303 @@ pub fn run_test(
         }
         DynTestFn(f) => {
             let cb = move || __rust_begin_short_backtrace(f);
-            run_test_inner(desc, monitor_ch, opts.nocapture, Box::new(cb), concurrency)
+            run_test_inner(desc, monitor_ch, opts, Box::new(cb), concurrency)
         }
         StaticTestFn(f) => run_test_inner(
             desc,
             monitor_ch,
-            opts.nocapture,
+            opts,
             Box::new(move || __rust_begin_short_backtrace(f)),
             concurrency,
         ),
     }
 }
 
+fn run_test_in_process(
+    desc: TestDesc,
+    nocapture: bool,
+    time_opts: Option<TestTimeOptions>,
+    testfn: Box<dyn FnBox() + Send>,
+    monitor_ch: Sender<MonitorMsg>,
+) {
+    // Buffer for capturing standard I/O
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let data2 = data.clone();
+
+    let oldio = if !nocapture {
+        Some((
+            io::set_print(Some(Box::new(Sink(data2.clone())))),
+            io::set_panic(Some(Box::new(Sink(data2)))),
+        ))
+    } else {
+        None
+    };
+
+    let start = if time_opts.is_some() {
+        Some(Instant::now())
+    } else {
+        None
+    };
+    let result = catch_unwind(AssertUnwindSafe(testfn));
+    let exec_time = start.map(|start| TestExecTime(start.elapsed()));
+
+    if let Some((printio, panicio)) = oldio {
+        io::set_print(printio);
+        io::set_panic(panicio);
+    };
+
+    let test_result = calc_result(&desc, result, &time_opts, &exec_time);
+    let stdout = data.lock().unwrap().to_vec();
+    // The runner stops listening for tests that timed out, and may even be
+    // finished with the whole run already.
+    let _ = monitor_ch.send((desc.clone(), test_result, exec_time, stdout));
+}
+
+/// Runs a single test by re-executing the current binary, which reports the
+/// result of the test through a dedicated pipe. Both output streams of the
+/// child are captured through pipes, stdout first. The process is killed if
+/// it is still running once `timeout` expires.
+fn spawn_test_subprocess(
+    desc: TestDesc,
+    nocapture: bool,
+    time_opts: Option<TestTimeOptions>,
+    timeout: Option<Duration>,
+    monitor_ch: Sender<MonitorMsg>,
+) {
+    let (result, exec_time, test_output) = (|| {
+        let current_exe = match env::current_exe() {
+            Ok(path) => path,
+            Err(e) => {
+                let err = format!("failed to find the test binary to re-execute: {}", e);
+                return (TrFailedMsg(err), None, Vec::new());
+            }
+        };
+
+        let mut command = Command::new(&current_exe);
+        command.env(SECONDARY_TEST_INVOKER_VAR, desc.name.as_slice());
+        if nocapture {
+            command.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        } else {
+            command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        let (result_reader, result_writer) = match result_pipe(&mut command) {
+            Ok(pipe) => pipe,
+            Err(e) => {
+                let err = format!("failed to create a pipe for the test result: {}", e);
+                return (TrFailedMsg(err), None, Vec::new());
+            }
+        };
+        let report = read_in_background(result_reader);
+
+        let start = if time_opts.is_some() {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        let output = output_with_timeout(&mut command, timeout, result_writer);
+        // The parent's copy of the writing end is closed by now, so this only
+        // waits for the child to exit.
+        let report = report.join().unwrap_or_default();
+        let (status, test_output) = match output {
+            Ok(output) => output,
+            Err(e) => {
+                let err = format!(
+                    "failed to spawn {} as a child process for the test: {}",
+                    current_exe.display(),
+                    e
+                );
+                return (TrFailedMsg(err), None, Vec::new());
+            }
+        };
+        let exec_time = start.map(|start| TestExecTime(start.elapsed()));
+
+        let result = match (status, timeout) {
+            (Some(status), _) => {
+                calc_result_from_report(&desc, status, &report, &time_opts, &exec_time)
+            }
+            (None, Some(timeout)) => calc_timed_out_result(&desc, timeout, true),
+            (None, None) => unreachable!(),
+        };
+        (result, exec_time, test_output)
+    })();
+
+    monitor_ch
+        .send((desc, result, exec_time, test_output))
+        .unwrap();
+}
+
+fn read_in_background<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Waits for the process spawned from `command` to exit and collects its
+/// output. `result_writer` is the end of the result pipe that the process
+/// inherits, it is closed as soon as the process is spawned. Returns `None` as
+/// the exit status if the process had to be killed because it was still
+/// running once `timeout` expired.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+    result_writer: File,
+) -> io::Result<(Option<ExitStatus>, Vec<u8>)> {
+    let spawned = command.spawn();
+    drop(result_writer);
+    let mut child = spawned?;
+    // Both pipes are drained while waiting, so that a chatty test cannot
+    // block on a full pipe.
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    let status = match timeout {
+        None => Some(child.wait()?),
+        Some(timeout) => {
+            let deadline = Instant::now() + timeout;
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break Some(status);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    // The process may have exited in the meantime.
+                    let _ = child.kill();
+                    child.wait()?;
+                    break None;
+                }
+                thread::sleep(cmp::min(deadline - now, Duration::from_millis(10)));
+            }
+        }
+    };
+
+    let mut output = Vec::new();
+    for reader in stdout.into_iter().chain(stderr) {
+        output.extend(reader.join().unwrap_or_default());
+    }
+    Ok((status, output))
+}
+
+/// Entry point of a test binary that was re-executed by `spawn_test_subprocess`.
+fn run_test_in_spawned_subprocess(name: &str, tests: Vec<TestDescAndFn>) -> ! {
+    let mut result_pipe = take_result_pipe().unwrap_or_else(|e| {
+        eprintln!("error: failed to open the pipe for the test result: {}", e);
+        process::exit(101)
+    });
+    let test = convert_benchmarks_to_tests(tests)
+        .into_iter()
+        .find(|test| test.desc.name.as_slice() == name)
+        .unwrap_or_else(|| panic!("couldn't find a test with the provided name '{}'", name));
+    let TestDescAndFn { desc, testfn } = test;
+    let testfn: Box<dyn FnBox() + Send> = match testfn {
+        StaticTestFn(f) => Box::new(move || __rust_begin_short_backtrace(f)),
+        DynTestFn(f) => Box::new(move || __rust_begin_short_backtrace(f)),
+        StaticBenchFn(..) | DynBenchFn(..) => unreachable!(),
+    };
+
+    let result = catch_unwind(AssertUnwindSafe(testfn));
+    let report = match calc_result(&desc, result, &None, &None) {
+        TrOk => "ok".to_owned(),
+        TrAllowedFail => "allowed-fail".to_owned(),
+        TrFailedMsg(msg) => format!("failed\n{}", msg),
+        _ => "failed".to_owned(),
+    };
+    if let Err(e) = result_pipe.write_all(report.as_bytes()) {
+        eprintln!("error: failed to report the test result: {}", e);
+        process::exit(101);
+    }
+    process::exit(0)
+}
+
+/// Creates the pipe a re-executed test binary reports the result of its test
+/// through, and sets up `command` so that the process inherits the writing
+/// end. Returns the reading and writing ends.
+#[cfg(unix)]
+fn result_pipe(command: &mut Command) -> io::Result<(File, File)> {
+    use std::os::unix::io::FromRawFd;
+    use std::os::unix::process::CommandExt;
+
+    let mut fds = [0; 2];
+    // Both ends are close-on-exec, so that the tests spawned concurrently
+    // don't inherit them and keep the pipe open. `pipe2` sets the flag
+    // atomically where it is available.
+    #[cfg(target_os = "linux")]
+    let ret = unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) };
+    #[cfg(not(target_os = "linux"))]
+    let ret = unsafe { libc::pipe(fds.as_mut_ptr()) };
+    if ret == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let (reader, writer) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    set_cloexec(fds[0], true)?;
+    set_cloexec(fds[1], true)?;
+
+    let fd = fds[1];
+    command.env(SECONDARY_TEST_RESULT_FD_VAR, fd.to_string());
+    unsafe {
+        command.pre_exec(move || set_cloexec(fd, false));
+    }
+    Ok((reader, writer))
+}
+
+#[cfg(not(unix))]
+fn result_pipe(_command: &mut Command) -> io::Result<(File, File)> {
+    Err(io::Error::new(io::ErrorKind::Other, "not supported on this platform"))
+}
+
+/// Opens the writing end of the result pipe in a re-executed test binary.
+#[cfg(unix)]
+fn take_result_pipe() -> io::Result<File> {
+    use std::os::unix::io::FromRawFd;
+
+    let fd = env::var(SECONDARY_TEST_RESULT_FD_VAR)
+        .ok()
+        .and_then(|fd| fd.parse::<libc::c_int>().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "no valid file descriptor given"))?;
+    env::remove_var(SECONDARY_TEST_RESULT_FD_VAR);
+    // Processes spawned by the test must not keep the pipe open.
+    set_cloexec(fd, true)?;
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+#[cfg(not(unix))]
+fn take_result_pipe() -> io::Result<File> {
+    Err(io::Error::new(io::ErrorKind::Other, "not supported on this platform"))
+}
+
+#[cfg(unix)]
+fn set_cloexec(fd: libc::c_int, cloexec: bool) -> io::Result<()> {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+        if flags == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let flags = if cloexec {
+            flags | libc::FD_CLOEXEC
+        } else {
+            flags & !libc::FD_CLOEXEC
+        };
+        if libc::fcntl(fd, libc::F_SETFD, flags) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
 /// Fixed frame used to clean the backtrace with `RUST_BACKTRACE=1`.
 #[inline(never)]
 fn __rust_begin_short_backtrace<F: FnOnce()>(f: F) {
     f()
 }
 
-fn calc_result(desc: &TestDesc, task_result: Result<(), Box<dyn Any + Send>>) -> TestResult {
-    match (&desc.should_panic, task_result) {
+fn calc_result(
+    desc: &TestDesc,
+    task_result: Result<(), Box<dyn Any + Send>>,
+    time_opts: &Option<TestTimeOptions>,
+    exec_time: &Option<TestExecTime>,
+) -> TestResult {
+    let result = match (&desc.should_panic, task_result) {
         (&ShouldPanic::No, Ok(())) | (&ShouldPanic::Yes, Err(_)) => TrOk,
         (&ShouldPanic::YesWithMessage(msg), Err(ref err)) => {
             if err
@@ -1543,7 +2244,121 @@ fn calc_result(desc: &TestDesc, task_result: Result<(), Box<dyn Any + Send>>) ->
         }
         _ if desc.allow_fail => TrAllowedFail,
         _ => TrFailed,
+    };
+
+    check_time_limit(result, time_opts, exec_time)
+}
+
+/// Computes the result of a test run by `spawn_test_subprocess` from the
+/// `report` it sent through the result pipe. A process that exited any other
+/// way than after sending a report is a failure, whatever its exit status.
+fn calc_result_from_report(
+    desc: &TestDesc,
+    status: ExitStatus,
+    report: &[u8],
+    time_opts: &Option<TestTimeOptions>,
+    exec_time: &Option<TestExecTime>,
+) -> TestResult {
+    let result = match parse_report(report) {
+        Some(result) if status.success() => result,
+        _ if desc.allow_fail => TrAllowedFail,
+        _ => match status.code() {
+            Some(code) => TrFailedMsg(format!(
+                "test process exited with status code {} without reporting a result",
+                code
+            )),
+            None => match exit_signal(&status) {
+                Some(signal) => TrFailedMsg(format!(
+                    "test process terminated by signal {}",
+                    fmt_signal(signal)
+                )),
+                None => TrFailedMsg("test process terminated abnormally".to_owned()),
+            },
+        },
+    };
+
+    check_time_limit(result, time_opts, exec_time)
+}
+
+/// Parses the report written by `run_test_in_spawned_subprocess`: the outcome
+/// on the first line, followed by the failure message if there is one.
+fn parse_report(report: &[u8]) -> Option<TestResult> {
+    let report = std::str::from_utf8(report).ok()?;
+    let mut lines = report.splitn(2, '\n');
+    match (lines.next()?, lines.next()) {
+        ("ok", None) => Some(TrOk),
+        ("allowed-fail", None) => Some(TrAllowedFail),
+        ("failed", None) => Some(TrFailed),
+        ("failed", Some(msg)) => Some(TrFailedMsg(msg.to_owned())),
+        _ => None,
+    }
+}
+
+fn calc_timed_out_result(desc: &TestDesc, timeout: Duration, killed: bool) -> TestResult {
+    if desc.allow_fail {
+        return TrAllowedFail;
+    }
+
+    let outcome = if killed {
+        "its process was killed"
+    } else {
+        "it was left running in the background"
+    };
+    TrFailedMsg(format!(
+        "test did not finish within {}s, {}",
+        timeout.as_secs(),
+        outcome
+    ))
+}
+
+/// Turns a passing test that took longer than the critical threshold into a
+/// failure when `--ensure-time` is used.
+fn check_time_limit(
+    result: TestResult,
+    time_opts: &Option<TestTimeOptions>,
+    exec_time: &Option<TestExecTime>,
+) -> TestResult {
+    // A test that already failed keeps its original, more useful, result.
+    if result != TrOk {
+        return result;
     }
+
+    match (time_opts, exec_time) {
+        (Some(opts), Some(time)) if opts.error_on_excess && opts.is_critical(time) => TrTimedFail,
+        _ => result,
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+fn fmt_signal(signal: i32) -> String {
+    let name = match signal {
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGILL => "SIGILL",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGTRAP => "SIGTRAP",
+        _ => return signal.to_string(),
+    };
+    format!("{} ({})", signal, name)
+}
+
+#[cfg(not(unix))]
+fn fmt_signal(signal: i32) -> String {
+    signal.to_string()
 }
 
 #[derive(Clone, PartialEq)]
@@ -1756,7 +2571,7 @@ pub mod bench {
         };
 
         let stdout = data.lock().unwrap().to_vec();
-        monitor_ch.send((desc, test_result, stdout)).unwrap();
+        monitor_ch.send((desc, test_result, None, stdout)).unwrap();
     }
 
     pub fn run_once<F>(f: F)
@@ -1777,12 +2592,16 @@ mod tests {
     use crate::bench;
     use crate::test::{
         filter_tests, parse_opts, run_test, DynTestFn, DynTestName, MetricMap, RunIgnored,
-        ShouldPanic, StaticTestName, TestDesc, TestDescAndFn, TestOpts, TrFailed, TrFailedMsg,
-        TrIgnored, TrOk,
+        ShouldPanic, StaticTestName, TestDesc, TestDescAndFn, TestOpts, TestTimeOptions, TrFailed,
+        TrFailedMsg, TrIgnored, TrOk, TrTimedFail,
     };
     use crate::Bencher;
     use crate::Concurrent;
+    use crate::OutputFormat;
+    use crate::{parse_report, run_tests, shuffle_tests, TestEvent, TrAllowedFail};
     use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
 
     fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
         vec![
@@ -1792,6 +2611,7 @@ mod tests {
                     ignore: true,
                     should_panic: ShouldPanic::No,
                     allow_fail: false,
+                    timeout: None,
                 },
                 testfn: DynTestFn(Box::new(move || {})),
             },
@@ -1801,6 +2621,7 @@ mod tests {
                     ignore: false,
                     should_panic: ShouldPanic::No,
                     allow_fail: false,
+                    timeout: None,
                 },
                 testfn: DynTestFn(Box::new(move || {})),
             },
@@ -1818,12 +2639,13 @@ mod tests {
                 ignore: true,
                 should_panic: ShouldPanic::No,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res != TrOk);
     }
 
@@ -1836,12 +2658,13 @@ mod tests {
                 ignore: true,
                 should_panic: ShouldPanic::No,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrIgnored);
     }
 
@@ -1856,12 +2679,13 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrOk);
     }
 
@@ -1876,12 +2700,13 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage("error message"),
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrOk);
     }
 
@@ -1898,12 +2723,13 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage(expected),
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrFailedMsg(format!("{} '{}'", failed_msg, expected)));
     }
 
@@ -1916,15 +2742,142 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
         let (tx, rx) = channel();
         run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
-        let (_, res, _) = rx.recv().unwrap();
+        let (_, res, _, _) = rx.recv().unwrap();
         assert!(res == TrFailed);
     }
 
+    #[test]
+    fn test_should_fail_on_critical_time() {
+        fn f() {}
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                timeout: None,
+            },
+            testfn: DynTestFn(Box::new(f)),
+        };
+        let opts = TestOpts {
+            time_options: Some(TestTimeOptions {
+                error_on_excess: true,
+                warn_threshold: Duration::from_millis(0),
+                critical_threshold: Duration::from_millis(0),
+            }),
+            ..TestOpts::new()
+        };
+        let (tx, rx) = channel();
+        run_test(&opts, false, desc, tx, Concurrent::No);
+        let (_, res, exec_time, _) = rx.recv().unwrap();
+        assert!(res == TrTimedFail);
+        assert!(exec_time.is_some());
+    }
+
+    #[test]
+    fn test_exec_time_not_recorded_by_default() {
+        fn f() {}
+        let desc = TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("whatever"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                timeout: None,
+            },
+            testfn: DynTestFn(Box::new(f)),
+        };
+        let (tx, rx) = channel();
+        run_test(&TestOpts::new(), false, desc, tx, Concurrent::No);
+        let (_, res, exec_time, _) = rx.recv().unwrap();
+        assert!(res == TrOk);
+        assert!(exec_time.is_none());
+    }
+
+    #[test]
+    fn parse_time_threshold() {
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            "--ensure-time".to_string(),
+            "--time-threshold=100,250".to_string(),
+        ];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert_eq!(
+            opts.time_options,
+            Some(TestTimeOptions {
+                error_on_excess: true,
+                warn_threshold: Duration::from_millis(100),
+                critical_threshold: Duration::from_millis(250),
+            })
+        );
+
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            "--report-time".to_string(),
+            "--time-threshold=250,100".to_string(),
+        ];
+        assert!(parse_opts(&args).unwrap().is_err());
+
+        let args = vec!["progname".to_string(), "--report-time".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_timeout_fails_hung_test() {
+        let mut opts = TestOpts::new();
+        opts.run_tests = true;
+        opts.test_threads = Some(1);
+
+        let tests = vec![
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("hangs"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    timeout: Some(1),
+                },
+                // Sleeps past the deadline, but still ends so that no thread
+                // outlives the test.
+                testfn: DynTestFn(Box::new(|| thread::sleep(Duration::from_secs(3)))),
+            },
+            TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName("passes"),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    timeout: None,
+                },
+                testfn: DynTestFn(Box::new(move || {})),
+            },
+        ];
+
+        let mut results = Vec::new();
+        run_tests(&opts, tests, |event| {
+            if let TestEvent::TeResult(desc, result, ..) = event {
+                results.push((desc.name.to_string(), result));
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "hangs");
+        let expected = "test did not finish within 1s, it was left running in the background";
+        assert!(results[0].1 == TrFailedMsg(expected.to_string()));
+        assert_eq!(results[1].0, "passes");
+        assert!(results[1].1 == TrOk);
+    }
+
     #[test]
     fn parse_ignored_flag() {
         let args = vec![
@@ -1948,6 +2901,20 @@ mod tests {
         assert_eq!(opts.run_ignored, RunIgnored::Yes);
     }
 
+    #[test]
+    fn parse_junit_format_needs_unstable_options() {
+        let args = vec!["progname".to_string(), "--format=junit".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            "--format=junit".to_string(),
+        ];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert_eq!(opts.format, OutputFormat::Junit);
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -1995,6 +2962,7 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || {})),
         });
@@ -2016,6 +2984,7 @@ mod tests {
                         ignore: false,
                         should_panic: ShouldPanic::No,
                         allow_fail: false,
+                        timeout: None,
                     },
                     testfn: DynTestFn(Box::new(move || {})),
                 })
@@ -2127,6 +3096,7 @@ mod tests {
                         ignore: false,
                         should_panic: ShouldPanic::No,
                         allow_fail: false,
+                        timeout: None,
                     },
                     testfn: DynTestFn(Box::new(testfn)),
                 };
@@ -2155,6 +3125,70 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn shuffle_tests_is_reproducible() {
+        fn tests() -> Vec<TestDescAndFn> {
+            (0..20)
+                .map(|i| TestDescAndFn {
+                    desc: TestDesc {
+                        name: DynTestName(format!("test_{:02}", i)),
+                        ignore: false,
+                        should_panic: ShouldPanic::No,
+                        allow_fail: false,
+                        timeout: None,
+                    },
+                    testfn: DynTestFn(Box::new(move || {})),
+                })
+                .collect()
+        }
+        fn names(tests: &[TestDescAndFn]) -> Vec<String> {
+            tests.iter().map(|t| t.desc.name.to_string()).collect()
+        }
+
+        let sorted = names(&tests());
+        let first = names(&shuffle_tests(42, tests()));
+        let second = names(&shuffle_tests(42, tests()));
+        let other_seed = names(&shuffle_tests(43, tests()));
+
+        assert_eq!(first, second);
+        assert!(first != sorted);
+        assert!(first != other_seed);
+
+        // The order for a given seed must never change, or recorded seeds
+        // would stop reproducing the runs they came from.
+        assert_eq!(&first[..5], ["test_16", "test_03", "test_08", "test_11", "test_17"]);
+
+        let mut first = first;
+        first.sort();
+        assert_eq!(first, sorted);
+    }
+
+    #[test]
+    fn parse_subprocess_report() {
+        assert!(parse_report(b"ok") == Some(TrOk));
+        assert!(parse_report(b"allowed-fail") == Some(TrAllowedFail));
+        assert!(parse_report(b"failed") == Some(TrFailed));
+        let msg = TrFailedMsg("some\nmessage".into());
+        assert!(parse_report(b"failed\nsome\nmessage") == Some(msg));
+        assert!(parse_report(b"") == None);
+        assert!(parse_report(b"ok\n") == None);
+        assert!(parse_report(b"\xff") == None);
+    }
+
+    #[test]
+    fn parse_shuffle_seed() {
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            "--shuffle-seed=1234".to_string(),
+        ];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert_eq!(opts.shuffle_seed, Some(1234));
+
+        let args = vec!["progname".to_string(), "--shuffle".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
     #[test]
     pub fn test_metricmap_compare() {
         let mut m1 = MetricMap::new();
@@ -2203,6 +3237,7 @@ mod tests {
             ignore: false,
             should_panic: ShouldPanic::No,
             allow_fail: false,
+            timeout: None,
         };
 
         crate::bench::benchmark(desc, tx, true, f);
@@ -2222,6 +3257,7 @@ mod tests {
             ignore: false,
             should_panic: ShouldPanic::No,
             allow_fail: false,
+            timeout: None,
         };
 
         crate::bench::benchmark(desc, tx, true, f);
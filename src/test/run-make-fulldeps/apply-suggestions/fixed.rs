@@ -0,0 +1,5 @@
+fn main() {
+    let a = 1 + 2;
+    let b = a * (a + 1);
+    println!("{}", b);
+}
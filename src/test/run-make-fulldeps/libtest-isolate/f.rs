@@ -0,0 +1,27 @@
+#[test]
+fn passes() {
+    // Should pass
+}
+
+#[test]
+fn aborts() {
+    std::process::abort();
+}
+
+#[test]
+fn exits() {
+    // Exiting successfully without reporting a result is not a pass.
+    std::process::exit(0);
+}
+
+#[test]
+#[should_panic]
+fn panics() {
+    assert!(false);
+}
+
+#[test]
+#[should_panic(expected = "foo")]
+fn panics_with_wrong_message() {
+    panic!("bar");
+}
@@ -0,0 +1,19 @@
+#!/usr/bin/env python
+
+import sys
+import xml.etree.ElementTree as ET
+
+# Try to decode the whole report in order to ensure it is a valid XML document,
+# then check that the counts match the tests in f.rs
+suite = ET.parse(sys.stdin).getroot().find('testsuite')
+assert suite.get('tests') == '4'
+assert suite.get('failures') == '1'
+assert suite.get('skipped') == '1'
+
+cases = dict((case.get('name'), case) for case in suite.findall('testcase'))
+assert sorted(cases) == ['a', 'b', 'c', 'd']
+assert cases['b'].find('failure') is not None
+assert 'assertion failed' in cases['b'].find('system-out').text
+assert cases['d'].find('skipped') is not None
+for case in cases.values():
+    float(case.get('time'))
@@ -0,0 +1,6 @@
+fn main() {
+    if true {
+        let a =   1;
+    }
+    let b = 2;
+}
@@ -0,0 +1,9 @@
+#![crate_type = "lib"]
+
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+pub fn use_generic() -> (u8, u16, u32) {
+    (generic(), generic(), generic())
+}
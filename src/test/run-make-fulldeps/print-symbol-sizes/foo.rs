@@ -0,0 +1,11 @@
+#[inline(never)]
+fn answer(x: u64) -> u64 {
+    x * 6 + 1
+}
+
+fn main() {
+    let answer = answer(7);
+    // Formats through `<bool as core::fmt::Display>::fmt` and
+    // `<&T as core::fmt::Display>::fmt`.
+    println!("{} {}", answer == 43, &answer);
+}
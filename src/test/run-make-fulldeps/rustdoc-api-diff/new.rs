@@ -0,0 +1,55 @@
+#![crate_name = "api"]
+
+use std::marker::PhantomData;
+
+pub struct Config {
+    pub retries: u64,
+}
+
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn name(&self) -> String;
+}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+    cached: bool,
+}
+
+pub mod shapes {
+    pub struct Circle;
+}
+
+pub struct Handle {
+    _private: PhantomData<*const ()>,
+}
+
+pub fn double(value: u32) -> u32 {
+    value * 2
+}
+
+pub fn triple(x: u32) -> u32 {
+    x * 3
+}
+
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+pub enum Event {
+    Click { x: i32, y: i32 },
+}
+
+pub struct Buffer {
+    pub len: usize,
+    pub capacity: usize,
+    data: Vec<u8>,
+}
@@ -0,0 +1,48 @@
+#![crate_name = "api"]
+
+pub struct Config {
+    pub retries: u32,
+}
+
+pub enum Color {
+    Red,
+    Green,
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub mod shapes {
+    pub struct Circle;
+}
+
+pub use shapes::Circle;
+
+pub struct Handle {
+    _private: (),
+}
+
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
+
+pub fn legacy() {}
+
+pub struct Size {
+    pub width: u32,
+}
+
+pub enum Event {
+    Click { x: i32 },
+}
+
+pub struct Buffer {
+    pub len: usize,
+    data: Vec<u8>,
+}
@@ -0,0 +1,22 @@
+//! The crate root is documented, but has no code example.
+
+/// A documented function with a code example.
+///
+/// ```
+/// foo::documented();
+/// ```
+pub fn documented() {}
+
+/// A documented struct without a code example.
+pub struct Documented {
+    pub field: u32,
+}
+
+impl Documented {
+    pub fn method(&self) {}
+}
+
+pub mod inner {
+    /// A documented function in an undocumented module.
+    pub fn nested() {}
+}
@@ -0,0 +1,10 @@
+/// Sends a message to the queue.
+///
+/// Applies backpressure when the queue is full, which is a form of throttling.
+///
+/// ```
+/// let hidden_in_code = 1;
+/// ```
+///
+/// Whatever happens, the message is not lost—even with a naïve consumer.
+pub fn send() {}
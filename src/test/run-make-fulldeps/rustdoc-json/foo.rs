@@ -0,0 +1,21 @@
+#![crate_name = "foo"]
+
+/// A documented struct.
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Clone> Point<T> {
+    /// Swaps the coordinates.
+    pub fn swap(&self) -> Point<T> {
+        Point { x: self.y.clone(), y: self.x.clone() }
+    }
+}
+
+pub mod inner {
+    /// Adds one.
+    pub fn add_one(x: u32) -> u32 {
+        x + 1
+    }
+}
@@ -0,0 +1,15 @@
+#![crate_name = "bar"]
+
+/// ```
+/// #[no_mangle]
+/// pub extern "C" fn bar_hook() -> u32 { 1 }
+/// assert_eq!(bar_hook(), 1);
+/// ```
+pub fn first() {}
+
+/// ```
+/// #[no_mangle]
+/// pub extern "C" fn bar_hook() -> u32 { 2 }
+/// assert_eq!(bar_hook(), 2);
+/// ```
+pub fn second() {}
@@ -0,0 +1,18 @@
+#![crate_name = "foo"]
+
+/// ```
+/// assert_eq!(foo::add(1, 2), 3);
+/// ```
+///
+/// ```should_panic
+/// foo::add(1, 2);
+/// panic!("expected");
+/// ```
+pub fn add(a: u32, b: u32) -> u32 { a + b }
+
+/// ```
+/// use foo::add;
+///
+/// assert_eq!(add(2, 2), 5);
+/// ```
+pub fn failing() {}
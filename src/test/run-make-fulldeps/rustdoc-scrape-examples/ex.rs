@@ -0,0 +1,9 @@
+extern crate foo;
+
+fn main() {
+    let start = 41;
+    let answer = foo::add_one(start);
+    let mut counter = foo::Counter::new();
+    counter.bump();
+    println!("{} {}", answer, counter.bump());
+}
@@ -0,0 +1,27 @@
+#![crate_name = "foo"]
+
+/// Adds one to `x`.
+pub fn add_one(x: u32) -> u32 {
+    x + 1
+}
+
+/// Not called by the examples.
+pub fn unused() {}
+
+/// Counts things.
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    /// Creates a counter starting at zero.
+    pub fn new() -> Counter {
+        Counter { count: 0 }
+    }
+
+    /// Counts one more thing.
+    pub fn bump(&mut self) -> u32 {
+        self.count = add_one(self.count);
+        self.count
+    }
+}
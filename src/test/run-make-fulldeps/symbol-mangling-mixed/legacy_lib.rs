@@ -0,0 +1,16 @@
+#![crate_type = "rlib"]
+
+pub static GREETING: &str = "hello";
+
+pub fn answer() -> u32 {
+    42
+}
+
+pub fn sum<T: Copy + Into<u64>>(values: &[T]) -> u64 {
+    values.iter().map(|&v| v.into()).sum()
+}
+
+#[inline]
+pub fn twice(x: u32) -> u32 {
+    x * 2
+}
@@ -0,0 +1,15 @@
+extern crate legacy_lib;
+extern crate v0_lib;
+
+use std::collections::HashMap;
+
+fn main() {
+    let mut counts = HashMap::new();
+    for word in legacy_lib::GREETING.split('l').chain(v0_lib::GREETING.split('o')) {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+    let total = legacy_lib::answer() + v0_lib::answer() +
+                legacy_lib::twice(1) + v0_lib::twice(2) +
+                legacy_lib::sum(&[1u8, 2]) as u32 + v0_lib::sum(&[3u16, 4]) as u32;
+    println!("total: {}, words: {}", total, counts.len());
+}
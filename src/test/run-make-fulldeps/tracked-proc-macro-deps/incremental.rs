@@ -0,0 +1,8 @@
+#[macro_use]
+extern crate macro_def;
+
+tracked_greeting!();
+
+fn main() {
+    println!("{}", greeting());
+}
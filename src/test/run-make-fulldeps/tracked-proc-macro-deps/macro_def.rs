@@ -0,0 +1,27 @@
+#![feature(proc_macro_tracked_env, proc_macro_tracked_path)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+
+use proc_macro::{tracked_env, tracked_path, TokenStream};
+
+#[proc_macro]
+pub fn access_tracked_inputs(_: TokenStream) -> TokenStream {
+    assert_eq!(tracked_env::var("EXISTING_PROC_MACRO_ENV"), Ok("1".to_string()));
+    assert!(tracked_env::var("NONEXISTENT_PROC_MACRO_ENV").is_err());
+    tracked_path::path("schema.txt");
+    TokenStream::new()
+}
+
+// Expands to a function returning the value of `PROC_MACRO_GREETING` followed
+// by the contents of `greeting.txt`, both read through the tracked interfaces.
+#[proc_macro]
+pub fn tracked_greeting(_: TokenStream) -> TokenStream {
+    let greeting = tracked_env::var("PROC_MACRO_GREETING").unwrap();
+    let path = format!("{}/greeting.txt", tracked_env::var("TMPDIR").unwrap());
+    tracked_path::path(&path);
+    let name = std::fs::read_to_string(&path).unwrap();
+    format!("fn greeting() -> &'static str {{ {:?} }}", greeting + " " + name.trim())
+        .parse()
+        .unwrap()
+}
@@ -0,0 +1,33 @@
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+#![feature(proc_macro_expand)]
+
+extern crate proc_macro;
+
+use proc_macro::*;
+
+/// Checks that the expression after the comma expands to the tokens before it.
+#[proc_macro]
+pub fn expand_expr_is(input: TokenStream) -> TokenStream {
+    let mut iter = input.into_iter();
+    let mut expected = Vec::new();
+    loop {
+        match iter.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == ',' => break,
+            Some(tt) => expected.push(tt),
+            None => panic!("expected a comma"),
+        }
+    }
+    let expected = expected.into_iter().collect::<TokenStream>();
+    let expanded = iter.collect::<TokenStream>().expand_expr().expect("failed to expand");
+    assert_eq!(expected.to_string(), expanded.to_string());
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn expand_expr_fails(input: TokenStream) -> TokenStream {
+    assert!(input.expand_expr().is_err());
+    TokenStream::new()
+}
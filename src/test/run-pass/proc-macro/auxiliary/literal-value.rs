@@ -0,0 +1,74 @@
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+#![feature(proc_macro_literal_value)]
+
+extern crate proc_macro;
+
+use proc_macro::*;
+
+fn literals(input: TokenStream) -> Vec<Literal> {
+    input.into_iter().map(|tt| match tt {
+        TokenTree::Literal(lit) => lit,
+        _ => panic!("expected a literal, found `{}`", tt),
+    }).collect()
+}
+
+#[proc_macro]
+pub fn check_source_literals(input: TokenStream) -> TokenStream {
+    let lits = literals(input);
+    assert_eq!(lits.len(), 11);
+
+    assert_eq!(lits[0].kind(), LiteralKind::Str);
+    assert_eq!(lits[0].str_value().as_ref().map(|s| &s[..]), Some("a\n\u{e9}\"b"));
+    assert_eq!(lits[0].int_value(), None);
+
+    assert_eq!(lits[1].kind(), LiteralKind::StrRaw(1));
+    assert_eq!(lits[1].str_value().as_ref().map(|s| &s[..]), Some("a\\n\"b"));
+
+    assert_eq!(lits[2].kind(), LiteralKind::ByteStr);
+    assert_eq!(lits[2].byte_str_value(), Some(b"\x01\xff".to_vec()));
+    assert_eq!(lits[2].str_value(), None);
+
+    assert_eq!(lits[3].kind(), LiteralKind::Char);
+    assert_eq!(lits[3].char_value(), Some('\u{1f980}'));
+
+    assert_eq!(lits[4].kind(), LiteralKind::Byte);
+    assert_eq!(lits[4].byte_value(), Some(b'\t'));
+
+    assert_eq!(lits[5].kind(), LiteralKind::Integer);
+    assert_eq!(lits[5].int_value(), Some(0x1f));
+    assert_eq!(lits[5].suffix().as_ref().map(|s| &s[..]), Some("u8"));
+
+    assert_eq!(lits[6].int_value(), Some(1_000_000));
+    assert_eq!(lits[6].suffix(), None);
+
+    assert_eq!(lits[7].kind(), LiteralKind::Float);
+    assert_eq!(lits[7].float_value(), Some(25.0));
+    assert_eq!(lits[7].int_value(), None);
+
+    assert_eq!(lits[8].kind(), LiteralKind::Integer);
+    assert_eq!(lits[8].float_value(), Some(3.0));
+    assert_eq!(lits[8].int_value(), None);
+
+    // Suffixes the compiler would reject are fine in the input of a proc macro.
+    assert_eq!(lits[9].str_value().as_ref().map(|s| &s[..]), Some("x"));
+    assert_eq!(lits[9].suffix().as_ref().map(|s| &s[..]), Some("px"));
+    assert_eq!(lits[10].int_value(), Some(10));
+    assert_eq!(lits[10].suffix().as_ref().map(|s| &s[..]), Some("px"));
+
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn check_constructed_literals(_input: TokenStream) -> TokenStream {
+    assert_eq!(Literal::string("a\n\"b").str_value().as_ref().map(|s| &s[..]), Some("a\n\"b"));
+    assert_eq!(Literal::byte_string(b"\0\xff").byte_str_value(), Some(b"\0\xff".to_vec()));
+    assert_eq!(Literal::character('\'').char_value(), Some('\''));
+    assert_eq!(Literal::u64_suffixed(u64::max_value()).int_value(),
+               Some(u64::max_value() as u128));
+    assert_eq!(Literal::f64_unsuffixed(0.5).float_value(), Some(0.5));
+
+    TokenStream::new()
+}
@@ -0,0 +1,24 @@
+// aux-build:expand-expr.rs
+
+extern crate expand_expr;
+
+use expand_expr::*;
+
+expand_expr_is!("ab", concat!("a", "b"));
+expand_expr_is!("a1true", concat!("a", 1, true));
+expand_expr_is!("hello", stringify!(hello));
+expand_expr_is!("1", concat!(stringify!(1)));
+expand_expr_is!(42u8, 42u8);
+expand_expr_is!(-1, -1);
+expand_expr_is!(true, true);
+
+macro_rules! echo {
+    ($e:expr) => { $e };
+}
+
+expand_expr_is!("nested", echo!(concat!("nest", "ed")));
+
+expand_expr_fails!(1 + 1);
+expand_expr_fails!(std::u32::MAX);
+
+fn main() {}
@@ -0,0 +1,23 @@
+// aux-build:literal-value.rs
+
+extern crate literal_value;
+
+use literal_value::*;
+
+check_source_literals!(
+    "a\n\u{e9}\"b"
+    r#"a\n"b"#
+    b"\x01\xff"
+    '\u{1f980}'
+    b'\t'
+    0x1_f_u8
+    1_000_000
+    2.5e1
+    3f32
+    "x"px
+    10px
+);
+
+check_constructed_literals!();
+
+fn main() {}
@@ -0,0 +1,14 @@
+// ignore-emscripten no threads support
+// compile-flags: --test
+#![feature(test_timeout)]
+
+#[test(timeout = "60")]
+fn test1() {
+    assert!(true);
+}
+
+#[test(timeout = "60")]
+#[should_panic]
+fn test2() {
+    panic!();
+}
@@ -0,0 +1,7 @@
+// compile-flags: --test
+// check that #[test(timeout = "...")] is feature-gated
+
+#[test(timeout = "5")] //~ ERROR `#[test(timeout = "...")]` is currently unstable
+fn takes_a_while() {
+    std::thread::sleep(std::time::Duration::from_secs(1));
+}
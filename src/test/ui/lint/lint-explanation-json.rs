@@ -0,0 +1,14 @@
+// compile-flags: --error-format pretty-json -Zunstable-options
+// compile-pass
+
+// Lints with a long-form explanation carry it in the `code.explanation` field
+// of the JSON output, just like error codes do.
+
+#[must_use]
+fn parse() -> u32 {
+    12
+}
+
+fn main() {
+    parse();
+}
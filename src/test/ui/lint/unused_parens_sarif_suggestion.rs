@@ -0,0 +1,12 @@
+// compile-flags: --error-format sarif -Zunstable-options
+// compile-pass
+
+// Checks that a lint with a machine-applicable suggestion is reported as a
+// SARIF result, with the lint level note as a related location and the
+// suggestion as a fix.
+
+#![warn(unused_parens)]
+
+fn main() {
+    let _a = (1 / (2 + 3));
+}
@@ -0,0 +1,31 @@
+// compile-flags: -Z print-type-sizes -Z print-type-sizes-json
+// compile-pass
+
+// This file illustrates the JSON output of `-Z print-type-sizes`, which
+// includes the padding of each variant and the niches of each type.
+
+#![feature(start)]
+
+#[repr(C)]
+pub struct Padded {
+    a: u8,
+    b: u32,
+}
+
+pub enum Tagged {
+    Small(u8),
+    Large(u32),
+}
+
+pub enum MaybeFlag {
+    Nothing,
+    Flag(bool),
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    let _p: Padded;
+    let _t: Tagged;
+    let _m: MaybeFlag;
+    0
+}
@@ -0,0 +1,49 @@
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+#![feature(proc_macro_diagnostic)]
+
+extern crate proc_macro;
+
+use proc_macro::*;
+
+#[proc_macro]
+pub fn uppercase_constant(input: TokenStream) -> TokenStream {
+    for tt in input {
+        let name = tt.to_string();
+        if name != name.to_uppercase() {
+            let mut diag = Diagnostic::spanned(
+                tt.span(),
+                Level::Warning,
+                "constant names should be uppercase",
+            ).span_suggestion(
+                tt.span(),
+                "use uppercase",
+                name.to_uppercase(),
+                Applicability::MachineApplicable,
+            );
+            diag.set_code("PM0001");
+            diag.emit();
+        }
+    }
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn lint_warning(input: TokenStream) -> TokenStream {
+    let span = input.into_iter().next().unwrap().span();
+    Diagnostic::spanned(span, Level::Lint, "warning from a proc macro")
+        .note("reported by `lint_warning!`")
+        .emit();
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn empty_suggestion(input: TokenStream) -> TokenStream {
+    let span = input.into_iter().next().unwrap().span();
+    Diagnostic::spanned(span, Level::Warning, "suggestion without replacements")
+        .multipart_suggestion("do nothing", vec![], Applicability::MachineApplicable)
+        .emit();
+    TokenStream::new()
+}
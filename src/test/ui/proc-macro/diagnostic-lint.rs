@@ -0,0 +1,27 @@
+// aux-build:diagnostic-suggestion.rs
+
+// Checks that warnings reported with `Level::Lint` respect lint attributes.
+
+extern crate diagnostic_suggestion;
+
+use diagnostic_suggestion::lint_warning;
+
+lint_warning!(a);
+//~^ WARN warning from a proc macro
+
+#[allow(proc_macro_warnings)]
+mod allowed {
+    use diagnostic_suggestion::lint_warning;
+
+    lint_warning!(b);
+}
+
+#[deny(proc_macro_warnings)]
+mod denied {
+    use diagnostic_suggestion::lint_warning;
+
+    lint_warning!(c);
+    //~^ ERROR warning from a proc macro
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+// aux-build:diagnostic-suggestion.rs
+// run-rustfix
+// compile-pass
+
+extern crate diagnostic_suggestion;
+
+use diagnostic_suggestion::*;
+
+uppercase_constant!(foo);
+//~^ WARN constant names should be uppercase
+
+empty_suggestion!(bar);
+//~^ WARN suggestion without replacements
+
+fn main() {}
@@ -0,0 +1,14 @@
+// compile-flags: -Z sort-diagnostics -Z diagnostic-summary
+// compile-pass
+
+// Checks that diagnostics are emitted in source order rather than in the order
+// the passes report them, and that the summary counts them per lint and file.
+
+#![warn(unused_parens, unused_variables)]
+
+fn main() {
+    let x = 1;
+    let _a = (1 / (2 + 3));
+}
+
+fn unused() {}
@@ -0,0 +1,63 @@
+// ignore-tidy-linelength
+// compile-flags: -Z symbol-mangling-version=v0
+// normalize-stderr-test: "Cs[0-9a-zA-Z]+_" -> "Cs[HASH]_"
+// normalize-stderr-test: "\[[0-9a-f]+\]" -> "[HASH]"
+
+#![feature(non_ascii_idents, optin_builtin_traits, rustc_attrs)]
+#![allow(dead_code)]
+
+mod foo {
+    pub struct Foo { x: u32 }
+
+    impl Foo {
+        #[rustc_symbol_name]
+        //~^ ERROR _11mangling_v03fooNtB2_3Foo3bar)
+        //~| ERROR ]::foo::Foo>::bar)
+        //~| ERROR demangling-alt(<mangling_v0::foo::Foo>::bar)
+        fn bar() { }
+    }
+}
+
+mod bar {
+    use foo::Foo;
+
+    impl Foo {
+        #[rustc_symbol_name]
+        //~^ ERROR _11mangling_v03barNtNtB4_3foo3Foo3baz)
+        //~| ERROR ]::foo::Foo>::baz)
+        //~| ERROR demangling-alt(<mangling_v0::foo::Foo>::baz)
+        fn baz() { }
+    }
+}
+
+// Identifiers that aren't ASCII are encoded with Punycode.
+#[rustc_symbol_name]
+//~^ ERROR _11mangling_v0u9bcher_kva)
+//~| ERROR ]::bücher)
+//~| ERROR demangling-alt(mangling_v0::bücher)
+fn bücher() { }
+
+trait Foo {
+    type Assoc;
+}
+
+auto trait AutoTrait {}
+
+fn main() {
+    // Test closure mangling, and disambiguators.
+    || {};
+    || {
+        trait Bar {
+            fn method(&self) {}
+        }
+
+        // Test type mangling, by putting them in an `impl` header.
+        impl Bar for [&(dyn Foo<Assoc = extern "C" fn(&u8, ...)> + AutoTrait); 3] {
+            #[rustc_symbol_name]
+            //~^ ERROR _11mangling_v04mains_0ARDNtB6_3Foop5AssocFG_KCRL0_hvEuNtB6_9AutoTraitEL_j3_NtB2_3Bar6method)
+            //~| ERROR ]::main::{closure#1}::Bar>::method)
+            //~| ERROR demangling-alt(<[&dyn mangling_v0::Foo<Assoc = for<'a> extern "C" fn(&'a u8, ...)> + mangling_v0::AutoTrait; 3] as mangling_v0::main::{closure#1}::Bar>::method)
+            fn method(&self) {}
+        }
+    };
+}
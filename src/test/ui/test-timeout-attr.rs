@@ -0,0 +1,17 @@
+// compile-flags: --test
+#![feature(test_timeout)]
+
+#[test(timeout = "0")]
+//~^ ERROR: argument must be of the form:
+fn test1() {}
+
+#[test(timeout = 5)]
+//~^ ERROR: argument must be of the form:
+fn test2() {}
+
+#[test(timeout = "5", timeout = "10")]
+//~^ ERROR: argument must be of the form:
+fn test3() {}
+
+#[test(timeout = "5")]
+fn test4() {}
@@ -542,6 +542,11 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         test_threads: None,
         skip: vec![],
         list: false,
+        shuffle: false,
+        shuffle_seed: None,
+        isolate: false,
+        test_timeout: None,
+        time_options: None,
         options: test::Options::new(),
     }
 }
@@ -678,6 +683,7 @@ pub fn make_test(config: &Config, testpaths: &TestPaths) -> Vec<test::TestDescAn
                     ignore,
                     should_panic,
                     allow_fail: false,
+                    timeout: None,
                 },
                 testfn: make_test_closure(config, early_props.ignore, testpaths, revision),
             }